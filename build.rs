@@ -1,5 +1,40 @@
+extern crate alloc;
+
 use bootloader;
-use std::path::PathBuf;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf}
+};
+
+/// The portfolio section format, shared with the kernel so they can't disagree about it.
+#[path = "kernel/src/content/section.rs"]
+mod section;
+
+/// The manual page format, shared with the kernel in the same way.
+#[path = "kernel/src/content/man.rs"]
+mod man;
+
+/// The names and aliases of the builtin commands, shared with the kernel in the same way. Only
+/// the list is needed here.
+#[path = "kernel/src/shell/commands/names.rs"]
+#[allow(dead_code)]
+mod names;
+
+/// The directory whose contents become the initramfs.
+const INITRAMFS_DIR: &str = "initramfs";
+
+/// The directory of the initramfs the portfolio sections are in.
+const SECTIONS_DIR: &str = "portfolio/";
+
+/// The directory of the initramfs the manual pages are in.
+const MAN_DIR: &str = "man/";
+
+/// The colors `@color` accepts, as named by the kernel's `ConsoleColor::name`.
+const COLORS: [&str; 16] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    "brightblack", "brightred", "brightgreen", "brightyellow", "brightblue", "brightmagenta", "brightcyan", "brightwhite",
+];
 
 fn main() {
     // Cargo provides the OUT_DIR for build artifacts.
@@ -13,8 +48,12 @@ fn main() {
     // Set a separate variable for clarity; KERNEL_ELF is used by LLDB.
     println!("cargo:rustc-env=KERNEL_ELF={}", kernel.to_str().unwrap());
 
-    let mut disk_image = bootloader::DiskImageBuilder::new(kernel);  
-    
+    let initramfs_path = out_dir.join("initramfs.cpio");
+    build_initramfs(Path::new(INITRAMFS_DIR), &initramfs_path);
+
+    let mut disk_image = bootloader::DiskImageBuilder::new(kernel);
+    disk_image.set_ramdisk(initramfs_path);
+
     disk_image.set_file_contents(
         "a_file.txt".to_string(),
         "Hello, world!".as_bytes().to_vec(),
@@ -40,5 +79,120 @@ fn main() {
     // Rerun build if relevant sources change.
     println!("cargo:rerun-if-changed=kernel/src/*.rs");
     println!("cargo:rerun-if-changed=src/*.rs");
+    println!("cargo:rerun-if-changed={}", INITRAMFS_DIR);
 }
 
+/// Packs every file under `source` into a `newc` cpio archive at `archive`, for the bootloader to
+/// load as the ramdisk. The portfolio sections and manual pages are checked first, and the build
+/// fails if any of them is broken.
+fn build_initramfs(source: &Path, archive: &Path) {
+    let mut files = Vec::new();
+    collect_files(source, "", &mut files);
+    files.sort();
+    check_sections(&files);
+    check_man_pages(&files);
+
+    let mut cpio = Vec::new();
+    for (ino, (path, data)) in files.iter().enumerate() {
+        write_cpio_entry(&mut cpio, ino + 1, path, 0o100644, data);
+    }
+    write_cpio_entry(&mut cpio, 0, "TRAILER!!!", 0, &[]);
+    fs::write(archive, cpio).expect("Failed to write the initramfs");
+}
+
+/// Reads every file under `dir` into `files`, with its path relative to the initramfs root.
+fn collect_files(dir: &Path, prefix: &str, files: &mut Vec<(String, Vec<u8>)>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries {
+        let entry = entry.expect("Failed to read the initramfs directory");
+        let name = entry.file_name().into_string().expect("initramfs file names must be UTF-8");
+        let path = format!("{prefix}{name}");
+        if entry.path().is_dir() {
+            collect_files(&entry.path(), &format!("{path}/"), files);
+        } else {
+            files.push((path, fs::read(entry.path()).expect("Failed to read an initramfs file")));
+        }
+    }
+}
+
+/// Checks that every portfolio section parses, uses real colors, and has a name and aliases that
+/// no builtin command or other section has. Panics, failing the build, with every problem found
+/// if not.
+fn check_sections(files: &[(String, Vec<u8>)]) {
+    let mut problems = Vec::new();
+    let mut names: HashMap<String, String> = HashMap::new();
+    for (name, aliases) in names::BUILTINS {
+        for name in std::iter::once(name).chain(aliases.iter()) {
+            names.insert(name.to_string(), String::from("a builtin command"));
+        }
+    }
+    let sections = files
+        .iter()
+        .filter(|(path, _)| path.starts_with(SECTIONS_DIR) && path.ends_with(".txt"));
+    for (path, data) in sections {
+        let Ok(text) = std::str::from_utf8(data) else {
+            problems.push(format!("{INITRAMFS_DIR}/{path}: not UTF-8"));
+            continue;
+        };
+        let section = match section::parse(text) {
+            Ok(section) => section,
+            Err(error) => {
+                problems.push(format!("{INITRAMFS_DIR}/{path}:{}: {}", error.line, error.message));
+                continue;
+            }
+        };
+        for line in &section.body {
+            if let section::Line::Color(Some(color)) = line {
+                if !COLORS.contains(&color.to_ascii_lowercase().as_str()) {
+                    problems.push(format!("{INITRAMFS_DIR}/{path}: unknown color `{color}`"));
+                }
+            }
+        }
+        for name in std::iter::once(&section.name).chain(&section.aliases) {
+            if let Some(other) = names.insert(name.to_string(), path.clone()) {
+                problems.push(format!("{INITRAMFS_DIR}/{path}: `{name}` is already used by {other}"));
+            }
+        }
+    }
+    if !problems.is_empty() {
+        panic!("Broken portfolio sections:\n{}", problems.join("\n"));
+    }
+}
+
+/// Appends a file to a `newc` cpio archive.
+fn write_cpio_entry(cpio: &mut Vec<u8>, ino: usize, name: &str, mode: u32, data: &[u8]) {
+    // ino, mode, uid, gid, nlink, mtime, filesize, devmajor, devminor, rdevmajor, rdevminor,
+    // namesize (counting the NUL) and check.
+    let fields = [ino, mode as usize, 0, 0, 1, 0, data.len(), 0, 0, 0, 0, name.len() + 1, 0];
+    cpio.extend_from_slice(b"070701");
+    for field in fields {
+        cpio.extend_from_slice(format!("{field:08x}").as_bytes());
+    }
+    cpio.extend_from_slice(name.as_bytes());
+    cpio.push(0);
+    cpio.resize(cpio.len().next_multiple_of(4), 0);
+    cpio.extend_from_slice(data);
+    cpio.resize(cpio.len().next_multiple_of(4), 0);
+}
+
+
+/// Checks that every manual page parses. Panics, failing the build, with every problem found if
+/// not.
+fn check_man_pages(files: &[(String, Vec<u8>)]) {
+    let mut problems = Vec::new();
+    let pages = files
+        .iter()
+        .filter_map(|(path, data)| Some((path, path.strip_prefix(MAN_DIR)?.strip_suffix(".md")?, data)));
+    for (path, name, data) in pages {
+        let Ok(text) = std::str::from_utf8(data) else {
+            problems.push(format!("{INITRAMFS_DIR}/{path}: not UTF-8"));
+            continue;
+        };
+        if let Err(message) = man::parse(name, text) {
+            problems.push(format!("{INITRAMFS_DIR}/{path}: {message}"));
+        }
+    }
+    if !problems.is_empty() {
+        panic!("Broken manual pages:\n{}", problems.join("\n"));
+    }
+}
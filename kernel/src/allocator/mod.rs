@@ -37,3 +37,14 @@ pub fn init_heap(
 
     Ok(())
 }
+
+/// Returns how many bytes of the heap are in use.
+pub fn heap_used() -> usize {
+    ALLOCATOR.lock().used()
+}
+
+/// Returns true if nothing holds the heap's lock. With interrupts off and everything else
+/// stopped, as in the debugger, nothing can take it afterwards either.
+pub fn heap_is_free() -> bool {
+    ALLOCATOR.try_lock().is_some()
+}
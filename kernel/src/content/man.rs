@@ -0,0 +1,63 @@
+//! The format of a manual page, as `man` shows it.
+//!
+//! A page is a Markdown file named after its command, like `ls.md`, and starts with a level one
+//! heading giving the command's name and a one-line summary, which `apropos` searches:
+//!
+//! ```text
+//! # ls - list files
+//!
+//! ## SYNOPSIS
+//!
+//! ~~~
+//! ls [-a] [directory]
+//! ~~~
+//!
+//! ## DESCRIPTION
+//!
+//! Lists the files in a directory...
+//! ```
+//!
+//! Every page has a `SYNOPSIS` and a `DESCRIPTION`. `OPTIONS` and `EXAMPLES` are up to the page.
+//!
+//! This file is also compiled into the build script, which checks every page before it goes into
+//! the initramfs, so it must only use `core` and `alloc`.
+use alloc::{format, string::String};
+
+/// The sections every page must have.
+const REQUIRED_SECTIONS: [&str; 2] = ["SYNOPSIS", "DESCRIPTION"];
+
+/// A parsed manual page. It borrows from the text it was parsed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page<'a> {
+    /// The name of the command the page is about.
+    pub name: &'a str,
+    pub summary: &'a str,
+    /// The whole page, as Markdown.
+    pub text: &'a str,
+}
+
+/// Parses the manual page for the command `name`.
+///
+/// ### returns:
+/// - `Err(String)`: what is wrong with the page.
+pub fn parse<'a>(name: &'a str, text: &'a str) -> Result<Page<'a>, String> {
+    let Some(title) = text.lines().find(|line| !line.trim().is_empty()) else {
+        return Err(String::from("the page is empty"));
+    };
+    let Some((title_name, summary)) = title.strip_prefix("# ").and_then(|title| title.split_once(" - ")) else {
+        return Err(format!("the first line should be `# {name} - <summary>`, not `{title}`"));
+    };
+    if title_name.trim() != name {
+        return Err(format!("the page is named `{}`, but its file is named after `{name}`", title_name.trim()));
+    }
+    let summary = summary.trim();
+    if summary.is_empty() {
+        return Err(String::from("the summary is empty"));
+    }
+    for section in REQUIRED_SECTIONS {
+        if !text.lines().any(|line| line.strip_prefix("## ").is_some_and(|heading| heading.trim() == section)) {
+            return Err(format!("there is no `## {section}` section"));
+        }
+    }
+    Ok(Page { name, summary, text })
+}
@@ -0,0 +1,79 @@
+//! Portfolio content: the sections, like `about` and `projects`, that the shell turns into
+//! commands.
+//!
+//! Each section is a file in `portfolio/` in the initramfs, in the format described in
+//! [section], so the portfolio can be changed without touching the kernel. The build script
+//! checks them, so a broken one is normally caught before it gets here; if one does get through,
+//! it is skipped.
+//!
+//! The manual pages `man` shows are in `man/`, in the format described in [man].
+pub mod man;
+pub mod section;
+
+use crate::{initramfs, serial_println};
+use alloc::vec::Vec;
+use core::str;
+use man::Page;
+use section::Section;
+use spin::Lazy;
+
+/// The directory in the initramfs the sections are in.
+pub const SECTIONS_DIR: &str = "portfolio/";
+
+/// The directory in the initramfs the manual pages are in.
+pub const MAN_DIR: &str = "man/";
+
+/// The sections, parsed the first time they are needed.
+static SECTIONS: Lazy<Vec<Section<'static>>> = Lazy::new(|| {
+    let mut sections = Vec::new();
+    for file in initramfs::files() {
+        let is_section = file.path.strip_prefix(SECTIONS_DIR).is_some_and(|name| name.ends_with(".txt"));
+        if !is_section {
+            continue;
+        }
+        let Ok(text) = str::from_utf8(file.data) else {
+            serial_println!("{}: not UTF-8, skipping it", file.path);
+            continue;
+        };
+        match section::parse(text) {
+            Ok(section) => sections.push(section),
+            Err(error) => serial_println!("{}:{}: {}, skipping it", file.path, error.line, error.message),
+        }
+    }
+    sections
+});
+
+/// Returns every portfolio section, in the order of their files' paths.
+pub fn sections() -> &'static [Section<'static>] {
+    &SECTIONS
+}
+
+/// The manual pages, parsed the first time they are needed.
+static MAN_PAGES: Lazy<Vec<Page<'static>>> = Lazy::new(|| {
+    let mut pages = Vec::new();
+    for file in initramfs::files() {
+        let Some(name) = file.path.strip_prefix(MAN_DIR).and_then(|name| name.strip_suffix(".md")) else {
+            continue;
+        };
+        let Ok(text) = str::from_utf8(file.data) else {
+            serial_println!("{}: not UTF-8, skipping it", file.path);
+            continue;
+        };
+        match man::parse(name, text) {
+            Ok(page) => pages.push(page),
+            Err(message) => serial_println!("{}: {}, skipping it", file.path, message),
+        }
+    }
+    pages
+});
+
+/// Returns every manual page, in the order of their commands' names.
+pub fn man_pages() -> &'static [Page<'static>] {
+    &MAN_PAGES
+}
+
+/// Returns the manual page for the command `name`, if it has one. Aliases don't have pages of
+/// their own.
+pub fn man_page(name: &str) -> Option<&'static Page<'static>> {
+    MAN_PAGES.iter().find(|page| page.name == name)
+}
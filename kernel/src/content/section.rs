@@ -0,0 +1,193 @@
+//! The format of a portfolio section file.
+//!
+//! ```text
+//! name: projects
+//! aliases: work, stuff
+//! description: Lists the projects I have worked on
+//! format: plain
+//! ---
+//! I have worked on the following projects:
+//! @color brightcyan
+//! 1. PortfoliOS
+//! @color
+//! @pause 500000
+//! Thanks for reading, {user}!
+//! ```
+//!
+//! The header names the command the section becomes, and describes it for `help`. `aliases` is
+//! optional, and so is `format`: `plain`, the default, or `markdown`. Everything after the `---` is
+//! printed, except for lines starting with `@`:
+//!
+//! - `@color <name>` prints the lines after it in that color, and `@color` goes back to normal.
+//! - `@pause <iterations>` spins for a while, for dramatic effect.
+//! - `@@` at the start of a line stands for a literal `@`.
+//!
+//! In the text, `\e` is replaced by an escape character, to start an ANSI color sequence, and
+//! variables in braces by their values:
+//!
+//! - `{user}`: the name of the user.
+//! - `{kernel}`: the kernel's name and version.
+//! - `{uptime}`: how long it has been since boot.
+//! - `{cpu}`, `{cpu_features}` and `{cpus}`: the CPU's model, its notable features, and how many
+//!   processors there are.
+//! - `{memory}` and `{heap}`: how much RAM there is, and how much of the kernel heap is in use.
+//! - `{display}`: the screen's resolution and pixel format.
+//! - `{interrupts}`: the interrupt controllers.
+//! - `{boot}`: the firmware it was booted by, BIOS or UEFI.
+//!
+//! Anything else in braces is printed as it is, unless it looks like a variable: lowercase
+//! letters and `_` only, which is an error, to catch typos.
+//!
+//! A `markdown` section's text is rendered as Markdown, wrapped to the screen, instead of being
+//! printed as it is. Its styles come from the Markdown, so it can't use `@color`.
+//!
+//! This file is also compiled into the build script, which checks every section before it goes
+//! into the initramfs, so it must only use `core` and `alloc`.
+use alloc::{
+    format,
+    string::String,
+    vec::Vec
+};
+
+/// The variables that can be used in a section's text, as `{name}`.
+pub const VARIABLES: [&str; 11] =
+    ["user", "kernel", "uptime", "cpu", "cpu_features", "cpus", "memory", "heap", "display", "interrupts", "boot"];
+
+/// A parsed section file. It borrows from the text it was parsed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section<'a> {
+    pub name: &'a str,
+    pub aliases: Vec<&'a str>,
+    pub description: &'a str,
+    /// True if the text is Markdown, from `format: markdown`.
+    pub markdown: bool,
+    pub body: Vec<Line<'a>>,
+}
+
+/// A line of a section's body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Line<'a> {
+    /// Text to print.
+    Text(&'a str),
+    /// `@color`: the color to print the following lines in, by name, or `None` to go back to
+    /// normal.
+    Color(Option<&'a str>),
+    /// `@pause`: how many iterations to spin for.
+    Pause(u64),
+}
+
+/// A problem with a section file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionError {
+    /// The line it is on, counting from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+/// Returns true if `name` can be a section's command name or alias: lowercase letters, digits,
+/// `-` and `_`, starting with a letter.
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|ch| ch.is_ascii_lowercase())
+        && chars.all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-' || ch == '_')
+}
+
+/// Parses a section file.
+pub fn parse(text: &str) -> Result<Section<'_>, SectionError> {
+    let error = |line: usize, message: String| Err(SectionError { line: line + 1, message });
+    let mut lines = text.lines().enumerate();
+
+    let mut name = None;
+    let mut aliases = None;
+    let mut description = None;
+    let mut format = None;
+    let mut end_of_header = None;
+    for (number, line) in lines.by_ref() {
+        if line.trim() == "---" {
+            end_of_header = Some(number);
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            return error(number, format!("expected `key: value` or `---`, found `{line}`"));
+        };
+        let value = value.trim();
+        let field = match key.trim() {
+            "name" => &mut name,
+            "aliases" => &mut aliases,
+            "description" => &mut description,
+            "format" => &mut format,
+            key => return error(number, format!("unknown key `{key}`; expected name, aliases, description or format")),
+        };
+        if field.is_some() {
+            return error(number, format!("`{}` is given twice", key.trim()));
+        }
+        *field = Some(value);
+    }
+
+    let Some(end_of_header) = end_of_header else {
+        return error(0, String::from("there is no `---` line to end the header"));
+    };
+    let Some(name) = name else {
+        return error(0, String::from("the header has no `name`"));
+    };
+    if !is_valid_name(name) {
+        return error(0, format!("`{name}` is not a valid command name"));
+    }
+    let aliases: Vec<&str> = aliases
+        .map(|aliases| aliases.split(',').map(str::trim).filter(|alias| !alias.is_empty()).collect())
+        .unwrap_or_default();
+    if let Some(alias) = aliases.iter().find(|alias| !is_valid_name(alias)) {
+        return error(0, format!("`{alias}` is not a valid alias"));
+    }
+    let Some(description) = description.filter(|description| !description.is_empty()) else {
+        return error(end_of_header, String::from("the header has no `description`"));
+    };
+    let markdown = match format {
+        None | Some("plain") => false,
+        Some("markdown") => true,
+        Some(format) => return error(0, format!("unknown format `{format}`; expected plain or markdown")),
+    };
+
+    let mut body = Vec::new();
+    for (number, line) in lines {
+        let text = match line.strip_prefix('@') {
+            None => Some(line),
+            Some(directive) if directive.starts_with('@') => Some(directive),
+            Some(_) => None,
+        };
+        if let Some(text) = text {
+            if let Some(variable) = variables(text).find(|variable| !VARIABLES.contains(variable)) {
+                return error(number, format!("unknown variable `{{{variable}}}`"));
+            }
+            body.push(Line::Text(text));
+            continue;
+        }
+        let directive = &line[1..];
+        let (keyword, argument) = directive.split_once(' ').unwrap_or((directive, ""));
+        let argument = argument.trim();
+        match keyword {
+            "color" if markdown => return error(number, String::from("`@color` can't be used in a markdown section")),
+            "color" if argument.is_empty() => body.push(Line::Color(None)),
+            "color" => body.push(Line::Color(Some(argument))),
+            "pause" => match argument.parse() {
+                Ok(iterations) => body.push(Line::Pause(iterations)),
+                Err(_) => return error(number, format!("`@pause` needs a number of iterations, not `{argument}`")),
+            },
+            _ => return error(number, format!("unknown directive `@{keyword}`; use `@@` for a literal `@`")),
+        }
+    }
+
+    Ok(Section { name, aliases, description, markdown, body })
+}
+
+/// Returns the names of the variables in `text`: the words of lowercase letters and `_` in braces.
+fn variables(text: &str) -> impl Iterator<Item = &str> {
+    text.split('{').skip(1).filter_map(|rest| {
+        let name = &rest[..rest.find('}')?];
+        let is_variable = !name.is_empty() && name.chars().all(|ch| ch.is_ascii_lowercase() || ch == '_');
+        is_variable.then_some(name)
+    })
+}
@@ -0,0 +1,179 @@
+//! The debugger's prompt, and what can be typed at it.
+use super::{console, TrapFrame};
+use crate::{
+    memory::inspect::{self, parse_number},
+    shell::PROMPT_WIDTH
+};
+use alloc::{format, string::String, vec::Vec};
+use core::sync::atomic::Ordering;
+use x86_64::registers::control::{Cr0, Cr2, Cr3, Cr4};
+
+/// How many quadwords `stack` shows if it isn't told.
+const STACK_COUNT: u64 = 16;
+
+/// How many bytes `x` shows if it isn't told, and the most it shows at once.
+const DUMP_LENGTH: u64 = 64;
+const MAX_DUMP: u64 = 4096;
+
+/// The flags in RFLAGS worth showing, and their bits.
+const FLAGS: [(&str, u32); 9] =
+    [("CF", 0), ("PF", 2), ("AF", 4), ("ZF", 6), ("SF", 7), ("TF", 8), ("IF", 9), ("DF", 10), ("OF", 11)];
+
+const HELP: &str = "\
+regs, r               show the registers
+stack [count]         show count quadwords from the top of the stack (16 if not given)
+x <address> [length]  show length bytes of memory as hex and ASCII (64 if not given)
+step, s               run one instruction, then stop again
+continue, c           go on from where the code stopped
+help, h               show this
+
+An address can be a number, in hex with 0x or decimal, or a register, like rsp.
+Enter on its own does the last command again.";
+
+/// How the debugger was left.
+pub(super) enum Leave {
+    Continue,
+    /// Run one instruction, and stop again.
+    Step,
+}
+
+/// Shows what stopped the code, and reads commands until the debugger is left.
+///
+/// ### params:
+/// - `resumable`: whether the code can go on afterwards. If it can't, `continue` leaves the
+///   debugger for the panic, and `step` isn't allowed.
+pub(super) fn session(frame: &mut TrapFrame, title: &str, resumable: bool) -> Leave {
+    // Nothing here is the shell's prompt, so backspace can go right back to the start of a line.
+    let prompt_width = PROMPT_WIDTH.swap(0, Ordering::Relaxed);
+    outln!("\n\x1b[93mkdb: {title} at {:#x}\x1b[0m", frame.rip);
+    if !resumable {
+        outln!("kdb: the code can't go on after this; leaving the debugger panics");
+    }
+    let mut last = String::new();
+    let leave = loop {
+        out!("kdb> ");
+        let mut line = console::read_line();
+        if line.trim().is_empty() {
+            line = last.clone();
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, args)) = words.split_first() else {
+            continue;
+        };
+        match command {
+            "regs" | "r" => registers(frame),
+            "stack" => stack(frame, args),
+            "x" => dump(frame, args),
+            "step" | "s" if resumable => break Leave::Step,
+            "step" | "s" => outln!("kdb: a fault can't be stepped past"),
+            "continue" | "c" => break Leave::Continue,
+            "help" | "h" => outln!("{HELP}"),
+            _ => outln!("kdb: unknown command {command}; try help"),
+        }
+        last = line;
+    };
+    if let Leave::Continue = leave {
+        outln!("kdb: {}", if resumable { "continuing" } else { "panicking" });
+    }
+    PROMPT_WIDTH.store(prompt_width, Ordering::Relaxed);
+    leave
+}
+
+/// The general purpose registers, and where the code was, by name.
+fn named_registers(frame: &TrapFrame) -> [(&'static str, u64); 18] {
+    [
+        ("rax", frame.rax),
+        ("rbx", frame.rbx),
+        ("rcx", frame.rcx),
+        ("rdx", frame.rdx),
+        ("rsi", frame.rsi),
+        ("rdi", frame.rdi),
+        ("rbp", frame.rbp),
+        ("rsp", frame.rsp),
+        ("r8", frame.r8),
+        ("r9", frame.r9),
+        ("r10", frame.r10),
+        ("r11", frame.r11),
+        ("r12", frame.r12),
+        ("r13", frame.r13),
+        ("r14", frame.r14),
+        ("r15", frame.r15),
+        ("rip", frame.rip),
+        ("rflags", frame.rflags),
+    ]
+}
+
+/// Parses an address or count: a number, or the name of a register.
+fn value(frame: &TrapFrame, text: &str) -> Option<u64> {
+    let register = named_registers(frame).into_iter().find(|(name, _)| name.eq_ignore_ascii_case(text));
+    match register {
+        Some((_, value)) => Some(value),
+        None => {
+            let value = parse_number(text);
+            if value.is_none() {
+                outln!("kdb: {text} is not a number or a register");
+            }
+            value
+        }
+    }
+}
+
+fn registers(frame: &TrapFrame) {
+    for row in named_registers(frame).chunks(3) {
+        let row: Vec<String> = row.iter().map(|(name, value)| format!("{name:<6} {value:#018x}")).collect();
+        outln!("{}", row.join("  "));
+    }
+    let flags: Vec<&str> =
+        FLAGS.iter().filter(|(_, bit)| frame.rflags & (1 << bit) != 0).map(|(name, _)| *name).collect();
+    outln!("flags  {}", flags.join(" "));
+    outln!("cs     {:#06x}              ss     {:#06x}", frame.cs, frame.ss);
+    outln!("cr0    {:#018x}  cr2    {:#018x}", Cr0::read_raw(), Cr2::read_raw());
+    outln!("cr3    {:#018x}  cr4    {:#018x}", Cr3::read_raw().0.start_address().as_u64(), Cr4::read_raw());
+}
+
+/// `stack [count]`: shows the quadwords from the top of the stack down, stopping at the first one
+/// that can't be read.
+fn stack(frame: &TrapFrame, args: &[&str]) {
+    let count = match args {
+        [] => STACK_COUNT,
+        [count] => match value(frame, count) {
+            Some(count) => count.min(MAX_DUMP / 8),
+            None => return,
+        },
+        _ => return outln!("kdb: usage: stack [count]"),
+    };
+    for index in 0..count {
+        let address = frame.rsp.wrapping_add(index * 8);
+        let read = inspect::resolve(address, false).and_then(|address| inspect::read(address, 8));
+        match read {
+            Ok(bytes) => {
+                let value = u64::from_le_bytes(bytes.try_into().unwrap_or_default());
+                outln!("{address:016x}  {value:#018x}");
+            }
+            Err(error) => return outln!("kdb: {error}"),
+        }
+    }
+}
+
+/// `x <address> [length]`: shows memory as hex and ASCII.
+fn dump(frame: &TrapFrame, args: &[&str]) {
+    let (address, length) = match args {
+        [address] => (value(frame, address), Some(DUMP_LENGTH)),
+        [address, length] => (value(frame, address), value(frame, length)),
+        _ => return outln!("kdb: usage: x <address> [length]"),
+    };
+    let (Some(address), Some(length)) = (address, length) else {
+        return;
+    };
+    if length > MAX_DUMP {
+        return outln!("kdb: at most {MAX_DUMP} bytes can be shown at once");
+    }
+    let read = inspect::resolve(address, false).and_then(|address| inspect::read(address, length as usize));
+    let bytes = match read {
+        Ok(bytes) => bytes,
+        Err(error) => return outln!("kdb: {error}"),
+    };
+    for (line, chunk) in bytes.chunks(16).enumerate() {
+        outln!("{}", inspect::dump_line(address + line as u64 * 16, chunk));
+    }
+}
@@ -0,0 +1,70 @@
+//! The debugger's console: the screen and the serial port at once.
+use crate::{
+    framebuffer::global_writer::FRAMEBUFFER_WRITER,
+    input::{self, EventKind},
+    serial::SERIAL1
+};
+use alloc::string::String;
+use core::fmt::{self, Write};
+use pc_keyboard::DecodedKey;
+
+/// Writes to both the screen and the serial port.
+///
+/// Either one is skipped if the code the debugger stopped was in the middle of using it, since
+/// waiting for it to be let go would be waiting forever.
+pub struct Console;
+
+impl Write for Console {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if let Some(mut guard) = FRAMEBUFFER_WRITER.try_lock() {
+            if let Some(writer) = guard.as_mut() {
+                _ = writer.write_str(s);
+            }
+        }
+        if let Some(mut serial) = SERIAL1.try_lock() {
+            _ = serial.write_str(s);
+        }
+        Ok(())
+    }
+}
+
+/// Reads a line, typed on the keyboard or over the serial port, echoing it on both, until Enter
+/// is pressed.
+pub fn read_line() -> String {
+    let mut line = String::new();
+    loop {
+        match read_char() {
+            '\n' | '\r' => {
+                outln!();
+                return line;
+            }
+            // Backspace, or Delete, which is what most terminals send for it.
+            '\x08' | '\x7f' => {
+                if line.pop().is_some() {
+                    out!("\x08");
+                }
+            }
+            ch if !ch.is_control() => {
+                line.push(ch);
+                out!("{ch}");
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Waits for a character from the keyboard or the serial port, whichever comes first. Interrupts
+/// are off in the debugger, so both are [polled](input::poll).
+fn read_char() -> char {
+    loop {
+        match input::poll().map(|event| event.kind) {
+            Some(EventKind::Key(press)) => {
+                if let DecodedKey::Unicode(ch) = press.key {
+                    return ch;
+                }
+            }
+            Some(EventKind::Serial(byte)) if byte.is_ascii() => return byte as char,
+            _ => core::hint::spin_loop(),
+        }
+    }
+}
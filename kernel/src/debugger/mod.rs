@@ -0,0 +1,242 @@
+//! A small kdb-style debugger, for looking at the kernel while it is stopped.
+//!
+//! An `int3` drops into it, from anywhere, and so do page faults and general protection faults
+//! while it is [catching faults](set_catch_faults). It stops everything else, with interrupts
+//! off, and talks over the framebuffer console and the serial port at once, so it can be driven
+//! from either. From its prompt, the registers, the stack and memory can be looked at, and the
+//! code it stopped can be stepped one instruction at a time, with the trap flag, or continued.
+//!
+//! The debugger needs the heap, so if the code it stopped holds the heap's lock, it says so and
+//! lets the exception go on as if it weren't there, rather than waiting for the lock forever.
+//!
+//! The exceptions come in through the entry points below, written in assembly, which save every
+//! general purpose register in a [TrapFrame] before calling `trap`, and load them back from it
+//! before returning.
+macro_rules! out {
+    ($($arg:tt)*) => {
+        _ = core::fmt::Write::write_fmt(&mut $crate::debugger::console::Console, format_args!($($arg)*))
+    };
+}
+
+macro_rules! outln {
+    () => (out!("\n"));
+    ($($arg:tt)*) => {{
+        out!($($arg)*);
+        out!("\n")
+    }};
+}
+
+mod commands;
+mod console;
+
+use crate::{allocator, interrupts};
+use alloc::{format, string::String};
+use commands::Leave;
+use core::{
+    arch::{asm, global_asm},
+    sync::atomic::{AtomicBool, Ordering}
+};
+use x86_64::{registers::control::Cr2, structures::idt::InterruptDescriptorTable, VirtAddr};
+
+/// The trap flag in RFLAGS. While it is set, the processor raises a debug exception after every
+/// instruction.
+const TRAP_FLAG: u64 = 1 << 8;
+
+/// Whether page faults and general protection faults stop in the debugger before panicking.
+static CATCH_FAULTS: AtomicBool = AtomicBool::new(false);
+
+/// Whether the debugger was last left to step one instruction, so the debug exception that
+/// follows is expected.
+static STEPPING: AtomicBool = AtomicBool::new(false);
+
+/// Whether the debugger is at its prompt, so a fault in the debugger itself doesn't try to start
+/// another one.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// The state of the code an exception stopped, as the entry points save it. Changes to the
+/// registers here are made real when the code goes on.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TrapFrame {
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub r11: u64,
+    pub r10: u64,
+    pub r9: u64,
+    pub r8: u64,
+    pub rbp: u64,
+    pub rdi: u64,
+    pub rsi: u64,
+    pub rdx: u64,
+    pub rcx: u64,
+    pub rbx: u64,
+    pub rax: u64,
+    /// Which exception it was: 1 for a debug exception, 3 for a breakpoint, 13 for a general
+    /// protection fault, or 14 for a page fault.
+    pub vector: u64,
+    /// The error code the processor gave, or 0 for exceptions that don't have one.
+    pub error_code: u64,
+    // The rest is pushed by the processor.
+    pub rip: u64,
+    pub cs: u64,
+    pub rflags: u64,
+    pub rsp: u64,
+    pub ss: u64,
+}
+
+// Each entry point makes the stack look the same whatever the exception: it pushes a 0 in place of
+// the error code if the processor didn't push one, then the vector. The processor aligns the stack
+// to 16 bytes before pushing its part, and 22 quadwords are pushed in all, so it is still aligned
+// for the call.
+global_asm!(
+    ".global kdb_debug_entry",
+    "kdb_debug_entry:",
+    "push 0",
+    "push 1",
+    "jmp .Lkdb_common",
+    ".global kdb_breakpoint_entry",
+    "kdb_breakpoint_entry:",
+    "push 0",
+    "push 3",
+    "jmp .Lkdb_common",
+    ".global kdb_general_protection_entry",
+    "kdb_general_protection_entry:",
+    "push 13",
+    "jmp .Lkdb_common",
+    ".global kdb_page_fault_entry",
+    "kdb_page_fault_entry:",
+    "push 14",
+    ".Lkdb_common:",
+    "push rax",
+    "push rbx",
+    "push rcx",
+    "push rdx",
+    "push rsi",
+    "push rdi",
+    "push rbp",
+    "push r8",
+    "push r9",
+    "push r10",
+    "push r11",
+    "push r12",
+    "push r13",
+    "push r14",
+    "push r15",
+    "mov rdi, rsp",
+    "cld",
+    "call {trap}",
+    "pop r15",
+    "pop r14",
+    "pop r13",
+    "pop r12",
+    "pop r11",
+    "pop r10",
+    "pop r9",
+    "pop r8",
+    "pop rbp",
+    "pop rdi",
+    "pop rsi",
+    "pop rdx",
+    "pop rcx",
+    "pop rbx",
+    "pop rax",
+    // The vector and the error code.
+    "add rsp, 16",
+    "iretq",
+    trap = sym trap,
+);
+
+extern "C" {
+    fn kdb_debug_entry();
+    fn kdb_breakpoint_entry();
+    fn kdb_general_protection_entry();
+    fn kdb_page_fault_entry();
+}
+
+/// Points the debug exception, breakpoint, general protection fault and page fault entries of
+/// `idt` at the debugger.
+pub fn install(idt: &mut InterruptDescriptorTable) {
+    let address = |entry: unsafe extern "C" fn()| VirtAddr::new(entry as usize as u64);
+    // The entry points save and restore everything they touch, and return with iretq, as a
+    // handler has to.
+    unsafe {
+        idt.debug.set_handler_addr(address(kdb_debug_entry));
+        idt.breakpoint.set_handler_addr(address(kdb_breakpoint_entry));
+        idt.general_protection_fault.set_handler_addr(address(kdb_general_protection_entry));
+        idt.page_fault.set_handler_addr(address(kdb_page_fault_entry));
+    }
+}
+
+/// Stops in the debugger, at the instruction after this call's `int3`.
+pub fn breakpoint() {
+    unsafe { asm!("int3", options(nostack)) };
+}
+
+/// Sets whether page faults and general protection faults stop in the debugger. Either way, they
+/// panic once it is left, since the code they happened in can't go on.
+pub fn set_catch_faults(catch: bool) {
+    CATCH_FAULTS.store(catch, Ordering::Relaxed);
+}
+
+pub fn catches_faults() -> bool {
+    CATCH_FAULTS.load(Ordering::Relaxed)
+}
+
+/// Called by the entry points, with interrupts off, for every exception the debugger handles.
+extern "C" fn trap(frame: &mut TrapFrame) {
+    interrupts::count(frame.vector as u8);
+    let heap_free = allocator::heap_is_free();
+    if !heap_free {
+        outln!("\nkdb: the code at {:#x} holds the heap's lock, so the debugger can't stop there", frame.rip);
+    }
+    let catch = heap_free && catches_faults() && !ACTIVE.load(Ordering::Relaxed);
+    match frame.vector {
+        1 => {
+            let stepped = STEPPING.swap(false, Ordering::Relaxed);
+            if stepped {
+                frame.rflags &= !TRAP_FLAG;
+            }
+            if heap_free {
+                stop(frame, if stepped { "Stepped" } else { "Debug exception" }, true);
+            }
+        }
+        3 => {
+            if heap_free {
+                stop(frame, "Breakpoint", true);
+            }
+        }
+        13 => {
+            if catch {
+                let title = format!("General protection fault, error code {:#x},", frame.error_code);
+                stop(frame, &title, false);
+            }
+            interrupts::general_protection_fault(frame);
+        }
+        _ => {
+            if catch {
+                let title = match Cr2::read() {
+                    Ok(address) => format!("Page fault on {:#x}", address.as_u64()),
+                    Err(_) => String::from("Page fault"),
+                };
+                stop(frame, &title, false);
+            }
+            interrupts::page_fault(frame);
+        }
+    }
+}
+
+/// Runs the debugger's prompt until it is left, and sets up stepping if that is how.
+///
+/// ### params:
+/// - `title`: what stopped the code, as in "Breakpoint at 0x...".
+/// - `resumable`: whether the code can go on afterwards; it can't after a fault.
+fn stop(frame: &mut TrapFrame, title: &str, resumable: bool) {
+    ACTIVE.store(true, Ordering::Relaxed);
+    if let Leave::Step = commands::session(frame, title, resumable) {
+        frame.rflags |= TRAP_FLAG;
+        STEPPING.store(true, Ordering::Relaxed);
+    }
+    ACTIVE.store(false, Ordering::Relaxed);
+}
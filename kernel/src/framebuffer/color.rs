@@ -25,6 +25,55 @@ pub enum ConsoleColor {
 }
 
 impl ConsoleColor {
+    /// Every console color, in ANSI order.
+    pub const ALL: [ConsoleColor; 16] = [
+        ConsoleColor::Black,
+        ConsoleColor::Red,
+        ConsoleColor::Green,
+        ConsoleColor::Yellow,
+        ConsoleColor::Blue,
+        ConsoleColor::Magenta,
+        ConsoleColor::Cyan,
+        ConsoleColor::White,
+        ConsoleColor::BrightBlack,
+        ConsoleColor::BrightRed,
+        ConsoleColor::BrightGreen,
+        ConsoleColor::BrightYellow,
+        ConsoleColor::BrightBlue,
+        ConsoleColor::BrightMagenta,
+        ConsoleColor::BrightCyan,
+        ConsoleColor::BrightWhite,
+    ];
+
+    /// The name users type to refer to this color, e.g. in `echo -c brightred`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConsoleColor::Black => "black",
+            ConsoleColor::Red => "red",
+            ConsoleColor::Green => "green",
+            ConsoleColor::Yellow => "yellow",
+            ConsoleColor::Blue => "blue",
+            ConsoleColor::Magenta => "magenta",
+            ConsoleColor::Cyan => "cyan",
+            ConsoleColor::White => "white",
+            ConsoleColor::BrightBlack => "brightblack",
+            ConsoleColor::BrightRed => "brightred",
+            ConsoleColor::BrightGreen => "brightgreen",
+            ConsoleColor::BrightYellow => "brightyellow",
+            ConsoleColor::BrightBlue => "brightblue",
+            ConsoleColor::BrightMagenta => "brightmagenta",
+            ConsoleColor::BrightCyan => "brightcyan",
+            ConsoleColor::BrightWhite => "brightwhite",
+        }
+    }
+
+    /// Looks a color up by its [name](ConsoleColor::name), ignoring case.
+    pub fn from_name(name: &str) -> Option<ConsoleColor> {
+        ConsoleColor::ALL
+            .into_iter()
+            .find(|color| color.name().eq_ignore_ascii_case(name))
+    }
+
     /// Convert ConsoleColor to Rgb888
     pub fn to_rgb888(&self) -> Rgb888 {
         match self {
@@ -48,6 +48,16 @@ pub fn print_fmt(args: fmt::Arguments, color_fg: ConsoleColor, color_bg: Console
     });
 }
 
+/// Returns how many characters fit on a line of the screen.
+pub fn columns() -> usize {
+    with_writer(|writer| writer.columns())
+}
+
+/// Returns how many lines fit on the screen.
+pub fn rows() -> usize {
+    with_writer(|writer| writer.rows())
+}
+
 /// Update the cursor Status(on or off), and position.
 pub fn update_cursor() {
     with_writer(|writer| {
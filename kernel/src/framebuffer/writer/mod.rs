@@ -57,7 +57,8 @@ impl PixelConversion for Rgb888 {
 /// Implementation for the `Bgr888` pixel format from embedded-graphics.
 /// Writes color in B, G, R order.
 use embedded_graphics::pixelcolor::Bgr888;
-use crate::shell::GLOBAL_SHELL;
+use core::sync::atomic::Ordering;
+use crate::shell::PROMPT_WIDTH;
 
 impl PixelConversion for Bgr888 {
     fn write_pixel(
@@ -118,12 +119,17 @@ pub struct FrameBufferWriter {
     pub background_color: ConsoleColor,
     /// Whether the cursor is currently visible (for blinking).
     pub cursor_visible: bool,
+    /// Whether the cursor is hidden altogether, like while a game is drawing on the screen.
+    pub cursor_hidden: bool,
     /// Timer used to control cursor blinking.
     pub cursor_blink_timer: usize,
     /// Last recorded x-position of the cursor (used to erase previous cursor drawing).
     pub last_cursor_x: usize,
     /// Last recorded y-position of the cursor.
     pub last_cursor_y: usize,
+    /// The character the cursor sits on, if it is over text rather than after it. The cursor
+    /// shows it inverted, and draws it back when erased.
+    cursor_char: Option<char>,
     /// Function pointer for converting an `Rgb888` color into the framebuffer's pixel format.
     pixel_converter: fn(&mut [u8], usize, Rgb888, &FrameBufferInfo),
 }
@@ -175,9 +181,11 @@ impl FrameBufferWriter {
             text_color: ConsoleColor::BrightWhite,
             background_color: ConsoleColor::Black,
             cursor_visible: true,
+            cursor_hidden: false,
             cursor_blink_timer: 0,
             last_cursor_x: padding,
             last_cursor_y: padding + font_height,
+            cursor_char: None,
             pixel_converter,
         };
         // Clear the framebuffer using the background color.
@@ -282,8 +290,8 @@ impl FrameBufferWriter {
             } else if ch == '\x08' {
                 // Handle backspace: erase cursor and move back one character if possible.
                 self.erase_cursor();
-                // len can be 0 also, so the second check is necessary. *10 occurs because, the font is 10 pixels wide.
-                if self.cursor_x > self.padding && self.cursor_x > (GLOBAL_SHELL.lock().len) {
+                // the prompt width can be 0 also, so the second check is necessary.
+                if self.cursor_x > self.padding && self.cursor_x > PROMPT_WIDTH.load(Ordering::Relaxed) {
                     self.cursor_x -= self.font_width;
                     self.write_char(' ').expect("If this happens, it is the end of the world.");
                     self.erase_cursor();
@@ -333,6 +341,18 @@ impl FrameBufferWriter {
         Ok(())
     }
 
+    /// Returns how many characters fit on a line before [draw_wrapped_text](Self::draw_wrapped_text)
+    /// wraps to the next one.
+    pub fn columns(&self) -> usize {
+        (self.info.width - self.padding * 3) / self.font_width
+    }
+
+    /// Returns how many lines fit on the screen before [new_line](Self::new_line) scrolls it, and
+    /// so how many rows [set_cursor_position](Self::set_cursor_position) can reach.
+    pub fn rows(&self) -> usize {
+        (self.info.height - self.padding * 2 - self.font_height) / self.line_spacing + 1
+    }
+
     /// Toggles the cursor's visibility for blinking.
     ///
     /// If the cursor becomes visible, it is drawn; otherwise, it is erased.
@@ -347,6 +367,9 @@ impl FrameBufferWriter {
 
     /// Erases the cursor from its current position by drawing a rectangle with the background color.
     fn erase_cursor(&mut self) {
+        if self.cursor_hidden {
+            return;
+        }
         let offset = 3; // Number of pixels to push the cursor down
         let cursor_rect = Rectangle::new(
             Point::new(self.cursor_x as i32, (self.cursor_y - self.font_height + offset) as i32),
@@ -354,12 +377,16 @@ impl FrameBufferWriter {
         );
         let bg_style = PrimitiveStyle::with_fill(self.background_color.to_rgb888());
         cursor_rect.into_styled(bg_style).draw(self).unwrap();
+        self.draw_cursor_char(self.text_color);
     }
 
     /// Draws the cursor at the current position using the text color.
     ///
     /// This method saves the current cursor position to allow for later erasing.
     pub fn draw_cursor(&mut self) {
+        if self.cursor_hidden {
+            return;
+        }
         let offset = 3; // Number of pixels to push the cursor down
         self.last_cursor_x = self.cursor_x;
         self.last_cursor_y = self.cursor_y;
@@ -369,6 +396,17 @@ impl FrameBufferWriter {
         );
         let cursor_style = PrimitiveStyle::with_fill(self.text_color.to_rgb888());
         cursor_rect.into_styled(cursor_style).draw(self).unwrap();
+        self.draw_cursor_char(self.background_color);
+    }
+
+    /// Draws the character under the cursor, if there is one, in `color`.
+    fn draw_cursor_char(&mut self, color: ConsoleColor) {
+        if let Some(ch) = self.cursor_char {
+            let position = Point::new(self.cursor_x as i32, self.cursor_y as i32);
+            let style = MonoTextStyle::new(&FONT_10X20, color.to_rgb888());
+            let s = ch.to_string();
+            Text::new(&s, position, style).draw(self).unwrap();
+        }
     }
 
     /// Scrolls the screen content up by one text line (using `line_spacing` pixels),
@@ -456,9 +494,54 @@ impl FrameBufferWriter {
         self.erase_cursor();
         self.cursor_x = self.padding + (x * self.font_width);
         self.cursor_y = self.padding + (y * self.line_spacing) + self.font_height;
+        self.cursor_char = None;
+        self.draw_cursor();
+    }
+
+    /// Hides the cursor, erasing it, or shows it again. A hidden cursor doesn't blink or get
+    /// drawn after text.
+    ///
+    /// # Parameters
+    ///
+    /// - `hidden`: Whether to hide the cursor.
+    pub fn set_cursor_hidden(&mut self, hidden: bool) {
+        if hidden {
+            self.erase_cursor();
+            self.cursor_hidden = true;
+        } else {
+            self.cursor_hidden = false;
+            self.draw_cursor();
+        }
+    }
+
+    /// Tells the cursor which character it is on, so it can be shown through the cursor and
+    /// survive it blinking. [set_cursor_position](Self::set_cursor_position) forgets it again.
+    ///
+    /// # Parameters
+    ///
+    /// - `ch`: The character drawn at the cursor's position, or `None` if there is none.
+    pub fn set_cursor_char(&mut self, ch: Option<char>) {
+        self.erase_cursor();
+        self.cursor_char = ch;
         self.draw_cursor();
     }
 
+    /// Clears a row of text, leaving the cursor where it is.
+    ///
+    /// # Parameters
+    ///
+    /// - `y`: Row number, as for [set_cursor_position](Self::set_cursor_position).
+    pub fn clear_row(&mut self, y: usize) {
+        let offset = 3; // The same as the cursor's, so the row covers the text drawn on it.
+        let top = self.padding + (y * self.line_spacing) + offset;
+        let row_rect = Rectangle::new(
+            Point::new(0, top as i32),
+            Size::new(self.info.width as u32, self.line_spacing as u32),
+        );
+        let bg_style = PrimitiveStyle::with_fill(self.background_color.to_rgb888());
+        row_rect.into_styled(bg_style).draw(self).unwrap();
+    }
+
     /// Sets the text color used for drawing text.
     ///
     /// # Parameters
@@ -486,6 +569,7 @@ impl FrameBufferWriter {
     pub fn clear_screen(&mut self) -> Result<(), Infallible>{
         self.cursor_x  =10;
         self.cursor_y = 31;
+        self.cursor_char = None;
         self.clear(ConsoleColor::Black.to_rgb888())
     }
 }
@@ -0,0 +1,119 @@
+use crate::initramfs;
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::String,
+    vec::Vec
+};
+use spin::Mutex;
+
+/// A tiny RAM-backed file store.
+///
+/// Files are kept in a map from absolute path to contents, so they live exactly as long as the
+/// kernel does. There are no directories: a path like `/home/agam/notes` is just a key.
+pub struct FileStore {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl FileStore {
+    /// Creates an empty file store.
+    pub const fn new() -> Self {
+        Self {
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the contents of the file at `path`, if it exists.
+    pub fn read(&self, path: &str) -> Option<&[u8]> {
+        self.files.get(path).map(Vec::as_slice)
+    }
+
+    /// Returns the contents of the file at `path` as text, if it exists.
+    ///
+    /// Invalid UTF-8 is replaced, rather than rejected, since everything in here is meant to be
+    /// read by a human anyway.
+    pub fn read_to_string(&self, path: &str) -> Option<String> {
+        self.read(path)
+            .map(|data| String::from_utf8_lossy(data).into_owned())
+    }
+
+    /// Replaces the contents of the file at `path`, creating it if needed.
+    pub fn write(&mut self, path: &str, data: &[u8]) {
+        self.files.insert(String::from(path), data.to_vec());
+    }
+
+    /// Appends to the file at `path`, creating it if needed.
+    pub fn append(&mut self, path: &str, data: &[u8]) {
+        self.files.entry(String::from(path)).or_default().extend_from_slice(data);
+    }
+
+    /// Removes every file under `directory`.
+    pub fn remove_tree(&mut self, directory: &str) {
+        let prefix = format!("{}/", directory.trim_end_matches('/'));
+        self.files.retain(|path, _| !path.starts_with(&prefix));
+    }
+
+    /// Returns an iterator over the path of every file, in order.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    /// Returns true if a file exists at `path`.
+    pub fn exists(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+}
+
+impl Default for FileStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Turns `path` into the absolute form files are stored under.
+///
+/// Relative paths are taken to start at `/`, empty and `.` components are dropped, and `..`
+/// removes the component before it.
+pub fn normalize(path: &str) -> String {
+    let mut components: Vec<&str> = Vec::new();
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    let mut normalized = String::new();
+    for component in components {
+        normalized.push('/');
+        normalized.push_str(component);
+    }
+    if normalized.is_empty() {
+        normalized.push('/');
+    }
+    normalized
+}
+
+/// The global file store. It is `None` until [mount] is called, which needs the heap.
+static FILE_STORE: Mutex<Option<FileStore>> = Mutex::new(None);
+
+/// Mounts the file store, with a copy of every file in the [initramfs] in it. Must be called
+/// after the heap and the initramfs have been initialised.
+pub fn mount() {
+    let mut store = FileStore::new();
+    for file in initramfs::files() {
+        store.write(&normalize(file.path), file.data);
+    }
+    *FILE_STORE.lock() = Some(store);
+}
+
+/// Runs `f` with the global file store.
+///
+/// ### returns:
+/// - `Some(R)`: the closure's result, if a file store is mounted.
+/// - `None`: if there is no file store (yet).
+pub fn with_store<R>(f: impl FnOnce(&mut FileStore) -> R) -> Option<R> {
+    FILE_STORE.lock().as_mut().map(f)
+}
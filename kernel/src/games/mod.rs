@@ -0,0 +1,200 @@
+//! Games to play on the console: [snake] and [tetris].
+//!
+//! They draw straight onto the framebuffer with embedded-graphics, rather than printing text, and
+//! are timed by the local APIC timer: a game moves on every so many [timer
+//! ticks](crate::interrupts::timer_ticks), and the keyboard is read in between without waiting
+//! for it. [play] runs the loop, and the keys every game shares: `p` to pause and `q` to quit.
+pub mod snake;
+pub mod tetris;
+
+use crate::{
+    framebuffer::{global_writer::with_writer, writer::FrameBufferWriter},
+    input::{self, Kinds},
+    interrupts::timer_ticks
+};
+use alloc::{format, string::String};
+use core::arch::x86_64::_rdtsc;
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyleBuilder},
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Baseline, Text}
+};
+use pc_keyboard::DecodedKey;
+
+/// How tall the lines above and below a game are, in pixels.
+const LINE_HEIGHT: u32 = 24;
+
+/// How far the lines above and below a game are from the edges of the screen, in pixels.
+const MARGIN: u32 = 10;
+
+const TEXT_COLOR: Rgb888 = Rgb888::new(0xe0, 0xe0, 0xe0);
+const STATUS_COLOR: Rgb888 = Rgb888::YELLOW;
+
+/// A game, as [play] runs it.
+trait Game {
+    /// The game's own keys, for the line under it.
+    const KEYS: &'static str;
+
+    /// How many timer ticks go by between one [step](Game::step) and the next.
+    fn interval(&self) -> u64;
+
+    /// Handles a key that isn't one of the keys every game has.
+    ///
+    /// ### returns:
+    /// - `false` if the key ended the game.
+    fn key(&mut self, key: DecodedKey) -> bool;
+
+    /// Moves the game on by one step.
+    ///
+    /// ### returns:
+    /// - `false` once the game is over.
+    fn step(&mut self) -> bool;
+
+    /// Draws what has changed since the last time, or everything if `all` is set.
+    fn draw(&mut self, writer: &mut FrameBufferWriter, all: bool);
+
+    /// The game's name, and how it is going, like the score, for the line above it.
+    fn status(&self) -> String;
+
+    fn score(&self) -> u32;
+
+    /// Whether the score can be a high score. A game a computer helped with can't be.
+    fn counts(&self) -> bool {
+        true
+    }
+}
+
+/// Plays rounds of a game until `q` is pressed, then clears the screen for the shell.
+///
+/// ### params:
+/// - `new_game`: starts a round, in the part of the screen it is given.
+/// - `best`: the high score so far, which is raised by any round that beats it.
+fn play<G: Game>(mut new_game: impl FnMut(Rectangle) -> G, best: &mut u32) {
+    let (width, height) = with_writer(|writer| {
+        writer.set_cursor_hidden(true);
+        (writer.info.width as u32, writer.info.height as u32)
+    });
+    let input = input::focus(Kinds::KEYS);
+    let top = MARGIN + LINE_HEIGHT + MARGIN;
+    let area = Rectangle::new(
+        Point::new(MARGIN as i32, top as i32),
+        Size::new(width - 2 * MARGIN, height.saturating_sub(2 * top)),
+    );
+    let footer = Point::new(MARGIN as i32, (height - MARGIN - LINE_HEIGHT) as i32);
+
+    'rounds: loop {
+        let mut game = new_game(area);
+        with_writer(|writer| {
+            _ = writer.clear(Rgb888::BLACK);
+            game.draw(writer, true);
+            draw_line(writer, footer, &format!("{}, p pause, q quit", G::KEYS), TEXT_COLOR);
+        });
+        let mut status = String::new();
+        let mut paused = false;
+        let mut next_step = timer_ticks() + game.interval();
+        let mut running = true;
+        while running {
+            while let Some(key) = input.try_read_key() {
+                match key {
+                    // q, Escape or Ctrl+C
+                    DecodedKey::Unicode('q' | 'Q' | '\x1b' | '\x03') => break 'rounds,
+                    DecodedKey::Unicode('p' | 'P') => {
+                        paused = !paused;
+                        next_step = timer_ticks() + game.interval();
+                    }
+                    key if !paused && running => running = game.key(key),
+                    _ => {}
+                }
+            }
+            if running && !paused && timer_ticks() >= next_step {
+                running = game.step();
+                next_step = timer_ticks() + game.interval();
+            }
+
+            let line = if paused { format!("{}  Paused, p to go on", game.status()) } else { game.status() };
+            let line = format!("{line}  Best: {best}");
+            with_writer(|writer| {
+                game.draw(writer, false);
+                if line != status {
+                    draw_line(writer, Point::new(MARGIN as i32, MARGIN as i32), &line, STATUS_COLOR);
+                }
+            });
+            status = line;
+            // Wait for the next timer tick or key.
+            if running {
+                x86_64::instructions::hlt();
+            }
+        }
+
+        let score = game.score();
+        let record = if game.counts() && score > *best {
+            *best = score;
+            "  New high score!"
+        } else {
+            ""
+        };
+        let line = format!("Game over: {score} points.{record}  r to play again, q to quit");
+        with_writer(|writer| {
+            game.draw(writer, false);
+            draw_line(writer, Point::new(MARGIN as i32, MARGIN as i32), &line, STATUS_COLOR);
+        });
+        loop {
+            match input.read_key() {
+                DecodedKey::Unicode('r' | 'R' | '\n') => continue 'rounds,
+                DecodedKey::Unicode('q' | 'Q' | '\x1b' | '\x03') => break 'rounds,
+                _ => {}
+            }
+        }
+    }
+
+    with_writer(|writer| {
+        _ = writer.clear_screen();
+        writer.set_cursor_hidden(false);
+    });
+}
+
+/// Clears the line of text at `position`, the top left of it, across the screen, and writes
+/// `text` there.
+fn draw_line(writer: &mut FrameBufferWriter, position: Point, text: &str, color: Rgb888) {
+    let width = writer.info.width as u32;
+    _ = Rectangle::new(Point::new(0, position.y), Size::new(width, LINE_HEIGHT))
+        .into_styled(PrimitiveStyle::with_fill(Rgb888::BLACK))
+        .draw(writer);
+    draw_text(writer, position, text, color);
+}
+
+/// Writes `text` with its top left at `position`, on a black background.
+fn draw_text(writer: &mut FrameBufferWriter, position: Point, text: &str, color: Rgb888) {
+    let style = MonoTextStyleBuilder::new()
+        .font(&FONT_10X20)
+        .text_color(color)
+        .background_color(Rgb888::BLACK)
+        .build();
+    _ = Text::with_baseline(text, position, style, Baseline::Top).draw(writer);
+}
+
+/// A small, fast pseudo-random number generator (xorshift64*), seeded from the time stamp
+/// counter. Good enough for where the food goes; not for anything secret.
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Self {
+        let seed = unsafe { _rdtsc() };
+        // The state must never be zero.
+        Self(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns a number from 0 up to, but not including, `n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
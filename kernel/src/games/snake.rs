@@ -0,0 +1,389 @@
+//! Snake: steer the snake to the food to make it grow, without running into the walls or itself.
+//! It speeds up as it grows.
+//!
+//! The autopilot, turned on with `-a` or Tab, plays by itself, in the same way as the AI Snake
+//! game in the portfolio: it takes the shortest way to the food, but only if the snake could
+//! still reach its own tail after eating it, so it never boxes itself in. When it can't, it
+//! follows its tail the long way round until the way to the food opens up.
+use super::{Game, Rng};
+use crate::framebuffer::writer::FrameBufferWriter;
+use alloc::{
+    collections::VecDeque,
+    format,
+    string::String,
+    vec,
+    vec::Vec
+};
+use embedded_graphics::{
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle}
+};
+use pc_keyboard::{DecodedKey, KeyCode};
+
+/// How wide and tall a cell of the board is, in pixels.
+const CELL_SIZE: u32 = 16;
+
+/// The most cells the board has across and down, however big the screen is.
+const MAX_COLUMNS: i32 = 48;
+const MAX_ROWS: i32 = 32;
+
+/// How many timer ticks a step takes at the start, and at the fastest.
+const START_INTERVAL: u64 = 12;
+const MIN_INTERVAL: u64 = 4;
+
+/// How many pieces of food it takes to speed up by a tick.
+const SPEED_UP_EVERY: u32 = 4;
+
+/// How much a piece of food is worth.
+const FOOD_POINTS: u32 = 10;
+
+const HEAD_COLOR: Rgb888 = Rgb888::new(0x90, 0xff, 0x90);
+const BODY_COLOR: Rgb888 = Rgb888::new(0x20, 0xb0, 0x40);
+const FOOD_COLOR: Rgb888 = Rgb888::new(0xff, 0x40, 0x40);
+const DEAD_COLOR: Rgb888 = Rgb888::new(0x70, 0x70, 0x70);
+const WALL_COLOR: Rgb888 = Rgb888::new(0x80, 0x80, 0x80);
+
+/// Plays Snake until `q` is pressed.
+///
+/// ### params:
+/// - `autopilot`: whether the snake starts out playing by itself.
+/// - `best`: the high score so far. Rounds the autopilot played any of don't count.
+pub fn play(autopilot: bool, best: &mut u32) {
+    super::play(|area| Snake::new(area, autopilot), best);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+    fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
+/// A cell of the board, as its column and row.
+type Cell = (i32, i32);
+
+struct Snake {
+    /// Where the top left cell of the board is drawn, in pixels.
+    origin: Point,
+    columns: i32,
+    rows: i32,
+    /// The cells the snake is on, from its head to its tail.
+    body: VecDeque<Cell>,
+    direction: Direction,
+    /// Turns that were pressed but not made yet, so pressing two quickly makes both.
+    turns: VecDeque<Direction>,
+    food: Option<Cell>,
+    eaten: u32,
+    autopilot: bool,
+    /// Set once the autopilot has played any of the round.
+    helped: bool,
+    rng: Rng,
+    /// Cells to draw again, and what color.
+    changed: Vec<(Cell, Rgb888)>,
+}
+
+impl Snake {
+    fn new(area: Rectangle, autopilot: bool) -> Self {
+        // Leave room for the walls around the board.
+        let columns = ((area.size.width.saturating_sub(8) / CELL_SIZE) as i32).clamp(4, MAX_COLUMNS);
+        let rows = ((area.size.height.saturating_sub(8) / CELL_SIZE) as i32).clamp(4, MAX_ROWS);
+        let origin = area.top_left
+            + Point::new(
+                (area.size.width as i32 - columns * CELL_SIZE as i32) / 2,
+                (area.size.height as i32 - rows * CELL_SIZE as i32) / 2,
+            );
+        let (x, y) = (columns / 4, rows / 2);
+        let mut snake = Self {
+            origin,
+            columns,
+            rows,
+            body: VecDeque::from([(x, y), (x - 1, y), (x - 2, y)]),
+            direction: Direction::Right,
+            turns: VecDeque::new(),
+            food: None,
+            eaten: 0,
+            autopilot,
+            helped: autopilot,
+            rng: Rng::new(),
+            changed: Vec::new(),
+        };
+        snake.place_food();
+        snake
+    }
+
+    /// Returns the cell next to `cell` in `direction`, or `None` if that is off the board.
+    fn neighbour(&self, (x, y): Cell, direction: Direction) -> Option<Cell> {
+        let (x, y) = match direction {
+            Direction::Up => (x, y - 1),
+            Direction::Down => (x, y + 1),
+            Direction::Left => (x - 1, y),
+            Direction::Right => (x + 1, y),
+        };
+        if (0..self.columns).contains(&x) && (0..self.rows).contains(&y) {
+            Some((x, y))
+        } else {
+            None
+        }
+    }
+
+    fn index(&self, (x, y): Cell) -> usize {
+        (y * self.columns + x) as usize
+    }
+
+    /// Puts the food on a free cell, or nowhere if the snake fills the board.
+    fn place_food(&mut self) {
+        let occupied = self.occupied(&self.body, false);
+        let free: Vec<Cell> = (0..self.rows)
+            .flat_map(|y| (0..self.columns).map(move |x| (x, y)))
+            .filter(|&cell| !occupied[self.index(cell)])
+            .collect();
+        self.food = (!free.is_empty()).then(|| free[self.rng.below(free.len())]);
+        if let Some(food) = self.food {
+            self.changed.push((food, FOOD_COLOR));
+        }
+    }
+
+    /// Returns which cells of the board `body` is on. The tail can be left out, since it moves
+    /// out of the way as the head moves on.
+    fn occupied(&self, body: &VecDeque<Cell>, without_tail: bool) -> Vec<bool> {
+        let mut occupied = vec![false; (self.columns * self.rows) as usize];
+        let count = if without_tail { body.len() - 1 } else { body.len() };
+        for &cell in body.iter().take(count) {
+            occupied[self.index(cell)] = true;
+        }
+        occupied
+    }
+
+    /// Finds the shortest way from the head of `body` to `to`, around the rest of it.
+    fn path(&self, body: &VecDeque<Cell>, to: Cell) -> Option<Vec<Direction>> {
+        let from = body[0];
+        let mut seen = self.occupied(body, true);
+        let mut came_from: Vec<Option<Direction>> = vec![None; seen.len()];
+        let mut queue = VecDeque::from([from]);
+        seen[self.index(from)] = true;
+        while let Some(cell) = queue.pop_front() {
+            if cell == to {
+                let mut path = Vec::new();
+                let mut cell = to;
+                while let Some(direction) = came_from[self.index(cell)] {
+                    path.push(direction);
+                    cell = self.neighbour(cell, direction.opposite())?;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for direction in Direction::ALL {
+                let Some(next) = self.neighbour(cell, direction) else {
+                    continue;
+                };
+                if !seen[self.index(next)] {
+                    seen[self.index(next)] = true;
+                    came_from[self.index(next)] = Some(direction);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// Counts the cells the head of `body` can get to.
+    fn room(&self, body: &VecDeque<Cell>) -> usize {
+        let mut seen = self.occupied(body, true);
+        let mut stack = vec![body[0]];
+        let mut count = 0;
+        while let Some(cell) = stack.pop() {
+            for direction in Direction::ALL {
+                if let Some(next) = self.neighbour(cell, direction) {
+                    if !seen[self.index(next)] {
+                        seen[self.index(next)] = true;
+                        count += 1;
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// Returns where `body` would be after moving along `path`, eating the food if it gets there.
+    fn moved(&self, body: &VecDeque<Cell>, path: &[Direction]) -> Option<VecDeque<Cell>> {
+        let mut body = body.clone();
+        for &direction in path {
+            let head = self.neighbour(body[0], direction)?;
+            body.push_front(head);
+            if Some(head) != self.food {
+                body.pop_back();
+            }
+        }
+        Some(body)
+    }
+
+    /// Whether the head of `body` could get to its tail, which means it can keep going forever.
+    fn can_reach_tail(&self, body: &VecDeque<Cell>) -> bool {
+        body.len() < 3 || self.path(body, body[body.len() - 1]).is_some()
+    }
+
+    /// Decides which way the autopilot goes.
+    fn autopilot_direction(&self) -> Direction {
+        // The shortest way to the food, if it doesn't leave the snake trapped.
+        if let Some(path) = self.food.and_then(|food| self.path(&self.body, food)) {
+            if let Some(body) = self.moved(&self.body, &path) {
+                if !path.is_empty() && self.can_reach_tail(&body) {
+                    return path[0];
+                }
+            }
+        }
+
+        // Otherwise a safe step that is furthest from the tail, to go the long way round to it
+        // and give the way to the food time to open up.
+        let occupied = self.occupied(&self.body, true);
+        let moves: Vec<(Direction, VecDeque<Cell>)> = Direction::ALL
+            .iter()
+            .filter_map(|&direction| {
+                let next = self.neighbour(self.body[0], direction)?;
+                if occupied[self.index(next)] {
+                    return None;
+                }
+                Some((direction, self.moved(&self.body, &[direction])?))
+            })
+            .collect();
+        let safe = moves
+            .iter()
+            .filter_map(|(direction, body)| {
+                let tail = body[body.len() - 1];
+                let distance = self.path(body, tail)?.len();
+                Some((*direction, distance))
+            })
+            .max_by_key(|&(_, distance)| distance);
+        if let Some((direction, _)) = safe {
+            return direction;
+        }
+
+        // Otherwise, the most room to move in, and hope.
+        moves
+            .iter()
+            .max_by_key(|(_, body)| self.room(body))
+            .map_or(self.direction, |(direction, _)| *direction)
+    }
+
+    /// Queues a turn pressed by the player, unless it goes back the way the snake came, or is
+    /// the way it is going anyway.
+    fn turn(&mut self, direction: Direction) {
+        let last = self.turns.back().copied().unwrap_or(self.direction);
+        if direction != last && direction != last.opposite() && self.turns.len() < 2 {
+            self.turns.push_back(direction);
+        }
+    }
+
+    /// Draws a cell, a little smaller than the cell so the snake's segments stand apart.
+    fn draw_cell(&self, writer: &mut FrameBufferWriter, (x, y): Cell, color: Rgb888) {
+        let top_left = self.origin + Point::new(x * CELL_SIZE as i32 + 1, y * CELL_SIZE as i32 + 1);
+        _ = Rectangle::new(top_left, Size::new(CELL_SIZE - 2, CELL_SIZE - 2))
+            .into_styled(PrimitiveStyle::with_fill(color))
+            .draw(writer);
+    }
+}
+
+impl Game for Snake {
+    const KEYS: &'static str = "Arrows or WASD steer, Tab autopilot";
+
+    fn interval(&self) -> u64 {
+        START_INTERVAL.saturating_sub((self.eaten / SPEED_UP_EVERY) as u64).max(MIN_INTERVAL)
+    }
+
+    fn key(&mut self, key: DecodedKey) -> bool {
+        match key {
+            DecodedKey::Unicode('\t') => {
+                self.autopilot = !self.autopilot;
+                self.helped = true;
+                self.turns.clear();
+            }
+            // The player can't steer while the autopilot is.
+            _ if self.autopilot => {}
+            DecodedKey::RawKey(KeyCode::ArrowUp) | DecodedKey::Unicode('w' | 'W') => self.turn(Direction::Up),
+            DecodedKey::RawKey(KeyCode::ArrowDown) | DecodedKey::Unicode('s' | 'S') => self.turn(Direction::Down),
+            DecodedKey::RawKey(KeyCode::ArrowLeft) | DecodedKey::Unicode('a' | 'A') => self.turn(Direction::Left),
+            DecodedKey::RawKey(KeyCode::ArrowRight) | DecodedKey::Unicode('d' | 'D') => self.turn(Direction::Right),
+            _ => {}
+        }
+        true
+    }
+
+    fn step(&mut self) -> bool {
+        self.direction = if self.autopilot {
+            self.autopilot_direction()
+        } else {
+            self.turns.pop_front().unwrap_or(self.direction)
+        };
+        let head = self.body[0];
+        let next = self.neighbour(head, self.direction);
+        let eats = next.is_some() && next == self.food;
+        // The tail moves out of the way first, unless the snake is growing.
+        if !eats {
+            if let Some(tail) = self.body.pop_back() {
+                self.changed.push((tail, Rgb888::BLACK));
+            }
+        }
+        let Some(next) = next.filter(|next| !self.body.contains(next)) else {
+            let body: Vec<Cell> = self.body.iter().copied().collect();
+            self.changed.extend(body.into_iter().map(|cell| (cell, DEAD_COLOR)));
+            return false;
+        };
+        self.changed.push((head, BODY_COLOR));
+        self.changed.push((next, HEAD_COLOR));
+        self.body.push_front(next);
+        if eats {
+            self.eaten += 1;
+            self.place_food();
+            // A snake as big as the board has won.
+            return self.food.is_some();
+        }
+        true
+    }
+
+    fn draw(&mut self, writer: &mut FrameBufferWriter, all: bool) {
+        if all {
+            let size = Size::new(self.columns as u32 * CELL_SIZE, self.rows as u32 * CELL_SIZE);
+            _ = Rectangle::new(self.origin - Point::new(3, 3), size + Size::new(6, 6))
+                .into_styled(PrimitiveStyle::with_stroke(WALL_COLOR, 2))
+                .draw(writer);
+            for (index, &cell) in self.body.iter().enumerate() {
+                self.draw_cell(writer, cell, if index == 0 { HEAD_COLOR } else { BODY_COLOR });
+            }
+            if let Some(food) = self.food {
+                self.draw_cell(writer, food, FOOD_COLOR);
+            }
+        }
+        for (cell, color) in core::mem::take(&mut self.changed) {
+            self.draw_cell(writer, cell, color);
+        }
+    }
+
+    fn status(&self) -> String {
+        let autopilot = if self.autopilot { "  [autopilot]" } else { "" };
+        format!("Snake  Score: {}  Length: {}{autopilot}", self.score(), self.body.len())
+    }
+
+    fn score(&self) -> u32 {
+        self.eaten * FOOD_POINTS
+    }
+
+    fn counts(&self) -> bool {
+        !self.helped
+    }
+}
@@ -0,0 +1,368 @@
+//! Tetris: move and turn the falling pieces so they fill rows of the well, which then clear.
+//! Clearing more rows at once is worth more, and every ten rows cleared the pieces fall faster.
+//! The game is over when a piece can't come into the well.
+//!
+//! Pieces come in bags of all seven, shuffled, so none is ever long in coming. The outline at the
+//! bottom shows where the piece would land if dropped.
+use super::{Game, Rng};
+use crate::framebuffer::writer::FrameBufferWriter;
+use alloc::{format, string::String, vec::Vec};
+use embedded_graphics::{
+    pixelcolor::{Rgb888, WebColors},
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle}
+};
+use pc_keyboard::{DecodedKey, KeyCode};
+
+/// How many cells wide and deep the well is.
+const COLUMNS: usize = 10;
+const ROWS: usize = 20;
+
+/// The biggest a cell can be, in pixels. They are smaller if the screen isn't tall enough.
+const MAX_CELL_SIZE: u32 = 24;
+
+/// What clearing one, two, three or four rows at once is worth, times the level plus one.
+const LINE_POINTS: [u32; 4] = [40, 100, 300, 1200];
+
+const LINES_PER_LEVEL: u32 = 10;
+
+/// How many timer ticks a piece takes to fall a row at level 0, how many fewer each level
+/// after, and the fewest.
+const START_INTERVAL: u64 = 80;
+const LEVEL_SPEED_UP: u64 = 7;
+const MIN_INTERVAL: u64 = 6;
+
+/// How far a piece that can't turn where it is tries moving sideways to make room.
+const KICKS: [i32; 5] = [0, -1, 1, -2, 2];
+
+const WALL_COLOR: Rgb888 = Rgb888::new(0x80, 0x80, 0x80);
+const GHOST_COLOR: Rgb888 = Rgb888::new(0x60, 0x60, 0x60);
+const LABEL_COLOR: Rgb888 = Rgb888::new(0xe0, 0xe0, 0xe0);
+
+/// Plays Tetris until `q` is pressed.
+///
+/// ### params:
+/// - `best`: the high score so far.
+pub fn play(best: &mut u32) {
+    super::play(Tetris::new, best);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    I,
+    O,
+    T,
+    S,
+    Z,
+    J,
+    L,
+}
+
+impl Kind {
+    const ALL: [Kind; 7] = [Kind::I, Kind::O, Kind::T, Kind::S, Kind::Z, Kind::J, Kind::L];
+
+    /// The piece's cells as it comes into the well, in a square [size](Kind::size) cells wide.
+    fn cells(self) -> [(i32, i32); 4] {
+        match self {
+            Kind::I => [(0, 1), (1, 1), (2, 1), (3, 1)],
+            Kind::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+            Kind::T => [(1, 0), (0, 1), (1, 1), (2, 1)],
+            Kind::S => [(1, 0), (2, 0), (0, 1), (1, 1)],
+            Kind::Z => [(0, 0), (1, 0), (1, 1), (2, 1)],
+            Kind::J => [(0, 0), (0, 1), (1, 1), (2, 1)],
+            Kind::L => [(2, 0), (0, 1), (1, 1), (2, 1)],
+        }
+    }
+
+    /// How wide the square the piece turns in is.
+    fn size(self) -> i32 {
+        match self {
+            Kind::I => 4,
+            Kind::O => 2,
+            _ => 3,
+        }
+    }
+
+    fn color(self) -> Rgb888 {
+        match self {
+            Kind::I => Rgb888::CSS_CYAN,
+            Kind::O => Rgb888::CSS_GOLD,
+            Kind::T => Rgb888::CSS_MEDIUM_ORCHID,
+            Kind::S => Rgb888::CSS_LIME_GREEN,
+            Kind::Z => Rgb888::CSS_CRIMSON,
+            Kind::J => Rgb888::CSS_ROYAL_BLUE,
+            Kind::L => Rgb888::CSS_DARK_ORANGE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Piece {
+    kind: Kind,
+    /// Where the top left of the square the piece turns in is, in the well. Rows above the
+    /// well are negative.
+    x: i32,
+    y: i32,
+    /// How many quarter turns clockwise it has made.
+    rotation: u8,
+}
+
+impl Piece {
+    /// A piece at the top of the well, in the middle.
+    fn new(kind: Kind) -> Self {
+        Self { kind, x: (COLUMNS as i32 - kind.size()) / 2, y: 0, rotation: 0 }
+    }
+
+    /// Returns the cells of the well the piece is on.
+    fn cells(&self) -> [(i32, i32); 4] {
+        let size = self.kind.size();
+        self.kind.cells().map(|(mut x, mut y)| {
+            for _ in 0..self.rotation {
+                (x, y) = (size - 1 - y, x);
+            }
+            (self.x + x, self.y + y)
+        })
+    }
+
+    fn moved(&self, x: i32, y: i32) -> Self {
+        Self { x: self.x + x, y: self.y + y, ..*self }
+    }
+
+    /// Returns the piece turned by a quarter clockwise, or anticlockwise if `clockwise` is false.
+    fn turned(&self, clockwise: bool) -> Self {
+        let rotation = if clockwise { self.rotation + 1 } else { self.rotation + 3 };
+        Self { rotation: rotation % 4, ..*self }
+    }
+}
+
+struct Tetris {
+    /// Where the top left cell of the well is drawn, in pixels.
+    origin: Point,
+    /// Where the top left cell of the next piece is drawn.
+    preview: Point,
+    cell_size: u32,
+    /// What is in each cell of the well, row by row from the top.
+    well: [[Option<Kind>; COLUMNS]; ROWS],
+    piece: Piece,
+    next: Kind,
+    /// The pieces still to come from the current bag.
+    bag: Vec<Kind>,
+    rng: Rng,
+    score: u32,
+    lines: u32,
+    /// Set when the well or the pieces have changed since they were last drawn.
+    changed: bool,
+}
+
+impl Tetris {
+    fn new(area: Rectangle) -> Self {
+        let cell_size = (area.size.height.saturating_sub(8) / ROWS as u32).clamp(4, MAX_CELL_SIZE);
+        // The well, and the next piece to the right of it.
+        let width = (COLUMNS as u32 + 6) * cell_size;
+        let height = ROWS as u32 * cell_size;
+        let origin = area.top_left
+            + Point::new(
+                (area.size.width as i32 - width as i32) / 2,
+                (area.size.height as i32 - height as i32) / 2,
+            );
+        let preview = origin + Point::new((COLUMNS as u32 * cell_size + 2 * cell_size) as i32, 2 * cell_size as i32);
+        let mut tetris = Self {
+            origin,
+            preview,
+            cell_size,
+            well: [[None; COLUMNS]; ROWS],
+            piece: Piece::new(Kind::I),
+            next: Kind::I,
+            bag: Vec::new(),
+            rng: Rng::new(),
+            score: 0,
+            lines: 0,
+            changed: true,
+        };
+        let first = tetris.take_from_bag();
+        tetris.piece = Piece::new(first);
+        tetris.next = tetris.take_from_bag();
+        tetris
+    }
+
+    fn level(&self) -> u32 {
+        self.lines / LINES_PER_LEVEL
+    }
+
+    /// Takes the next piece out of the bag, filling it with all seven, shuffled, when it is empty.
+    fn take_from_bag(&mut self) -> Kind {
+        if self.bag.is_empty() {
+            self.bag.extend(Kind::ALL);
+            for index in (1..self.bag.len()).rev() {
+                let other = self.rng.below(index + 1);
+                self.bag.swap(index, other);
+            }
+        }
+        self.bag.pop().unwrap_or(Kind::I)
+    }
+
+    /// Whether `piece` is inside the well, clear of what has landed. It can stick out of the
+    /// top.
+    fn fits(&self, piece: &Piece) -> bool {
+        piece.cells().iter().all(|&(x, y)| {
+            (0..COLUMNS as i32).contains(&x)
+                && y < ROWS as i32
+                && (y < 0 || self.well[y as usize][x as usize].is_none())
+        })
+    }
+
+    /// Moves the piece if it fits where it would go.
+    ///
+    /// ### returns:
+    /// - `true` if it moved.
+    fn try_move(&mut self, x: i32, y: i32) -> bool {
+        let moved = self.piece.moved(x, y);
+        if self.fits(&moved) {
+            self.piece = moved;
+            self.changed = true;
+        }
+        self.fits(&moved)
+    }
+
+    /// Turns the piece, moving it sideways if it has to, to fit.
+    fn turn(&mut self, clockwise: bool) {
+        let turned = self.piece.turned(clockwise);
+        if let Some(kicked) = KICKS.iter().map(|&x| turned.moved(x, 0)).find(|piece| self.fits(piece)) {
+            self.piece = kicked;
+            self.changed = true;
+        }
+    }
+
+    /// Where the piece would land if it were dropped.
+    fn landing(&self) -> Piece {
+        let mut piece = self.piece;
+        while self.fits(&piece.moved(0, 1)) {
+            piece = piece.moved(0, 1);
+        }
+        piece
+    }
+
+    /// Leaves the piece where it is, clears any full rows, and brings on the next piece.
+    ///
+    /// ### returns:
+    /// - `false` if the game is over: the piece didn't fit in the well, or the next one can't
+    ///   come in.
+    fn land(&mut self) -> bool {
+        self.changed = true;
+        let cells = self.piece.cells();
+        if cells.iter().any(|&(_, y)| y < 0) {
+            return false;
+        }
+        for (x, y) in cells {
+            self.well[y as usize][x as usize] = Some(self.piece.kind);
+        }
+
+        let remaining: Vec<[Option<Kind>; COLUMNS]> =
+            self.well.iter().filter(|row| row.iter().any(Option::is_none)).copied().collect();
+        let cleared = ROWS - remaining.len();
+        if cleared > 0 {
+            self.score += LINE_POINTS[cleared - 1] * (self.level() + 1);
+            self.lines += cleared as u32;
+            self.well = [[None; COLUMNS]; ROWS];
+            self.well[cleared..].copy_from_slice(&remaining);
+        }
+
+        self.piece = Piece::new(self.next);
+        self.next = self.take_from_bag();
+        self.fits(&self.piece)
+    }
+
+    /// Fills cell `(x, y)` of a grid whose top left cell is at `origin`.
+    fn draw_cell(&self, writer: &mut FrameBufferWriter, origin: Point, (x, y): (i32, i32), style: PrimitiveStyle<Rgb888>) {
+        let size = self.cell_size as i32;
+        let top_left = origin + Point::new(x * size + 1, y * size + 1);
+        _ = Rectangle::new(top_left, Size::new(self.cell_size - 2, self.cell_size - 2))
+            .into_styled(style)
+            .draw(writer);
+    }
+}
+
+impl Game for Tetris {
+    const KEYS: &'static str = "Arrows move, up or x turn, z turn back, space drop";
+
+    fn interval(&self) -> u64 {
+        START_INTERVAL.saturating_sub(self.level() as u64 * LEVEL_SPEED_UP).max(MIN_INTERVAL)
+    }
+
+    fn key(&mut self, key: DecodedKey) -> bool {
+        match key {
+            DecodedKey::RawKey(KeyCode::ArrowLeft) | DecodedKey::Unicode('a' | 'A') => _ = self.try_move(-1, 0),
+            DecodedKey::RawKey(KeyCode::ArrowRight) | DecodedKey::Unicode('d' | 'D') => _ = self.try_move(1, 0),
+            DecodedKey::RawKey(KeyCode::ArrowUp) | DecodedKey::Unicode('x' | 'X' | 'w' | 'W') => self.turn(true),
+            DecodedKey::Unicode('z' | 'Z') => self.turn(false),
+            // A soft drop is worth a point a row, and a hard drop two.
+            DecodedKey::RawKey(KeyCode::ArrowDown) | DecodedKey::Unicode('s' | 'S') => {
+                if self.try_move(0, 1) {
+                    self.score += 1;
+                }
+            }
+            DecodedKey::Unicode(' ') => {
+                let landing = self.landing();
+                self.score += 2 * (landing.y - self.piece.y) as u32;
+                self.piece = landing;
+                return self.land();
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn step(&mut self) -> bool {
+        self.try_move(0, 1) || self.land()
+    }
+
+    fn draw(&mut self, writer: &mut FrameBufferWriter, all: bool) {
+        let size = self.cell_size;
+        if all {
+            let well = Size::new(COLUMNS as u32 * size, ROWS as u32 * size);
+            _ = Rectangle::new(self.origin - Point::new(3, 3), well + Size::new(6, 6))
+                .into_styled(PrimitiveStyle::with_stroke(WALL_COLOR, 2))
+                .draw(writer);
+            let label = self.preview - Point::new(0, size as i32 + 12);
+            super::draw_text(writer, label, "Next", LABEL_COLOR);
+            self.changed = true;
+        }
+        if !core::mem::take(&mut self.changed) {
+            return;
+        }
+
+        let empty = PrimitiveStyle::with_fill(Rgb888::BLACK);
+        for (y, row) in self.well.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let style = cell.map_or(empty, |kind| PrimitiveStyle::with_fill(kind.color()));
+                self.draw_cell(writer, self.origin, (x as i32, y as i32), style);
+            }
+        }
+        let ghost = PrimitiveStyle::with_stroke(GHOST_COLOR, 1);
+        for cell in self.landing().cells().into_iter().filter(|&(_, y)| y >= 0) {
+            self.draw_cell(writer, self.origin, cell, ghost);
+        }
+        let piece = PrimitiveStyle::with_fill(self.piece.kind.color());
+        for cell in self.piece.cells().into_iter().filter(|&(_, y)| y >= 0) {
+            self.draw_cell(writer, self.origin, cell, piece);
+        }
+
+        for y in 0..2 {
+            for x in 0..4 {
+                self.draw_cell(writer, self.preview, (x, y), empty);
+            }
+        }
+        let next = PrimitiveStyle::with_fill(self.next.color());
+        for cell in self.next.cells() {
+            self.draw_cell(writer, self.preview, cell, next);
+        }
+    }
+
+    fn status(&self) -> String {
+        format!("Tetris  Score: {}  Lines: {}  Level: {}", self.score, self.lines, self.level())
+    }
+
+    fn score(&self) -> u32 {
+        self.score
+    }
+}
@@ -0,0 +1,109 @@
+//! The initramfs: files packed into the disk image at build time, which the bootloader loads
+//! into memory as the ramdisk.
+//!
+//! It is a `newc` cpio archive, the same format Linux uses for its initramfs, of everything in
+//! the `initramfs/` directory of the repository. The build script writes it.
+use core::str;
+use spin::Once;
+
+/// The archive, as loaded by the bootloader. Unset if there is no ramdisk.
+static ARCHIVE: Once<&'static [u8]> = Once::new();
+
+/// The magic number every `newc` header starts with.
+const MAGIC: &[u8] = b"070701";
+
+/// The size of a `newc` header: the magic number, and 13 fields of 8 hex digits.
+const HEADER_LEN: usize = 6 + 13 * 8;
+
+/// The name of the entry that ends the archive.
+const TRAILER: &str = "TRAILER!!!";
+
+/// A file in the initramfs.
+#[derive(Debug, Clone, Copy)]
+pub struct File {
+    /// The file's path, relative to the root of the archive, like `portfolio/about.txt`.
+    pub path: &'static str,
+    pub data: &'static [u8],
+}
+
+/// Finds the initramfs the bootloader loaded.
+///
+/// # Safety
+///
+/// `addr` and `len` must describe the ramdisk as the bootloader mapped it, which stays mapped for
+/// as long as the kernel runs.
+pub unsafe fn init(addr: Option<u64>, len: u64) {
+    if let Some(addr) = addr {
+        let archive = unsafe { core::slice::from_raw_parts(addr as *const u8, len as usize) };
+        ARCHIVE.call_once(|| archive);
+    }
+}
+
+/// Returns an iterator over every file in the initramfs. There are none if there is no ramdisk.
+///
+/// A damaged archive is read up to the first damaged entry.
+pub fn files() -> Files {
+    Files { rest: ARCHIVE.get().copied().unwrap_or_default() }
+}
+
+/// Returns the contents of the file at `path`, relative to the root of the archive.
+pub fn read(path: &str) -> Option<&'static [u8]> {
+    files().find(|file| file.path == path).map(|file| file.data)
+}
+
+/// An iterator over the files in the initramfs, returned by [files].
+pub struct Files {
+    rest: &'static [u8],
+}
+
+impl Iterator for Files {
+    type Item = File;
+
+    fn next(&mut self) -> Option<File> {
+        loop {
+            let header = self.rest.get(..HEADER_LEN)?;
+            if !header.starts_with(MAGIC) {
+                self.rest = &[];
+                return None;
+            }
+            // The fields after the magic number are: ino, mode, uid, gid, nlink, mtime, filesize,
+            // devmajor, devminor, rdevmajor, rdevminor, namesize and check.
+            let field = |index: usize| {
+                let start = MAGIC.len() + index * 8;
+                str::from_utf8(&header[start..start + 8]).ok().and_then(|hex| usize::from_str_radix(hex, 16).ok())
+            };
+            let (Some(mode), Some(size), Some(name_size)) = (field(1), field(6), field(11)) else {
+                self.rest = &[];
+                return None;
+            };
+
+            // The name, and then the data, are each padded to a multiple of 4 bytes. The sizes
+            // come from the archive, so they can't be trusted not to overflow.
+            let data_start = (HEADER_LEN + name_size).checked_next_multiple_of(4);
+            let data_end = data_start.and_then(|start| start.checked_add(size));
+            let (Some(data_start), Some(data_end)) = (data_start, data_end) else {
+                self.rest = &[];
+                return None;
+            };
+            let name = self.rest.get(HEADER_LEN..(HEADER_LEN + name_size).saturating_sub(1));
+            let (Some(name), Some(data)) = (name, self.rest.get(data_start..data_end)) else {
+                self.rest = &[];
+                return None;
+            };
+            let Ok(path) = str::from_utf8(name) else {
+                self.rest = &[];
+                return None;
+            };
+            self.rest = data_end.checked_next_multiple_of(4).and_then(|next| self.rest.get(next..)).unwrap_or_default();
+
+            if path == TRAILER {
+                self.rest = &[];
+                return None;
+            }
+            // Skip directories and anything else that isn't a regular file.
+            if mode & 0o170000 == 0o100000 {
+                return Some(File { path, data });
+            }
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! Input from the keyboard, the mouse and the serial port, as one stream of [Event]s.
+//!
+//! The interrupt handlers only turn what the devices send into events and [push] them: they
+//! don't know who reads them. Whatever is reading input, like the shell, the editor or a game,
+//! takes the [focus], and gets its own queue of the kinds of events it asked for. Events go to
+//! whoever took the focus last, until it lets go of it, when it goes back to whoever had it
+//! before, with what was waiting in their queue still there. What the one letting go didn't read
+//! is thrown away, so keys pressed in a game don't turn up at the shell's prompt.
+//!
+//! The [debugger](crate::debugger) runs with interrupts off, so nothing is pushed while it runs.
+//! It [polls](poll) the devices instead.
+pub mod mouse;
+
+use crate::{
+    keyboard::{self, KeyPress, Modifiers},
+    serial,
+    time
+};
+use alloc::{sync::Arc, vec::Vec};
+use core::{fmt, ops::BitOr, time::Duration};
+use crossbeam_queue::ArrayQueue;
+use pc_keyboard::{DecodedKey, KeyCode};
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+
+/// How many events can be waiting for each reader before new ones are dropped.
+const QUEUE_SIZE: usize = 128;
+
+/// Something that happened on an input device, and when.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    /// How long after the timer started it happened, to the nearest timer tick.
+    pub time: Duration,
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// A key went down, or repeated from being held down, with the modifier keys held then.
+    KeyDown(KeyCode, Modifiers),
+    /// A key was let go of.
+    KeyUp(KeyCode, Modifiers),
+    /// A key was typed, as the keyboard layout reads it: a character, or a key that has none, like
+    /// an arrow. Modifier and lock keys aren't typed.
+    Key(KeyPress),
+    Mouse(mouse::Motion),
+    /// A byte came in on the serial port.
+    Serial(u8),
+}
+
+impl EventKind {
+    /// The one kind of [Kinds] this is.
+    pub fn kinds(&self) -> Kinds {
+        match self {
+            EventKind::KeyDown(..) | EventKind::KeyUp(..) => Kinds::KEY_CHANGES,
+            EventKind::Key(_) => Kinds::KEYS,
+            EventKind::Mouse(_) => Kinds::MOUSE,
+            EventKind::Serial(_) => Kinds::SERIAL,
+        }
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventKind::KeyDown(code, modifiers) => write!(f, "key down  {code:?}{modifiers}"),
+            EventKind::KeyUp(code, modifiers) => write!(f, "key up    {code:?}{modifiers}"),
+            EventKind::Key(press) => {
+                let modifiers = Modifiers { ctrl: press.ctrl, alt: press.alt, shift: press.shift };
+                match press.key {
+                    DecodedKey::Unicode(ch) if ch.is_control() => write!(f, "typed     {ch:?}{modifiers}"),
+                    DecodedKey::Unicode(ch) => write!(f, "typed     '{ch}'{modifiers}"),
+                    DecodedKey::RawKey(code) => write!(f, "typed     {code:?}{modifiers}"),
+                }
+            }
+            EventKind::Mouse(motion) => write!(f, "mouse     {motion}"),
+            EventKind::Serial(byte) => write!(f, "serial    {byte:#04x} {:?}", *byte as char),
+        }
+    }
+}
+
+/// Which kinds of events a reader wants, put together with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Kinds(u8);
+
+impl Kinds {
+    /// Keys typed: [EventKind::Key].
+    pub const KEYS: Kinds = Kinds(1 << 0);
+    /// Keys going down and up: [EventKind::KeyDown] and [EventKind::KeyUp].
+    pub const KEY_CHANGES: Kinds = Kinds(1 << 1);
+    pub const MOUSE: Kinds = Kinds(1 << 2);
+    pub const SERIAL: Kinds = Kinds(1 << 3);
+    pub const ALL: Kinds = Kinds(0xf);
+
+    pub fn contains(self, other: Kinds) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for Kinds {
+    type Output = Kinds;
+
+    fn bitor(self, other: Kinds) -> Kinds {
+        Kinds(self.0 | other.0)
+    }
+}
+
+/// A reader on the [FOCUS] stack.
+struct Reader {
+    kinds: Kinds,
+    /// Lock-free, so [push] can add to it while the reader is in the middle of taking from it.
+    queue: Arc<ArrayQueue<Event>>,
+}
+
+/// Everyone who has taken the focus and not let go yet, the one with it last. Only changed with
+/// interrupts off, so the interrupt handlers never find it locked.
+static FOCUS: Mutex<Vec<Reader>> = Mutex::new(Vec::new());
+
+/// Takes the focus, so events of the `kinds` given come to the [Focus] returned, until it is
+/// dropped.
+pub fn focus(kinds: Kinds) -> Focus {
+    let queue = Arc::new(ArrayQueue::new(QUEUE_SIZE));
+    let reader = Reader { kinds, queue: queue.clone() };
+    interrupts::without_interrupts(|| FOCUS.lock().push(reader));
+    Focus { queue }
+}
+
+/// Hands an event to whoever has the focus, stamped with the time now. Called from interrupt
+/// handlers, so it does nothing else.
+///
+/// The event is dropped if they don't want its kind, or their queue is full.
+pub fn push(kind: EventKind) {
+    let event = Event { time: time::uptime(), kind };
+    // The debugger may have stopped the code changing the focus.
+    let Some(focus) = FOCUS.try_lock() else {
+        return;
+    };
+    if let Some(reader) = focus.last().filter(|reader| reader.kinds.contains(kind.kinds())) {
+        _ = reader.queue.push(event);
+    }
+}
+
+/// Reads a key typed on the keyboard, or a byte from the serial port, straight from the device,
+/// without going through the interrupt handlers or anyone's queue. This is for when interrupts
+/// are off, like in the [debugger](crate::debugger).
+pub fn poll() -> Option<Event> {
+    let kind = keyboard::poll_key_press()
+        .map(EventKind::Key)
+        .or_else(|| serial::try_read_byte().map(EventKind::Serial))?;
+    Some(Event { time: time::uptime(), kind })
+}
+
+/// The focus, taken by [focus], and the queue of events that come with it. Dropping it lets go of
+/// the focus.
+pub struct Focus {
+    queue: Arc<ArrayQueue<Event>>,
+}
+
+impl Focus {
+    /// Returns the next event, if there is one waiting.
+    pub fn try_read_event(&self) -> Option<Event> {
+        self.queue.pop()
+    }
+
+    /// Waits for the next event, halting the CPU until then.
+    pub fn read_event(&self) -> Event {
+        loop {
+            // Check the queue with interrupts off, so an event can't arrive between finding the
+            // queue empty and halting, which would leave it waiting there until the next one.
+            interrupts::disable();
+            if let Some(event) = self.try_read_event() {
+                interrupts::enable();
+                return event;
+            }
+            interrupts::enable_and_hlt();
+        }
+    }
+
+    /// Returns the next key typed, with the modifiers held down with it, if there is one waiting.
+    /// Other events before it are thrown away.
+    pub fn try_read_key_press(&self) -> Option<KeyPress> {
+        loop {
+            if let EventKind::Key(press) = self.try_read_event()?.kind {
+                return Some(press);
+            }
+        }
+    }
+
+    /// Waits for the next key to be typed, like [Focus::read_key], and returns it with the
+    /// modifiers held down with it, for shortcuts like Ctrl+Backspace.
+    pub fn read_key_press(&self) -> KeyPress {
+        loop {
+            if let EventKind::Key(press) = self.read_event().kind {
+                return press;
+            }
+        }
+    }
+
+    /// Returns the next key typed, if there is one waiting.
+    pub fn try_read_key(&self) -> Option<DecodedKey> {
+        self.try_read_key_press().map(|press| press.key)
+    }
+
+    /// Waits for the next key to be typed.
+    pub fn read_key(&self) -> DecodedKey {
+        self.read_key_press().key
+    }
+}
+
+impl Drop for Focus {
+    fn drop(&mut self) {
+        interrupts::without_interrupts(|| {
+            FOCUS.lock().retain(|reader| !Arc::ptr_eq(&reader.queue, &self.queue));
+        });
+    }
+}
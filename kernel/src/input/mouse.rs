@@ -0,0 +1,72 @@
+//! The PS/2 mouse, on the i8042's [second port](crate::keyboard::i8042).
+//!
+//! The mouse sends a packet of three bytes each time it moves or a button changes, which the
+//! interrupt handler passes here a byte at a time.
+use super::{push, EventKind};
+use core::fmt;
+use spin::Mutex;
+
+/// Bit 3 of the first byte of a packet is always set, which is how a lost byte is noticed.
+const ALWAYS_SET: u8 = 1 << 3;
+const X_SIGN: u8 = 1 << 4;
+const Y_SIGN: u8 = 1 << 5;
+/// Bits 6 and 7: the mouse moved further than it could count.
+const OVERFLOW: u8 = 0b1100_0000;
+
+/// The mouse moving, or a button changing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Motion {
+    /// How far it moved right, in the mouse's own units.
+    pub dx: i16,
+    /// How far it moved up. The screen's y goes down, so this is the other way around to it.
+    pub dy: i16,
+    pub left: bool,
+    pub right: bool,
+    pub middle: bool,
+}
+
+impl fmt::Display for Motion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:+4} {:+4}", self.dx, self.dy)?;
+        for (held, name) in [(self.left, "left"), (self.right, "right"), (self.middle, "middle")] {
+            if held {
+                write!(f, " {name}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The packet so far, and how much of it there is.
+static PACKET: Mutex<([u8; 3], usize)> = Mutex::new(([0; 3], 0));
+
+/// Adds a byte from the mouse to the packet it is part of, and pushes the [Motion] it finishes.
+/// Called from the mouse's interrupt handler.
+pub fn handle_byte(byte: u8) {
+    let mut packet = PACKET.lock();
+    let (bytes, length) = &mut *packet;
+    if *length == 0 && byte & ALWAYS_SET == 0 {
+        // Out of step: wait for the start of a packet.
+        return;
+    }
+    bytes[*length] = byte;
+    *length += 1;
+    if *length < 3 {
+        return;
+    }
+    *length = 0;
+
+    let [flags, x, y] = *bytes;
+    if flags & OVERFLOW != 0 {
+        return;
+    }
+    // The movements are 9 bit numbers, with the top bit in the first byte.
+    let extend = |low: u8, negative: bool| i16::from(low) - if negative { 0x100 } else { 0 };
+    push(EventKind::Mouse(Motion {
+        dx: extend(x, flags & X_SIGN != 0),
+        dy: extend(y, flags & Y_SIGN != 0),
+        left: flags & (1 << 0) != 0,
+        right: flags & (1 << 1) != 0,
+        middle: flags & (1 << 2) != 0,
+    }));
+}
@@ -1,10 +1,11 @@
 use core::cell::UnsafeCell;
 use spin::Mutex;
 use x2apic::ioapic::IoApic;
+use super::IRQ_BASE;
 
 pub unsafe fn io_apic_init(apic_base: u64) -> IoApic {
     let mut io_apic = IoApic::new(apic_base);
-    io_apic.init(0x23);
+    io_apic.init(IRQ_BASE);
     io_apic
 }
 
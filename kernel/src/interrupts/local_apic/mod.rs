@@ -2,6 +2,7 @@ use spin::Mutex;
 use x2apic::lapic::{LocalApic, LocalApicBuilder, TimerMode};
 use x86_64::instructions::port::Port;
 use core::cell::UnsafeCell;
+use super::{APIC_ERROR_VECTOR, SPURIOUS_VECTOR, TIMER_VECTOR};
 
 
 pub fn local_apic_init(apic_base: usize) -> LocalApic {
@@ -57,9 +58,9 @@ pub fn local_apic_init(apic_base: usize) -> LocalApic {
     // Configure Local APIC with calibrated values
     let mut builder = LocalApicBuilder::new();
     builder
-        .timer_vector(0x20)
-        .spurious_vector(0x2e)
-        .error_vector(0x2f)
+        .timer_vector(TIMER_VECTOR as usize)
+        .spurious_vector(SPURIOUS_VECTOR as usize)
+        .error_vector(APIC_ERROR_VECTOR as usize)
         .timer_mode(TimerMode::Periodic)
         .timer_divide(x2apic::lapic::TimerDivide::Div16) // Divider 16 (0x3)
         .timer_initial(ticks_in_10ms)
@@ -3,46 +3,112 @@ pub mod gdt;
 pub mod local_apic;
 pub mod io_apic;
 
-use pc_keyboard::DecodedKey;
+use core::sync::atomic::{AtomicU64, Ordering};
 use spin::lazy::Lazy;
-use spin::Mutex;
 use x86_64::{
     registers::control::Cr2,
     structures::{
-        idt::InterruptDescriptorTable,
+        idt::{InterruptDescriptorTable, PageFaultErrorCode},
         paging::{Mapper, OffsetPageTable, Page, PageTableFlags, PhysFrame, Size4KiB}
     },
     PhysAddr,
     VirtAddr,
     instructions::port::Port,
 };
-use crate::{framebuffer::{
+use crate::{debugger::{self, TrapFrame}, input, framebuffer::{
     ConsoleColor,
     color::ColoredWriting,
-    update_cursor,
-    global_writer::FRAMEBUFFER_WRITER
-}, hlt_loop, println, serial_eprintln, serial_println, print, interrupts::{
+    update_cursor
+}, hlt_loop, println, serial_eprintln, serial_println, interrupts::{
     local_apic::LOCAL_APIC,
     gdt::DOUBLE_FAULT_IST_INDEX
-}, memory::BootInfoFrameAllocator, serial_print};
-use crate::shell::GLOBAL_SHELL;
+}, memory::BootInfoFrameAllocator};
 
 
-static INTERRUPT_TIMER_COUNT: Mutex<u64> = Mutex::new(0);
+/// How often the local APIC timer fires, in milliseconds. It is calibrated against the PIT to
+/// count down this long, in [local_apic::local_apic_init].
+pub const TIMER_INTERVAL_MS: u64 = 10;
+
+/// The vector the local APIC timer fires on.
+pub const TIMER_VECTOR: u8 = 0x20;
+/// The vector of the local APIC's spurious interrupts. The local APIC is programmed with it, and
+/// the IDT's handler set on it, so the two can't disagree about where they come in.
+pub const SPURIOUS_VECTOR: u8 = 0x21;
+/// The vector the local APIC reports its errors on, shared by the local APIC and the IDT like
+/// [SPURIOUS_VECTOR].
+pub const APIC_ERROR_VECTOR: u8 = 0x22;
+/// The vector of IRQ 0 from the I/O APIC. IRQ n comes in on this plus n.
+pub const IRQ_BASE: u8 = 0x23;
+/// The vector of the keyboard, on IRQ 1.
+pub const KEYBOARD_VECTOR: u8 = IRQ_BASE + 1;
+/// The vector of the serial port COM1, on IRQ 4.
+pub const SERIAL_VECTOR: u8 = IRQ_BASE + 4;
+/// The vector of the PS/2 mouse, on IRQ 12.
+pub const MOUSE_VECTOR: u8 = IRQ_BASE + 12;
+
+/// A vector the kernel has a handler for, as `lsirq` lists them.
+#[derive(Debug, Clone, Copy)]
+pub struct Vector {
+    pub number: u8,
+    /// What the vector is for.
+    pub name: &'static str,
+    /// The function that handles it.
+    pub handler: &'static str,
+}
+
+/// Every vector [IDT] has a handler for.
+pub const VECTORS: [Vector; 12] = [
+    Vector { number: 0x01, name: "Debug exception", handler: "debugger" },
+    Vector { number: 0x02, name: "Non-maskable interrupt", handler: "nmi_interrupt_handler" },
+    Vector { number: 0x03, name: "Breakpoint", handler: "debugger" },
+    Vector { number: 0x08, name: "Double fault", handler: "double_fault_handler" },
+    Vector { number: 0x0d, name: "General protection fault", handler: "general_protection_fault" },
+    Vector { number: 0x0e, name: "Page fault", handler: "page_fault" },
+    Vector { number: TIMER_VECTOR, name: "Local APIC timer", handler: "timer_interrupt_handler" },
+    Vector { number: SPURIOUS_VECTOR, name: "Spurious interrupt", handler: "spurious_interrupt_handler" },
+    Vector { number: APIC_ERROR_VECTOR, name: "Local APIC error", handler: "apic_error_handler" },
+    Vector { number: KEYBOARD_VECTOR, name: "Keyboard (IRQ 1)", handler: "keyboard_interrupt_handler" },
+    Vector { number: SERIAL_VECTOR, name: "Serial port COM1 (IRQ 4)", handler: "serial_interrupt_handler" },
+    Vector { number: MOUSE_VECTOR, name: "PS/2 mouse (IRQ 12)", handler: "mouse_interrupt_handler" },
+];
+
+/// How many times the timer has fired since it was started.
+static INTERRUPT_TIMER_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// How many times each vector has come in.
+static INTERRUPT_COUNTS: [AtomicU64; 256] = [const { AtomicU64::new(0) }; 256];
+
+/// Counts an interrupt or exception on `vector`. Every handler calls this first.
+pub fn count(vector: u8) {
+    INTERRUPT_COUNTS[vector as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns how many times `vector` has come in since boot.
+pub fn interrupt_count(vector: u8) -> u64 {
+    INTERRUPT_COUNTS[vector as usize].load(Ordering::Relaxed)
+}
+
+/// Returns how many times the timer has fired since it was started, every
+/// [TIMER_INTERVAL_MS] milliseconds.
+pub fn timer_ticks() -> u64 {
+    INTERRUPT_TIMER_COUNT.load(Ordering::Relaxed)
+}
 
 fn create_idt() -> InterruptDescriptorTable {
     let mut idt = InterruptDescriptorTable::new();
     unsafe {
         idt.double_fault.set_handler_fn(double_fault_handler)
             .set_stack_index(DOUBLE_FAULT_IST_INDEX);
-    }    idt.breakpoint.set_handler_fn(breakpoint_handler);
-    idt.page_fault.set_handler_fn(page_fault_handler);
+    }
+    // Breakpoints, single steps, and the faults it can catch go to the debugger.
+    debugger::install(&mut idt);
     idt.non_maskable_interrupt.set_handler_fn(nmi_interrupt_handler);
-    idt[0x20].set_handler_fn(timer_interrupt_handler);
-    idt[0x21].set_handler_fn(spurious_interrupt_handler);
-    idt[0x22].set_handler_fn(apic_error_handler);
-    idt[0x24].set_handler_fn(keyboard_interrupt_handler);
-    idt.general_protection_fault.set_handler_fn(gp_interrupt_handler);
+    idt[TIMER_VECTOR].set_handler_fn(timer_interrupt_handler);
+    idt[SPURIOUS_VECTOR].set_handler_fn(spurious_interrupt_handler);
+    idt[APIC_ERROR_VECTOR].set_handler_fn(apic_error_handler);
+    idt[KEYBOARD_VECTOR].set_handler_fn(keyboard_interrupt_handler);
+    idt[SERIAL_VECTOR].set_handler_fn(serial_interrupt_handler);
+    idt[MOUSE_VECTOR].set_handler_fn(mouse_interrupt_handler);
     idt
 }
 
@@ -52,27 +118,20 @@ pub extern "x86-interrupt" fn double_fault_handler(
     stack_frame: x86_64::structures::idt::InterruptStackFrame,
     error_code: u64,
 ) -> ! {
+    count(0x08);
     serial_eprintln!("\x1b[31mDOUBLE FAULT ERROR CODE: {}\x1b[0m", error_code);
     serial_eprintln!("{:#?}", stack_frame);
     hlt_loop()
 }
 
-pub extern "x86-interrupt" fn breakpoint_handler(
-    stack_frame: x86_64::structures::idt::InterruptStackFrame,
-) {
-    println!("\x1b[31mBREAKPOINT EXCEPTION\x1b[0m");
-    println!("{:#?}", stack_frame);
-}
-
-pub extern "x86-interrupt" fn page_fault_handler(
-    stack_frame: x86_64::structures::idt::InterruptStackFrame,
-    err: x86_64::structures::idt::PageFaultErrorCode,
-) {
+/// Reports a page fault, and panics. The [debugger] calls this for every page fault, once it is
+/// done with it if it is catching faults.
+pub fn page_fault(frame: &TrapFrame) -> ! {
     let fault_addr = Cr2::read(); // Get faulting address
 
     println!("\x1b[31mPAGE FAULT EXCEPTION\x1b[0m");
-    serial_eprintln!("{:#?}", stack_frame);
-    serial_eprintln!("{:#?}", err);
+    serial_eprintln!("{:#?}", frame);
+    serial_eprintln!("{:#?}", PageFaultErrorCode::from_bits_truncate(frame.error_code));
     serial_eprintln!("{:#?}", fault_addr.unwrap());
     panic!("{}", "PAGE FAULT EXCEPTION".fg(ConsoleColor::Red).as_mut_str());
 }
@@ -80,11 +139,12 @@ pub extern "x86-interrupt" fn page_fault_handler(
 pub extern "x86-interrupt" fn timer_interrupt_handler(
     _stack_frame: x86_64::structures::idt::InterruptStackFrame,
 ) {
-    unsafe { FRAMEBUFFER_WRITER.force_unlock(); }
-    *INTERRUPT_TIMER_COUNT.lock() += 1;
-    if *INTERRUPT_TIMER_COUNT.lock() % 50 == 0 {
+    count(TIMER_VECTOR);
+    let count = INTERRUPT_TIMER_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
+    if count.is_multiple_of(50) {
         update_cursor();
     }
+    crate::keyboard::i8042::tick();
     let binding = LOCAL_APIC.lock();
     let apic = unsafe { binding.as_ref().unwrap().get_mut() };
     unsafe {
@@ -95,6 +155,7 @@ pub extern "x86-interrupt" fn timer_interrupt_handler(
 pub extern "x86-interrupt" fn nmi_interrupt_handler(
     _stack_frame: x86_64::structures::idt::InterruptStackFrame,
 ) {
+    count(0x02);
     println!("\x1b[31mNMI INTERRUPT\x1b[0m");
     serial_println!("NMI INTERRUPT");
     serial_eprintln!("{:#?}", _stack_frame);
@@ -103,10 +164,10 @@ pub extern "x86-interrupt" fn nmi_interrupt_handler(
 pub extern "x86-interrupt" fn spurious_interrupt_handler(
     _stack_frame: x86_64::structures::idt::InterruptStackFrame,
 ) {
+    count(SPURIOUS_VECTOR);
     serial_println!("SPURIOUS INTERRUPT");
     serial_println!("{:#?}", _stack_frame);
 
-    unsafe { LOCAL_APIC.force_unlock(); }
     let binding = LOCAL_APIC.lock();
 
     let apic = unsafe { binding.as_ref().unwrap().get_mut() };
@@ -119,9 +180,9 @@ pub extern "x86-interrupt" fn spurious_interrupt_handler(
 pub extern "x86-interrupt" fn apic_error_handler(
     _stack_frame: x86_64::structures::idt::InterruptStackFrame,
 ){
+    count(APIC_ERROR_VECTOR);
     serial_eprintln!("APIC_ERROR INTERRUPT");
     serial_println!("{:#?}", _stack_frame);
-    unsafe { LOCAL_APIC.force_unlock(); }
     let binding = LOCAL_APIC.lock();
     let apic = unsafe { binding.as_ref().unwrap().get_mut() };
     unsafe {
@@ -129,45 +190,37 @@ pub extern "x86-interrupt" fn apic_error_handler(
     }
 }
 
-pub extern "x86-interrupt" fn gp_interrupt_handler(
-    stack_frame: x86_64::structures::idt::InterruptStackFrame,
-    err_code: u64
-) {
+/// Reports a general protection fault, and panics. Like [page_fault], the [debugger] calls it.
+pub fn general_protection_fault(frame: &TrapFrame) -> ! {
     serial_println!("GP INTERRUPT HANDLER");
-    serial_eprintln!("{:#?}", stack_frame);
-    serial_eprintln!("{:#?}", err_code);
+    serial_eprintln!("{:#?}", frame);
+    serial_eprintln!("{:#?}", frame.error_code);
     panic!("\x1b[31mGP INTERRUPT HANDLER\x1b[0m");
 }
 
 pub extern "x86-interrupt" fn keyboard_interrupt_handler(
     _stack_frame: x86_64::structures::idt::InterruptStackFrame,
 ) {
+    count(KEYBOARD_VECTOR);
     let mut port = Port::new(0x60);
     let scancode: u8 = unsafe { port.read() };
-    let key = crate::keyboard::get_key(scancode);
-    if let Some(key) = key {
-        match key {
-            DecodedKey::Unicode(char) => {
-                match char {
-                    '\n' => {
-                        println!();
-                        GLOBAL_SHELL.lock().exec();
-                    },
-                    '\x08' => {
-                        GLOBAL_SHELL.lock().pop();
-                        print!("\x08");
-                    },
-                    _ => {
-                        GLOBAL_SHELL.lock().append(char);
-                        print!("{}", char);
-                    }
-                }
-
-            }
-            DecodedKey::RawKey(key) => {
-                serial_print!("{:#?}  ", key);
-            }
-        }
+    // Only push the events: whoever has the focus picks them up outside of the handler.
+    crate::keyboard::handle_scancode(scancode);
+
+    let binding = LOCAL_APIC.lock();
+    let apic = unsafe { binding.as_ref().unwrap().get_mut() };
+    unsafe {
+        apic.end_of_interrupt();
+    }
+}
+
+pub extern "x86-interrupt" fn serial_interrupt_handler(
+    _stack_frame: x86_64::structures::idt::InterruptStackFrame,
+) {
+    count(SERIAL_VECTOR);
+    // The port holds up to 16 bytes, and only interrupts again once they have all been read.
+    while let Some(byte) = crate::serial::try_read_byte() {
+        input::push(input::EventKind::Serial(byte));
     }
 
     let binding = LOCAL_APIC.lock();
@@ -177,6 +230,19 @@ pub extern "x86-interrupt" fn keyboard_interrupt_handler(
     }
 }
 
+pub extern "x86-interrupt" fn mouse_interrupt_handler(
+    _stack_frame: x86_64::structures::idt::InterruptStackFrame,
+) {
+    count(MOUSE_VECTOR);
+    input::mouse::handle_byte(crate::keyboard::i8042::read_data());
+
+    let binding = LOCAL_APIC.lock();
+    let apic = unsafe { binding.as_ref().unwrap().get_mut() };
+    unsafe {
+        apic.end_of_interrupt();
+    }
+}
+
 
 
 
@@ -0,0 +1,528 @@
+//! A driver for the i8042, the PS/2 controller the keyboard is plugged into.
+//!
+//! [init] sets the controller up from scratch, rather than trusting whatever the firmware left:
+//! it tests the controller and its ports, resets the keyboard, asks it for scancode set 2 for the
+//! controller to translate into set 1, which is what the [keyboard](super) decodes, sets how fast
+//! held keys repeat, turns on the [mouse](crate::input::mouse) if there is one, and only then
+//! turns their interrupts on.
+//!
+//! After that, commands to the keyboard, like the lock lights, are queued, and sent one byte at a
+//! time on each timer [tick]: each goes out once the keyboard has acknowledged the one before,
+//! which the keyboard's interrupt handler passes on to [handle_reply], so nothing waits for the
+//! keyboard. A command it doesn't take in time is given up on.
+use crate::interrupts::{timer_ticks, TIMER_INTERVAL_MS};
+use alloc::collections::VecDeque;
+use core::{
+    fmt,
+    sync::atomic::{AtomicU8, Ordering}
+};
+use spin::{Mutex, Once};
+use x86_64::instructions::{interrupts, port::Port};
+
+const DATA_PORT: u16 = 0x60;
+/// Read for the status, written for a command to the controller.
+const COMMAND_PORT: u16 = 0x64;
+
+/// Status: there is a byte to read from [DATA_PORT].
+const OUTPUT_FULL: u8 = 1 << 0;
+/// Status: the controller hasn't taken the last byte written yet.
+const INPUT_FULL: u8 = 1 << 1;
+/// Status: the byte to read came from the second port, where a mouse would be.
+const FROM_SECOND_PORT: u8 = 1 << 5;
+
+/// Configuration: interrupt on IRQ 1 when the keyboard sends a byte.
+const FIRST_PORT_INTERRUPT: u8 = 1 << 0;
+/// Configuration: interrupt on IRQ 12 when the second port sends a byte.
+const SECOND_PORT_INTERRUPT: u8 = 1 << 1;
+/// Configuration: the second port's clock is off. Only a controller with two ports can turn
+/// it on.
+const SECOND_PORT_DISABLED: u8 = 1 << 5;
+/// Configuration: translate scancode set 2 from the keyboard into set 1.
+const TRANSLATION: u8 = 1 << 6;
+
+const READ_CONFIG: u8 = 0x20;
+const WRITE_CONFIG: u8 = 0x60;
+const DISABLE_SECOND_PORT: u8 = 0xa7;
+const ENABLE_SECOND_PORT: u8 = 0xa8;
+const TEST_SECOND_PORT: u8 = 0xa9;
+const SELF_TEST: u8 = 0xaa;
+const TEST_FIRST_PORT: u8 = 0xab;
+const DISABLE_FIRST_PORT: u8 = 0xad;
+const ENABLE_FIRST_PORT: u8 = 0xae;
+/// Sends the next byte written to the second port, rather than the first.
+const WRITE_SECOND_PORT: u8 = 0xd4;
+
+const SET_LEDS: u8 = 0xed;
+const SCANCODE_SET: u8 = 0xf0;
+const SET_TYPEMATIC: u8 = 0xf3;
+/// To the keyboard, start sending scancodes. To the mouse, start sending packets.
+const ENABLE_SCANNING: u8 = 0xf4;
+/// To the mouse: go back to its default settings.
+const SET_DEFAULTS: u8 = 0xf6;
+const RESET: u8 = 0xff;
+
+const SELF_TEST_PASSED: u8 = 0x55;
+const RESET_PASSED: u8 = 0xaa;
+const ACK: u8 = 0xfa;
+const RESEND: u8 = 0xfe;
+
+/// The argument to [SET_LEDS] for Num Lock's light.
+const NUM_LOCK_LED: u8 = 1 << 1;
+
+/// How many times the status is read before the controller or keyboard is given up on. Each
+/// read takes about a microsecond, and the timer isn't running yet when [init] is.
+const TIMEOUT: u32 = 100_000;
+
+/// How many commands can wait to be sent to the keyboard before more are dropped.
+const MAX_QUEUED: usize = 8;
+/// How many timer ticks the keyboard has to take a whole command before it is given up on.
+const COMMAND_TIMEOUT_TICKS: u64 = 200 / TIMER_INTERVAL_MS;
+/// How many times a byte is sent, when the keyboard keeps asking for it again, before its
+/// command is given up on.
+const MAX_TRIES: u8 = 3;
+
+/// How often the keyboard repeats a held key, in tenths of a character a second, for each of the
+/// 32 rates it has.
+pub const RATES: [u16; 32] = [
+    300, 267, 240, 218, 207, 185, 171, 160, 150, 133, 120, 109, 100, 92, 86, 80,
+    75, 67, 60, 55, 50, 46, 43, 40, 37, 33, 30, 27, 25, 23, 21, 20,
+];
+
+/// How long a key must be held before it repeats, in milliseconds, for each of the 4 delays the
+/// keyboard has.
+pub const DELAYS: [u16; 4] = [250, 500, 750, 1000];
+
+/// The typematic byte [init] sets: 30 characters a second, after 500 ms.
+const DEFAULT_TYPEMATIC: u8 = 1 << 5;
+
+/// Why the controller couldn't be set up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// It didn't take a byte, or send one back, in time. There may be no controller at all.
+    Timeout,
+    /// The controller's self test sent back this instead of 0x55.
+    SelfTestFailed(u8),
+    /// The test of the keyboard's port found this problem.
+    PortTestFailed(u8),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Timeout => write!(f, "the controller isn't answering"),
+            Error::SelfTestFailed(reply) => write!(f, "the controller failed its self test ({reply:#04x})"),
+            Error::PortTestFailed(reply) => write!(f, "the keyboard port failed its test ({reply:#04x})"),
+        }
+    }
+}
+
+/// What [init] found.
+#[derive(Debug, Clone, Copy)]
+pub struct Controller {
+    /// Whether there is a second port, for a mouse.
+    pub dual_channel: bool,
+    /// Whether the second port passed its test, if there is one.
+    pub second_port_works: bool,
+    /// Whether a mouse on the second port agreed to send packets, and its interrupt is on.
+    pub mouse: bool,
+    /// Whether the keyboard passed its self test when it was reset.
+    pub keyboard_reset: bool,
+    /// The scancode set the keyboard agreed to, if it would say. Set 2 is translated to set 1 by
+    /// the controller.
+    pub scancode_set: Option<u8>,
+}
+
+static CONTROLLER: Once<Controller> = Once::new();
+
+/// Returns what [init] found, or `None` if it hasn't set the controller up.
+pub fn controller() -> Option<&'static Controller> {
+    CONTROLLER.get()
+}
+
+fn read_status() -> u8 {
+    unsafe { Port::<u8>::new(COMMAND_PORT).read() }
+}
+
+/// Reads the byte waiting for the CPU, whether there is one or not.
+pub fn read_data() -> u8 {
+    unsafe { Port::<u8>::new(DATA_PORT).read() }
+}
+
+/// Reads the byte from the keyboard waiting for the CPU, if there is one.
+///
+/// ### returns:
+/// - `None` if nothing is waiting, or the byte was from the second port.
+pub fn poll_data() -> Option<u8> {
+    let status = read_status();
+    if status & OUTPUT_FULL == 0 {
+        return None;
+    }
+    let byte = read_data();
+    (status & FROM_SECOND_PORT == 0).then_some(byte)
+}
+
+/// Waits until the controller can take another byte.
+fn wait_to_write() -> Result<(), Error> {
+    for _ in 0..TIMEOUT {
+        if read_status() & INPUT_FULL == 0 {
+            return Ok(());
+        }
+        core::hint::spin_loop();
+    }
+    Err(Error::Timeout)
+}
+
+/// Waits for a byte to read, and reads it.
+fn read_data_waiting(timeout: u32) -> Result<u8, Error> {
+    for _ in 0..timeout {
+        if read_status() & OUTPUT_FULL != 0 {
+            return Ok(read_data());
+        }
+        core::hint::spin_loop();
+    }
+    Err(Error::Timeout)
+}
+
+fn write_command(command: u8) -> Result<(), Error> {
+    wait_to_write()?;
+    unsafe { Port::<u8>::new(COMMAND_PORT).write(command) };
+    Ok(())
+}
+
+fn write_data(byte: u8) -> Result<(), Error> {
+    wait_to_write()?;
+    unsafe { Port::<u8>::new(DATA_PORT).write(byte) };
+    Ok(())
+}
+
+/// Throws away anything waiting to be read.
+fn flush() {
+    for _ in 0..16 {
+        if read_status() & OUTPUT_FULL == 0 {
+            return;
+        }
+        read_data();
+    }
+}
+
+fn read_config() -> Result<u8, Error> {
+    write_command(READ_CONFIG)?;
+    read_data_waiting(TIMEOUT)
+}
+
+fn write_config(config: u8) -> Result<(), Error> {
+    write_command(WRITE_CONFIG)?;
+    write_data(config)
+}
+
+/// Sends a byte to the keyboard and waits for it to be acknowledged, sending it again if the
+/// keyboard asks, for while its interrupt is off.
+///
+/// ### returns:
+/// - `false` if the keyboard didn't acknowledge it.
+fn send_waiting(byte: u8) -> bool {
+    send_to_port_waiting(byte, false)
+}
+
+/// Sends a byte to the mouse like [send_waiting] sends one to the keyboard.
+fn send_to_mouse_waiting(byte: u8) -> bool {
+    send_to_port_waiting(byte, true)
+}
+
+fn send_to_port_waiting(byte: u8, second_port: bool) -> bool {
+    for _ in 0..3 {
+        if second_port && write_command(WRITE_SECOND_PORT).is_err() {
+            return false;
+        }
+        if write_data(byte).is_err() {
+            return false;
+        }
+        match read_data_waiting(TIMEOUT) {
+            Ok(ACK) => return true,
+            Ok(RESEND) => continue,
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Asks the keyboard for scancode set `set`, and checks that it took it.
+fn select_scancode_set(set: u8) -> bool {
+    if !(send_waiting(SCANCODE_SET) && send_waiting(set)) {
+        return false;
+    }
+    // 0 asks which set is in use, and the keyboard answers after acknowledging it.
+    send_waiting(SCANCODE_SET) && send_waiting(0) && read_data_waiting(TIMEOUT) == Ok(set)
+}
+
+/// Sets the controller and the keyboard up, and turns the keyboard's interrupt on. Must be called
+/// once, before interrupts are enabled.
+///
+/// If the controller fails, the keyboard's port and interrupt are turned on anyway, with
+/// translation, as the firmware usually leaves them, in case the keyboard works regardless.
+pub fn init() -> Result<&'static Controller, Error> {
+    interrupts::without_interrupts(|| {
+        let result = set_up();
+        if result.is_err() {
+            _ = write_command(ENABLE_FIRST_PORT);
+            if let Ok(config) = read_config() {
+                _ = write_config(config | FIRST_PORT_INTERRUPT | TRANSLATION);
+            }
+        }
+        flush();
+        let controller = result?;
+        OUTGOING.lock().queue.reserve_exact(MAX_QUEUED);
+        Ok(CONTROLLER.call_once(|| controller))
+    })
+}
+
+fn set_up() -> Result<Controller, Error> {
+    write_command(DISABLE_FIRST_PORT)?;
+    write_command(DISABLE_SECOND_PORT)?;
+    flush();
+
+    // No interrupts or translation while the keyboard is being set up, so its replies can be
+    // read here, as they are.
+    let mut config = read_config()? & !(FIRST_PORT_INTERRUPT | SECOND_PORT_INTERRUPT | TRANSLATION);
+    write_config(config)?;
+
+    write_command(SELF_TEST)?;
+    match read_data_waiting(TIMEOUT)? {
+        SELF_TEST_PASSED => {}
+        reply => return Err(Error::SelfTestFailed(reply)),
+    }
+    // Some controllers reset themselves in the self test.
+    write_config(config)?;
+
+    // With two ports, the second one's clock comes on when it is enabled. With one, it can't.
+    // Either way, the keyboard doesn't depend on it.
+    let mut dual_channel = false;
+    if config & SECOND_PORT_DISABLED != 0 {
+        dual_channel = write_command(ENABLE_SECOND_PORT).is_ok()
+            && read_config().is_ok_and(|config| config & SECOND_PORT_DISABLED == 0);
+        _ = write_command(DISABLE_SECOND_PORT);
+    }
+
+    write_command(TEST_FIRST_PORT)?;
+    match read_data_waiting(TIMEOUT)? {
+        0 => {}
+        reply => return Err(Error::PortTestFailed(reply)),
+    }
+    let second_port_works = dual_channel && write_command(TEST_SECOND_PORT).is_ok() && read_data_waiting(TIMEOUT) == Ok(0);
+
+    write_command(ENABLE_FIRST_PORT)?;
+    // The keyboard can take a while to test itself after a reset.
+    let keyboard_reset = send_waiting(RESET) && read_data_waiting(TIMEOUT * 10) == Ok(RESET_PASSED);
+
+    // Set 2 is the one every keyboard has, and the controller turns it into set 1. If the keyboard
+    // won't, it may already send set 1, which must then not be translated.
+    let scancode_set = if select_scancode_set(2) {
+        config |= TRANSLATION;
+        Some(2)
+    } else if select_scancode_set(1) {
+        Some(1)
+    } else {
+        config |= TRANSLATION;
+        None
+    };
+
+    send_waiting(SET_TYPEMATIC);
+    send_waiting(DEFAULT_TYPEMATIC);
+    *TYPEMATIC.lock() = DEFAULT_TYPEMATIC;
+    // The keyboard's decoder starts with Num Lock on.
+    send_waiting(SET_LEDS);
+    send_waiting(NUM_LOCK_LED);
+
+    // The mouse is set up before the keyboard starts sending, so nothing typed gets mixed up
+    // with its replies. It is optional: if anything about it fails, the keyboard goes on without
+    // it.
+    let mouse = second_port_works
+        && write_command(ENABLE_SECOND_PORT).is_ok()
+        && send_to_mouse_waiting(SET_DEFAULTS)
+        && send_to_mouse_waiting(ENABLE_SCANNING);
+    if mouse {
+        config = (config & !SECOND_PORT_DISABLED) | SECOND_PORT_INTERRUPT;
+    } else if second_port_works {
+        _ = write_command(DISABLE_SECOND_PORT);
+    }
+    send_waiting(ENABLE_SCANNING);
+
+    write_config(config | FIRST_PORT_INTERRUPT)?;
+    Ok(Controller { dual_channel, second_port_works, mouse, keyboard_reset, scancode_set })
+}
+
+/// A command for the keyboard, and its argument.
+type Command = [u8; 2];
+
+/// Commands for the keyboard, waiting to be sent.
+struct Outgoing {
+    /// The one going out first. Room for [MAX_QUEUED] is made by [init], so pushing in an
+    /// interrupt handler never allocates.
+    queue: VecDeque<Command>,
+    /// How many bytes of the first command the keyboard has acknowledged.
+    acknowledged: usize,
+    /// Whether the next byte of the first command has gone out, and is waiting for a reply.
+    sent: bool,
+    /// Whether the keyboard asked for that byte again.
+    resend: bool,
+    /// How many times that byte has gone out.
+    tries: u8,
+    /// The timer tick the first command started going out on.
+    started: u64,
+    /// The lights last queued, as the argument to [SET_LEDS].
+    leds: u8,
+}
+
+impl Outgoing {
+    /// Queues a command, unless [MAX_QUEUED] are already waiting.
+    ///
+    /// ### returns:
+    /// - `false` if the command was dropped.
+    fn push(&mut self, command: Command, now: u64) -> bool {
+        if self.queue.len() >= MAX_QUEUED {
+            return false;
+        }
+        if self.queue.is_empty() {
+            self.started = now;
+        }
+        self.queue.push_back(command);
+        true
+    }
+
+    /// Gets rid of the first command, sent or given up on, arguments and all, and starts on the
+    /// next.
+    fn next_command(&mut self, now: u64) {
+        self.queue.pop_front();
+        self.acknowledged = 0;
+        self.sent = false;
+        self.resend = false;
+        self.tries = 0;
+        self.started = now;
+    }
+
+    /// Sends the next byte, or the last one again if the keyboard asked, unless it is still
+    /// waiting for a reply, or the controller can't take it yet. A command the keyboard hasn't
+    /// taken within [COMMAND_TIMEOUT_TICKS], or asked for [MAX_TRIES] times, is given up on.
+    fn send_next(&mut self, now: u64) {
+        let timed_out = now - self.started > COMMAND_TIMEOUT_TICKS;
+        if !self.queue.is_empty() && (timed_out || (self.resend && self.tries >= MAX_TRIES)) {
+            self.next_command(now);
+        }
+        let Some(command) = self.queue.front() else {
+            return;
+        };
+        if (self.sent && !self.resend) || read_status() & INPUT_FULL != 0 {
+            return;
+        }
+        unsafe { Port::<u8>::new(DATA_PORT).write(command[self.acknowledged]) };
+        self.sent = true;
+        self.resend = false;
+        self.tries += 1;
+    }
+}
+
+static OUTGOING: Mutex<Outgoing> = Mutex::new(Outgoing {
+    queue: VecDeque::new(),
+    acknowledged: 0,
+    sent: false,
+    resend: false,
+    tries: 0,
+    started: 0,
+    leds: NUM_LOCK_LED,
+});
+
+/// The lock lights the keyboard should show, as the argument to [SET_LEDS]. [tick] sends them
+/// when they change.
+static LEDS: AtomicU8 = AtomicU8::new(NUM_LOCK_LED);
+
+/// The typematic byte last sent to the keyboard: the rate in bits 0 to 4, and the delay in bits
+/// 5 and 6.
+static TYPEMATIC: Mutex<u8> = Mutex::new(DEFAULT_TYPEMATIC);
+
+/// Queues a command, with its argument, for the keyboard, once [init] has set it up. It goes
+/// out on the next timer [tick]s, a byte at a time, as the keyboard acknowledges them.
+///
+/// The command is dropped if too many are waiting already, or if the code the
+/// [debugger](crate::debugger) stopped was queuing another one.
+fn send(command: Command) {
+    if controller().is_none() {
+        return;
+    }
+    interrupts::without_interrupts(|| {
+        if let Some(mut outgoing) = OUTGOING.try_lock() {
+            _ = outgoing.push(command, timer_ticks());
+        }
+    });
+}
+
+/// Queues the lock lights if they have changed, and sends what is waiting for the keyboard.
+/// Called from the timer interrupt handler on every tick, so the keyboard's own handler never
+/// waits on the controller.
+pub fn tick() {
+    if controller().is_none() {
+        return;
+    }
+    let Some(mut outgoing) = OUTGOING.try_lock() else {
+        return;
+    };
+    let now = timer_ticks();
+    let leds = LEDS.load(Ordering::Relaxed);
+    if leds != outgoing.leds && outgoing.push([SET_LEDS, leds], now) {
+        outgoing.leds = leds;
+    }
+    outgoing.send_next(now);
+}
+
+/// Handles a byte from the keyboard if it is a reply to a command. The next byte, or the same one
+/// again if the keyboard asks, goes out on the next [tick].
+///
+/// ### returns:
+/// - `true` if the byte was a reply, and so not part of a scancode.
+pub fn handle_reply(byte: u8) -> bool {
+    if byte != ACK && byte != RESEND {
+        return false;
+    }
+    if let Some(mut outgoing) = OUTGOING.try_lock() {
+        if outgoing.sent {
+            match byte {
+                ACK => {
+                    outgoing.acknowledged += 1;
+                    outgoing.sent = false;
+                    outgoing.tries = 0;
+                    if outgoing.acknowledged == size_of::<Command>() {
+                        outgoing.next_command(timer_ticks());
+                    }
+                }
+                _ => outgoing.resend = true,
+            }
+        }
+    }
+    true
+}
+
+/// Turns the keyboard's Scroll Lock, Num Lock and Caps Lock lights on or off, on the next [tick].
+pub fn set_leds(scroll_lock: bool, num_lock: bool, caps_lock: bool) {
+    let leds = u8::from(scroll_lock) | u8::from(num_lock) << 1 | u8::from(caps_lock) << 2;
+    LEDS.store(leds, Ordering::Relaxed);
+}
+
+/// Sets how fast held keys repeat, to the closest the keyboard can do.
+///
+/// ### params:
+/// - `rate`: in tenths of a character a second, from 20 to 300.
+/// - `delay`: how long a key must be held before it repeats, in milliseconds, from 250 to 1000.
+pub fn set_typematic(rate: u16, delay: u16) {
+    let closest = |values: &[u16], wanted: u16| {
+        (0..values.len()).min_by_key(|&index| values[index].abs_diff(wanted)).unwrap_or(0) as u8
+    };
+    let typematic = closest(&RATES, rate) | closest(&DELAYS, delay) << 5;
+    *TYPEMATIC.lock() = typematic;
+    send([SET_TYPEMATIC, typematic]);
+}
+
+/// Returns how fast held keys repeat, in tenths of a character a second, and after how many
+/// milliseconds.
+pub fn typematic() -> (u16, u16) {
+    let typematic = *TYPEMATIC.lock();
+    (RATES[usize::from(typematic & 0x1f)], DELAYS[usize::from(typematic >> 5 & 0x3)])
+}
@@ -1,21 +1,200 @@
-use pc_keyboard::{layouts, DecodedKey, HandleControl, Keyboard, ScancodeSet1};
+pub mod i8042;
+
+use crate::input::{self, EventKind};
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering}
+};
+use pc_keyboard::{
+    layouts::{self, AnyLayout},
+    DecodedKey,
+    HandleControl,
+    KeyCode,
+    KeyEvent,
+    KeyState,
+    Keyboard,
+    ScancodeSet1
+};
 use spin::{Lazy, Mutex};
+use x86_64::instructions::interrupts;
+
+/// The layouts [set_layout] can switch to: the name `loadkeys` knows each by, and what it is.
+pub const LAYOUTS: [(&str, &str); 10] = [
+    ("us", "US English (104 keys)"),
+    ("uk", "UK English (105 keys)"),
+    ("de", "German (105 keys)"),
+    ("fr", "French AZERTY"),
+    ("dvorak", "Dvorak (104 keys)"),
+    ("dvp", "Programmer Dvorak (104 keys)"),
+    ("colemak", "Colemak"),
+    ("jp", "Japanese JIS (109 keys)"),
+    ("no", "Norwegian (105 keys)"),
+    ("fi", "Finnish and Swedish (105 keys)"),
+];
 
-static KEYBOARD: Lazy<Mutex<Keyboard<layouts::Us104Key, ScancodeSet1>>> = Lazy::new(|| {
+/// Letters typed with Ctrl held come through as control characters, like `\x03` for Ctrl+C. What
+/// else Ctrl was held for is in [KeyPress::ctrl].
+const CTRL_HANDLING: HandleControl = HandleControl::MapLettersToUnicode;
+
+static KEYBOARD: Lazy<Mutex<Keyboard<AnyLayout, ScancodeSet1>>> = Lazy::new(|| {
     Mutex::new(Keyboard::new(
         ScancodeSet1::new(),
-        layouts::Us104Key, 
-        HandleControl::Ignore
+        AnyLayout::Us104Key(layouts::Us104Key),
+        CTRL_HANDLING
     ))
 });
 
+/// The name of the layout in use, from [LAYOUTS].
+static LAYOUT: Mutex<&str> = Mutex::new("us");
+
+/// Whether Scroll Lock is on. The decoder keeps Caps Lock and Num Lock, but not this one, as no
+/// key depends on it.
+static SCROLL_LOCK: AtomicBool = AtomicBool::new(false);
+
+/// A key pressed, and the modifier keys that were held down with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPress {
+    pub key: DecodedKey,
+    /// The key pressed, whatever it typed. Ctrl+H and Ctrl+Backspace both type `\x08`, for one.
+    pub code: KeyCode,
+    pub ctrl: bool,
+    /// Either Alt key, AltGr included.
+    pub alt: bool,
+    pub shift: bool,
+}
+
+/// The modifier keys held down when a key went down or up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    /// Either Alt key, AltGr included.
+    pub alt: bool,
+    pub shift: bool,
+}
+
+/// Shows the modifiers held, like ` (ctrl+shift)`, or nothing if there are none.
+impl fmt::Display for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = " (";
+        for (held, name) in [(self.ctrl, "ctrl"), (self.alt, "alt"), (self.shift, "shift")] {
+            if held {
+                write!(f, "{separator}{name}")?;
+                separator = "+";
+            }
+        }
+        if separator == "+" {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
 
-pub fn get_key(scancode: u8) -> Option<DecodedKey> {
+/// Switches to the keyboard layout called `name` in [LAYOUTS].
+///
+/// ### returns:
+/// - `false` if there is no layout by that name.
+pub fn set_layout(name: &str) -> bool {
+    let layout = match name {
+        "us" => AnyLayout::Us104Key(layouts::Us104Key),
+        "uk" => AnyLayout::Uk105Key(layouts::Uk105Key),
+        "de" => AnyLayout::De105Key(layouts::De105Key),
+        "fr" => AnyLayout::Azerty(layouts::Azerty),
+        "dvorak" => AnyLayout::Dvorak104Key(layouts::Dvorak104Key),
+        "dvp" => AnyLayout::DVP104Key(layouts::DVP104Key),
+        "colemak" => AnyLayout::Colemak(layouts::Colemak),
+        "jp" => AnyLayout::Jis109Key(layouts::Jis109Key),
+        "no" => AnyLayout::No105Key(layouts::No105Key),
+        "fi" => AnyLayout::FiSe105Key(layouts::FiSe105Key),
+        _ => return false,
+    };
+    let Some(&(name, _)) = LAYOUTS.iter().find(|(known, _)| *known == name) else {
+        return false;
+    };
+    // The decoder only takes a layout when it is made, so a new one replaces it. The interrupt
+    // handler uses it too, so it must not come in halfway through.
+    interrupts::without_interrupts(|| {
+        let mut keyboard = KEYBOARD.lock();
+        *keyboard = Keyboard::new(ScancodeSet1::new(), layout, CTRL_HANDLING);
+        *LAYOUT.lock() = name;
+        // The new decoder starts with Num Lock on and Caps Lock off, whatever the lights say.
+        update_leds(&keyboard);
+    });
+    true
+}
+
+/// Returns the name of the keyboard layout in use, from [LAYOUTS].
+pub fn layout() -> &'static str {
+    *LAYOUT.lock()
+}
+
+/// Sets the keyboard's lights to match the lock keys. They change on the next timer tick, so this
+/// is safe to call from the interrupt handler.
+fn update_leds(keyboard: &Keyboard<AnyLayout, ScancodeSet1>) {
+    let modifiers = keyboard.get_modifiers();
+    i8042::set_leds(SCROLL_LOCK.load(Ordering::Relaxed), modifiers.numlock, modifiers.capslock);
+}
+
+fn modifiers(keyboard: &Keyboard<AnyLayout, ScancodeSet1>) -> Modifiers {
+    let modifiers = keyboard.get_modifiers();
+    Modifiers { ctrl: modifiers.is_ctrl(), alt: modifiers.is_alt(), shift: modifiers.is_shifted() }
+}
+
+/// Feeds a scancode to the decoder, and returns the key going down or up it completes, if any,
+/// with the key typed by it, if any. A lock key turns its light on or off rather than being typed.
+fn decode(keyboard: &mut Keyboard<AnyLayout, ScancodeSet1>, scancode: u8) -> Option<(KeyEvent, Option<KeyPress>)> {
+    if i8042::handle_reply(scancode) {
+        return None;
+    }
+    let key_event = keyboard.add_byte(scancode).ok()??;
+    let key = match keyboard.process_keyevent(key_event.clone()) {
+        Some(DecodedKey::RawKey(KeyCode::ScrollLock)) => {
+            SCROLL_LOCK.fetch_xor(true, Ordering::Relaxed);
+            update_leds(keyboard);
+            None
+        }
+        Some(DecodedKey::RawKey(KeyCode::CapsLock | KeyCode::NumpadLock)) => {
+            update_leds(keyboard);
+            None
+        }
+        key => key,
+    };
+    let Modifiers { ctrl, alt, shift } = modifiers(keyboard);
+    let code = key_event.code;
+    Some((key_event, key.map(|key| KeyPress { key, code, ctrl, alt, shift })))
+}
+
+/// Decodes a scancode, and [pushes](input::push) the events it completes: a key going down or up,
+/// and the key typed, if any. Called from the keyboard interrupt handler, so it does nothing else.
+pub fn handle_scancode(scancode: u8) {
     let mut keyboard = KEYBOARD.lock();
-    let key_event = keyboard.add_byte(scancode);
-    if let Ok(Some(key_event)) = key_event {
-        if let Some(key) = keyboard.process_keyevent(key_event) {
-            Some(key)
-        }else { None }
-    } else { None }
-}
\ No newline at end of file
+    let Some((key_event, press)) = decode(&mut keyboard, scancode) else {
+        return;
+    };
+    let modifiers = modifiers(&keyboard);
+    drop(keyboard);
+    match key_event.state {
+        KeyState::Down => input::push(EventKind::KeyDown(key_event.code, modifiers)),
+        KeyState::Up => input::push(EventKind::KeyUp(key_event.code, modifiers)),
+        // Keys like Pause, which only say they were pressed.
+        KeyState::SingleShot => {
+            input::push(EventKind::KeyDown(key_event.code, modifiers));
+            input::push(EventKind::KeyUp(key_event.code, modifiers));
+        }
+    }
+    if let Some(press) = press {
+        input::push(EventKind::Key(press));
+    }
+}
+
+/// Reads a key straight from the keyboard controller, if one has been typed, without going
+/// through the interrupt handler. This is for when interrupts are off, like in the
+/// [debugger](crate::debugger).
+///
+/// ### returns:
+/// - `None` if nothing was typed, the byte waiting was from the mouse, or the keyboard's
+///   decoder is in use by the code that was interrupted.
+pub fn poll_key_press() -> Option<KeyPress> {
+    let scancode = i8042::poll_data()?;
+    let mut keyboard = KEYBOARD.try_lock()?;
+    decode(&mut keyboard, scancode)?.1
+}
@@ -1,4 +1,5 @@
 #![feature(abi_x86_interrupt)]
+#![feature(allocator_api)]
 #![no_std]
 
 extern crate alloc;
@@ -8,13 +9,22 @@ use core::marker::PhantomData;
 use embedded_graphics::pixelcolor::{Bgr888, Gray8, Rgb888};
 
 pub mod allocator;
+pub mod content;
+pub mod debugger;
 pub mod framebuffer;
+pub mod fs;
+pub mod games;
+pub mod initramfs;
+pub mod input;
 pub mod interrupts;
 pub mod memory;
 pub mod power;
 pub mod serial;
 pub mod keyboard;
 pub mod shell;
+pub mod sysinfo;
+pub mod time;
+pub mod users;
 
 use x86_64::{
     instructions::tables::load_tss,
@@ -55,6 +65,7 @@ pub fn hlt_loop() -> ! {
 /// ### returns:
 /// - `()`: Nothing/Void.
 pub fn init(boot_info: &'static mut bootloader_api::BootInfo) -> () {
+    serial::init();
     // if the framebuffer, exists, then init it, else, panic.
     if let Some(framebuffer) = boot_info.framebuffer.as_mut() {
         let info = framebuffer.info();  // Get the framebuffer info
@@ -113,6 +124,12 @@ pub fn init(boot_info: &'static mut bootloader_api::BootInfo) -> () {
         unsafe { memory::BootInfoFrameAllocator::init(&boot_info.memory_regions) };
 
     allocator::init_heap(&mut mapper, &mut frame_allocator).expect("heap initialization failed");
+    unsafe { initramfs::init(boot_info.ramdisk_addr.into_option(), boot_info.ramdisk_len); }
+    fs::mount();
+    match keyboard::i8042::init() {
+        Ok(controller) => serial_println!("PS/2 controller initialized: {:?}", controller),
+        Err(error) => serial_println!("PS/2 controller failed, {}; using it as it is", error),
+    }
 
     let phys_offset = boot_info.physical_memory_offset.into_option().unwrap();
 
@@ -132,14 +149,17 @@ pub fn init(boot_info: &'static mut bootloader_api::BootInfo) -> () {
 
     let platform_info = acpi_tables.platform_info().unwrap(); // Get the platform info
 
+    sysinfo::init(&boot_info.memory_regions, &acpi_tables, &platform_info);
+
     let interrupt_model = platform_info.interrupt_model;
 
     let fadt_addr = acpi_tables
         .find_table::<acpi::fadt::Fadt>()
-        .map(|fadt| &fadt as *const _ as usize)
+        .map(|fadt| fadt.virtual_start().as_ptr() as usize)
         .expect("Why the Fuck does this not exist????");
 
     *FADT_ADDR.lock() = Some(fadt_addr);
+    power::register_commands();
 
     serial_println!("{:#?}", interrupt_model);
 
@@ -176,8 +196,12 @@ pub fn init(boot_info: &'static mut bootloader_api::BootInfo) -> () {
     let io_apic_binding = IO_APIC.lock();
     let io_apic = io_apic_binding.as_ref().unwrap().get_mut();
     unsafe {
-        io_apic.init(0x23);
+        io_apic.init(interrupts::IRQ_BASE);
         io_apic.enable_irq(1);
+        io_apic.enable_irq(4);
+        if keyboard::i8042::controller().is_some_and(|controller| controller.mouse) {
+            io_apic.enable_irq(12);
+        }
     }
 
     serial_println!("{}","Enabling Interrupts...".fg(ConsoleColor::BrightGreen));
@@ -7,11 +7,13 @@ use bootloader_api::{entry_point, BootInfo, BootloaderConfig};
 use core::panic::PanicInfo;
 use kernel::framebuffer::{color::ColoredWriting, ConsoleColor};
 use kernel::{hlt_loop, println, serial_println};
-use kernel::shell::GLOBAL_SHELL;
 
 static CONFIG: BootloaderConfig = {
     let mut config = BootloaderConfig::new_default();
     config.mappings.physical_memory = Some(Mapping::Dynamic);
+    // The shell runs on this stack, and nested scripts and their functions can take over 600 KiB
+    // of it at their limits. There is no guard page below it.
+    config.kernel_stack_size = 1024 * 1024;
     config
 };
 
@@ -35,9 +37,7 @@ pub fn kernel_main(boot_info: &'static mut BootInfo) -> ! {
     println!("Hello, World!");
     println!("\x1b[32mHello, World!\x1b[0m");
     println!("{}", "Hi there!".fg(ConsoleColor::BrightCyan));
-    GLOBAL_SHELL.lock().init();
-
-    hlt_loop();
+    kernel::shell::run();
 }
 
 entry_point!(kernel_main, config = &CONFIG);
@@ -0,0 +1,268 @@
+//! Looking at memory from the shell, for `peek`, `poke`, `hexdump` and `pagewalk`.
+//!
+//! Every access is checked against the page tables first, so an address that isn't mapped, or
+//! isn't writable, is an [AccessError] rather than a page fault. The tables are the ones the
+//! kernel's `OffsetPageTable` maps pages in, found from CR3 and read through the physical memory
+//! offset, the same way it reads them.
+use super::physical_memory_offset;
+use alloc::{
+    format,
+    string::String,
+    vec::Vec
+};
+use core::{fmt, ptr};
+use x86_64::{
+    registers::control::Cr3,
+    structures::paging::PageTableFlags,
+    PhysAddr,
+    VirtAddr
+};
+
+/// The bits of a page table entry that hold the address of the table or page it points to.
+const ADDRESS_MASK: u64 = 0x000f_ffff_ffff_f000;
+
+/// The highest physical address x86_64 allows, plus one.
+const PHYSICAL_LIMIT: u64 = 1 << 52;
+
+/// Why memory couldn't be accessed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessError {
+    /// Bits 48 to 63 of the address aren't all copies of bit 47.
+    NotCanonical(u64),
+    /// A physical address higher than any there can be.
+    PhysicalOutOfRange(u64),
+    /// The physical memory offset isn't known, so physical memory can't be reached.
+    NoPhysicalMapping,
+    /// Nothing is mapped at the address.
+    NotMapped(VirtAddr),
+    /// The address is mapped read-only, and was to be written.
+    ReadOnly(VirtAddr),
+    /// An access of this many bytes must be at an address that is a multiple of it.
+    Misaligned(VirtAddr, usize),
+    /// The range goes past the end of the address space.
+    Overflow,
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessError::NotCanonical(address) => write!(f, "{address:#x} is not a canonical address"),
+            AccessError::PhysicalOutOfRange(address) => write!(f, "{address:#x} is beyond the physical address space"),
+            AccessError::NoPhysicalMapping => write!(f, "physical memory is not mapped"),
+            AccessError::NotMapped(address) => write!(f, "{:#x} is not mapped", address.as_u64()),
+            AccessError::ReadOnly(address) => write!(f, "{:#x} is mapped read-only", address.as_u64()),
+            AccessError::Misaligned(address, size) => {
+                write!(f, "{:#x} is not aligned to {size} bytes", address.as_u64())
+            }
+            AccessError::Overflow => write!(f, "the range runs past the end of the address space"),
+        }
+    }
+}
+
+/// An entry [walk] went through.
+#[derive(Debug, Clone, Copy)]
+pub struct WalkStep {
+    /// 4 for the PML4, down to 1 for the page table.
+    pub level: u8,
+    /// Which of the table's 512 entries the address picks.
+    pub index: u16,
+    /// The physical address of the table.
+    pub table: PhysAddr,
+    /// The entry, as it is in memory.
+    pub entry: u64,
+}
+
+impl WalkStep {
+    /// The usual name of the table, like `PML4`.
+    pub fn table_name(&self) -> &'static str {
+        match self.level {
+            4 => "PML4",
+            3 => "PDPT",
+            2 => "PD",
+            _ => "PT",
+        }
+    }
+
+    pub fn flags(&self) -> PageTableFlags {
+        PageTableFlags::from_bits_truncate(self.entry)
+    }
+
+    /// The address of the table or page the entry points to.
+    pub fn address(&self) -> PhysAddr {
+        PhysAddr::new(self.entry & ADDRESS_MASK)
+    }
+}
+
+/// Where a mapped virtual address leads.
+#[derive(Debug, Clone, Copy)]
+pub struct Mapping {
+    pub physical: PhysAddr,
+    /// The size of the page the address is in: 4 KiB, 2 MiB or 1 GiB.
+    pub page_size: u64,
+    /// Whether every level allows writing.
+    pub writable: bool,
+    /// Whether every level allows running code.
+    pub executable: bool,
+    /// Whether every level allows user mode in.
+    pub user: bool,
+}
+
+/// The way through the page tables to a virtual address.
+#[derive(Debug, Clone)]
+pub struct Walk {
+    /// The entries followed, from the PML4 down, ending at the one that maps the page or isn't
+    /// present.
+    pub steps: Vec<WalkStep>,
+    /// Where the address leads, or `None` if it isn't mapped.
+    pub mapping: Option<Mapping>,
+}
+
+/// Walks the page tables down to `address`, from the PML4 that CR3 points to.
+pub fn walk(address: VirtAddr) -> Walk {
+    let mut walk = Walk { steps: Vec::new(), mapping: None };
+    let Some(offset) = physical_memory_offset() else {
+        return walk;
+    };
+    let indexes = [address.p4_index(), address.p3_index(), address.p2_index(), address.p1_index()];
+    let mut table = Cr3::read().0.start_address();
+    for (level, index) in (1..=4).rev().zip(indexes) {
+        let index = u16::from(index);
+        let entry_address = offset + table.as_u64() + u64::from(index) * 8;
+        // Page tables are in RAM, which is all mapped at the offset.
+        let entry = unsafe { ptr::read_volatile(entry_address.as_ptr::<u64>()) };
+        let step = WalkStep { level, index, table, entry };
+        walk.steps.push(step);
+
+        let flags = step.flags();
+        if !flags.contains(PageTableFlags::PRESENT) {
+            break;
+        }
+        if level == 1 || (level < 4 && flags.contains(PageTableFlags::HUGE_PAGE)) {
+            let page_size = 1u64 << (12 + 9 * (u32::from(level) - 1));
+            // Bit 12 of a huge page's entry is its PAT bit, not part of the address.
+            let page = step.address().as_u64() & !(page_size - 1);
+            let all = |flag| walk.steps.iter().all(|step| step.flags().contains(flag));
+            let mapping = Mapping {
+                physical: PhysAddr::new(page + (address.as_u64() & (page_size - 1))),
+                page_size,
+                writable: all(PageTableFlags::WRITABLE),
+                executable: !walk.steps.iter().any(|step| step.flags().contains(PageTableFlags::NO_EXECUTE)),
+                user: all(PageTableFlags::USER_ACCESSIBLE),
+            };
+            walk.mapping = Some(mapping);
+            break;
+        }
+        table = step.address();
+    }
+    walk
+}
+
+/// Parses a number given by the user: hex with `0x`, or decimal. Underscores can split up the
+/// digits, as in `0xffff_8000_0000_0000`.
+pub fn parse_number(text: &str) -> Option<u64> {
+    let text: String = text.chars().filter(|&ch| ch != '_').collect();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// Formats up to 16 bytes read from `address` as a line of a hex dump: the address, the bytes in
+/// hex, and the bytes as ASCII, with a `.` for anything that can't be shown.
+pub fn dump_line(address: u64, bytes: &[u8]) -> String {
+    let hex: Vec<String> = (0..16)
+        .map(|index| bytes.get(index).map_or(String::from("  "), |byte| format!("{byte:02x}")))
+        .collect();
+    let ascii: String = bytes
+        .iter()
+        .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+        .collect();
+    format!("{address:016x}  {}  {}  |{ascii}|", hex[..8].join(" "), hex[8..].join(" "))
+}
+
+/// Turns an address given by the user into the virtual address to access it at.
+///
+/// ### params:
+/// - `physical`: whether `address` is a physical address, to be reached through the physical
+///   memory offset.
+pub fn resolve(address: u64, physical: bool) -> Result<VirtAddr, AccessError> {
+    if !physical {
+        return VirtAddr::try_new(address).map_err(|_| AccessError::NotCanonical(address));
+    }
+    if address >= PHYSICAL_LIMIT {
+        return Err(AccessError::PhysicalOutOfRange(address));
+    }
+    let offset = physical_memory_offset().ok_or(AccessError::NoPhysicalMapping)?;
+    let virtual_address = offset.as_u64().checked_add(address).ok_or(AccessError::Overflow)?;
+    VirtAddr::try_new(virtual_address).map_err(|_| AccessError::NotCanonical(virtual_address))
+}
+
+/// Checks that all `len` bytes from `start` are mapped, and writable if they are to be written.
+fn check(start: VirtAddr, len: u64, write: bool) -> Result<(), AccessError> {
+    if len == 0 {
+        return Ok(());
+    }
+    let end = start.as_u64().checked_add(len - 1).ok_or(AccessError::Overflow)?;
+    VirtAddr::try_new(end).map_err(|_| AccessError::NotCanonical(end))?;
+    let mut address = start.as_u64();
+    loop {
+        let virtual_address = VirtAddr::try_new(address).map_err(|_| AccessError::NotCanonical(address))?;
+        let Some(mapping) = walk(virtual_address).mapping else {
+            return Err(AccessError::NotMapped(virtual_address));
+        };
+        if write && !mapping.writable {
+            return Err(AccessError::ReadOnly(virtual_address));
+        }
+        // On to the next page, if the range goes into it.
+        match (address & !(mapping.page_size - 1)).checked_add(mapping.page_size) {
+            Some(next) if next <= end => address = next,
+            _ => return Ok(()),
+        }
+    }
+}
+
+/// Reads a value of `size` bytes, which must be 1, 2, 4 or 8, from `address`.
+pub fn peek(address: VirtAddr, size: usize) -> Result<u64, AccessError> {
+    if !address.is_aligned(size as u64) {
+        return Err(AccessError::Misaligned(address, size));
+    }
+    check(address, size as u64, false)?;
+    // The address was just checked to be mapped, and aligned for its size.
+    let value = unsafe {
+        match size {
+            1 => u64::from(ptr::read_volatile(address.as_ptr::<u8>())),
+            2 => u64::from(ptr::read_volatile(address.as_ptr::<u16>())),
+            4 => u64::from(ptr::read_volatile(address.as_ptr::<u32>())),
+            _ => ptr::read_volatile(address.as_ptr::<u64>()),
+        }
+    };
+    Ok(value)
+}
+
+/// Writes `value` as `size` bytes, which must be 1, 2, 4 or 8, to `address`.
+///
+/// # Safety
+///
+/// Nothing stops this from writing over anything, the kernel's own code and data included.
+/// The caller must be sure the write is wanted.
+pub unsafe fn poke(address: VirtAddr, value: u64, size: usize) -> Result<(), AccessError> {
+    if !address.is_aligned(size as u64) {
+        return Err(AccessError::Misaligned(address, size));
+    }
+    check(address, size as u64, true)?;
+    match size {
+        1 => ptr::write_volatile(address.as_mut_ptr::<u8>(), value as u8),
+        2 => ptr::write_volatile(address.as_mut_ptr::<u16>(), value as u16),
+        4 => ptr::write_volatile(address.as_mut_ptr::<u32>(), value as u32),
+        _ => ptr::write_volatile(address.as_mut_ptr::<u64>(), value),
+    }
+    Ok(())
+}
+
+/// Reads `len` bytes from `start`.
+pub fn read(start: VirtAddr, len: usize) -> Result<Vec<u8>, AccessError> {
+    check(start, len as u64, false)?;
+    // The whole range was just checked to be mapped.
+    let bytes = (0..len).map(|index| unsafe { ptr::read_volatile(start.as_ptr::<u8>().add(index)) }).collect();
+    Ok(bytes)
+}
@@ -27,10 +27,10 @@ pub unsafe fn acpi_shutdown(fadt_address: usize) {
     }
 
     // Write to PM1b_CNT register if it exists
-    if fadt.pm1b_control_block().unwrap().unwrap().address > 0 {
-        let mut port = x86_64::instructions::port::Port::new(
-            fadt.pm1b_control_block().unwrap().unwrap().address as u16,
-        );
-        port.write(SLP_TYP5 | SLP_EN);
+    if let Ok(Some(pm1b)) = fadt.pm1b_control_block() {
+        if pm1b.address > 0 {
+            let mut port = x86_64::instructions::port::Port::new(pm1b.address as u16);
+            port.write(SLP_TYP5 | SLP_EN);
+        }
     }
 }
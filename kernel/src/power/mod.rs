@@ -1,9 +1,11 @@
 pub mod management;
 
 use acpi::{AcpiHandler, PhysicalMapping};
+use alloc::string::String;
 use core::ptr::NonNull;
 use spin::Mutex;
 use x86_64::VirtAddr;
+use crate::shell::{command::{Command, ExitStatus, Io}, registry};
 
 // Define a simple handler that assumes physical memory is already mapped
 pub struct KernelAcpiHandler {
@@ -49,3 +51,36 @@ impl AcpiHandler for KernelAcpiHandler {
 }
 
 pub static FADT_ADDR: Mutex<Option<usize>> = Mutex::new(None);
+
+/// The `shutdown` command, which powers the machine off through ACPI.
+struct Shutdown;
+
+impl Command for Shutdown {
+    fn name(&self) -> &'static str {
+        "shutdown"
+    }
+
+    fn usage(&self) -> &'static str {
+        "shutdown"
+    }
+
+    fn description(&self) -> &'static str {
+        "Powers the machine off"
+    }
+
+    fn run(&self, _args: &[String], io: &mut Io) -> ExitStatus {
+        let Some(fadt_addr) = *FADT_ADDR.lock() else {
+            writeln!(io.err, "shutdown: no FADT, so there is no way to power off");
+            return ExitStatus::FAILURE;
+        };
+        writeln!(io.out, "Shutting down...");
+        unsafe { management::acpi_shutdown(fadt_addr) };
+        writeln!(io.err, "shutdown: the machine is still on...");
+        ExitStatus::FAILURE
+    }
+}
+
+/// Adds the power management commands to the shell.
+pub fn register_commands() {
+    registry::register(Shutdown);
+}
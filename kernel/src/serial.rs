@@ -3,20 +3,44 @@ use crate::framebuffer::ConsoleColor;
 use core::fmt;
 use spin::Mutex;
 use uart_16550::SerialPort;
+use x86_64::instructions::{interrupts::without_interrupts, port::Port};
+
+/// The base I/O port of COM1.
+const COM1: u16 = 0x3F8;
+/// The line status register. Bit 0 is set while a received byte is waiting.
+const LINE_STATUS: u16 = COM1 + 5;
 
 /// Global serial port instance (using the standard I/O port 0x3F8).
-pub static SERIAL1: Mutex<SerialPort> = Mutex::new(unsafe { SerialPort::new(0x3F8) });
+pub static SERIAL1: Mutex<SerialPort> = Mutex::new(unsafe { SerialPort::new(COM1) });
 
-/// Initialize the serial port—call this early in your kernel’s setup.
+/// Initialize the serial port—call this early in your kernel’s setup. This also has it interrupt
+/// on IRQ 4 when a byte comes in.
 pub fn init() {
-    SERIAL1.lock().init();
+    without_interrupts(|| SERIAL1.lock().init());
+}
+
+/// Reads a byte that came in on the serial port, if one is waiting.
+///
+/// This reads the port itself rather than going through [SERIAL1], so it works from an interrupt
+/// handler even while the port is locked for printing: reading received bytes doesn't get in the
+/// way of sending.
+pub fn try_read_byte() -> Option<u8> {
+    unsafe {
+        if Port::<u8>::new(LINE_STATUS).read() & 0x01 == 0 {
+            return None;
+        }
+        Some(Port::<u8>::new(COM1).read())
+    }
 }
 
 /// Internal helper: write formatted arguments to the serial port.
+///
+/// Interrupts are off while the port is locked, since some handlers print to it too, and one
+/// coming in while it was locked would wait for it forever.
 #[doc(hidden)]
 pub fn _print(args: fmt::Arguments) {
     use core::fmt::Write;
-    SERIAL1.lock().write_fmt(args).unwrap();
+    without_interrupts(|| SERIAL1.lock().write_fmt(args).unwrap());
 }
 
 /// Internal helper: write error-formatted arguments to the serial port.
@@ -24,9 +48,11 @@ pub fn _print(args: fmt::Arguments) {
 #[doc(hidden)]
 pub fn _eprint(args: fmt::Arguments) {
     use core::fmt::Write;
-    let mut serial = SERIAL1.lock();
-    write!(serial, "{}", "ERROR: ".fg(ConsoleColor::Red)).unwrap();
-    serial.write_fmt(args).unwrap();
+    without_interrupts(|| {
+        let mut serial = SERIAL1.lock();
+        write!(serial, "{}", "ERROR: ".fg(ConsoleColor::Red)).unwrap();
+        serial.write_fmt(args).unwrap();
+    });
 }
 
 /// Macro for printing to the serial port.
@@ -0,0 +1,542 @@
+//! The expression engine behind `calc`.
+//!
+//! Precedence, from loosest to tightest:
+//!
+//! | Operators         | Meaning                                  |
+//! |-------------------|------------------------------------------|
+//! | `\|`              | bitwise or                               |
+//! | `xor`             | bitwise exclusive or                     |
+//! | `&`               | bitwise and                              |
+//! | `<<` `>>`         | shifts                                   |
+//! | `+` `-`           | addition and subtraction                 |
+//! | `*` `/` `%`       | multiplication, division and remainder   |
+//! | `-` `+` `~`       | negation, and bitwise not (prefix)       |
+//! | `^`               | exponentiation, grouping to the right    |
+//!
+//! Numbers can be written in decimal (`1.5e3`), hex (`0xff`), binary (`0b101`) or octal (`0o17`).
+//! `pi`, `e` and `ans` (the previous answer) are predefined.
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+
+/// Whether the calculator works with integers or floating point numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Float,
+    Integer,
+}
+
+/// A result of the calculator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Int(value) => write!(f, "{value}"),
+            Number::Float(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// An error in an expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalcError {
+    /// The column (counting from 1) where the problem is.
+    pub column: usize,
+    pub message: String,
+}
+
+/// Evaluates an expression.
+///
+/// ### params:
+/// - `expression`: the expression.
+/// - `mode`: whether to work with integers or floats.
+/// - `ans`: the previous answer, which the expression can refer to as `ans`.
+pub fn evaluate(expression: &str, mode: Mode, ans: Option<Number>) -> Result<Number, CalcError> {
+    let tokens = tokenize(expression, mode)?;
+    let mut evaluator = Evaluator {
+        tokens,
+        position: 0,
+        mode,
+        ans,
+        end: expression.chars().count(),
+        depth: 0,
+    };
+    let value = evaluator.bit_or()?;
+    if let Some(token) = evaluator.tokens.get(evaluator.position) {
+        return Err(CalcError {
+            column: token.column,
+            message: String::from("expected an operator"),
+        });
+    }
+    match value {
+        Value::Int(value) => Ok(Number::Int(value)),
+        Value::Float(value) if value.is_finite() => Ok(Number::Float(value)),
+        Value::Float(_) => Err(CalcError {
+            column: 1,
+            message: String::from("the result is not a finite number"),
+        }),
+    }
+}
+
+/// A value while evaluating: an `Int` in integer mode, and a `Float` in float mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Int(i64),
+    Float(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Number(Value),
+    Ident(String),
+    Operator(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    kind: Kind,
+    column: usize,
+}
+
+/// The operators, longest first so `<<` is not read as two `<`s.
+const OPERATORS: [&str; 14] = ["<<", ">>", "+", "-", "*", "/", "%", "^", "(", ")", ",", "&", "|", "~"];
+
+fn tokenize(expression: &str, mode: Mode) -> Result<Vec<Token>, CalcError> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let start = index;
+        let column = start + 1;
+        let ch = chars[index];
+        if ch.is_whitespace() {
+            index += 1;
+            continue;
+        }
+
+        let kind = if ch.is_ascii_digit() || (ch == '.' && chars.get(index + 1).is_some_and(char::is_ascii_digit)) {
+            while index < chars.len() && (chars[index].is_ascii_alphanumeric() || chars[index] == '.') {
+                // Allow a sign in an exponent, like 1e-3.
+                let exponent = matches!(chars[index], 'e' | 'E') && !chars[start..index].contains(&'x');
+                index += 1;
+                if exponent && index < chars.len() && matches!(chars[index], '+' | '-') {
+                    index += 1;
+                }
+            }
+            let literal: String = chars[start..index].iter().collect();
+            Kind::Number(number(&literal, mode).map_err(|message| CalcError { column, message })?)
+        } else if ch.is_ascii_alphabetic() || ch == '_' {
+            while index < chars.len() && (chars[index].is_ascii_alphanumeric() || chars[index] == '_') {
+                index += 1;
+            }
+            Kind::Ident(chars[start..index].iter().collect())
+        } else {
+            let rest: String = chars[index..chars.len().min(index + 2)].iter().collect();
+            let Some(operator) = OPERATORS.iter().find(|operator| rest.starts_with(*operator)) else {
+                return Err(CalcError {
+                    column,
+                    message: format!("unexpected character `{ch}`"),
+                });
+            };
+            index += operator.len();
+            Kind::Operator(operator)
+        };
+        tokens.push(Token { kind, column });
+    }
+    Ok(tokens)
+}
+
+/// Parses a number literal.
+fn number(literal: &str, mode: Mode) -> Result<Value, String> {
+    let radix = match literal.get(..2) {
+        Some("0x" | "0X") => Some(16),
+        Some("0b" | "0B") => Some(2),
+        Some("0o" | "0O") => Some(8),
+        _ => None,
+    };
+    if let Some(radix) = radix {
+        let value = i64::from_str_radix(&literal[2..], radix)
+            .map_err(|_| format!("`{literal}` is not a valid base {radix} number"))?;
+        return Ok(match mode {
+            Mode::Integer => Value::Int(value),
+            Mode::Float => Value::Float(value as f64),
+        });
+    }
+    match mode {
+        Mode::Integer if literal.contains(['.', 'e', 'E']) => {
+            Err(format!("`{literal}` is not an integer; drop -i to use decimals"))
+        }
+        Mode::Integer => literal
+            .parse()
+            .map(Value::Int)
+            .map_err(|_| format!("`{literal}` is not a valid number")),
+        Mode::Float => literal
+            .parse()
+            .map(Value::Float)
+            .map_err(|_| format!("`{literal}` is not a valid number")),
+    }
+}
+
+/// How deeply an expression can nest before it is refused, so `((((...` can't overflow the kernel
+/// stack. Each level takes about 7 KiB of it.
+const MAX_DEPTH: usize = 32;
+
+/// Evaluates tokens as it parses them, one function per precedence level.
+struct Evaluator {
+    tokens: Vec<Token>,
+    position: usize,
+    mode: Mode,
+    ans: Option<Number>,
+    /// The column just past the end, for errors about a missing operand.
+    end: usize,
+    /// How many parentheses, prefix operators, powers and calls are being evaluated inside each
+    /// other.
+    depth: usize,
+}
+
+impl Evaluator {
+    /// Consumes the next token if it is `operator`, returning its column.
+    fn eat(&mut self, operator: &str) -> Option<usize> {
+        let token = self.tokens.get(self.position)?;
+        if token.kind == Kind::Operator(operator_str(operator)?) {
+            self.position += 1;
+            Some(token.column)
+        } else {
+            None
+        }
+    }
+
+    /// Consumes the next token if it is the word `word`.
+    fn eat_word(&mut self, word: &str) -> Option<usize> {
+        let token = self.tokens.get(self.position)?;
+        match &token.kind {
+            Kind::Ident(ident) if ident == word => {
+                self.position += 1;
+                Some(token.column)
+            }
+            _ => None,
+        }
+    }
+
+    fn error<T>(&self, column: usize, message: impl Into<String>) -> Result<T, CalcError> {
+        Err(CalcError {
+            column,
+            message: message.into(),
+        })
+    }
+
+    fn bit_or(&mut self) -> Result<Value, CalcError> {
+        let mut left = self.bit_xor()?;
+        while let Some(column) = self.eat("|") {
+            let right = self.bit_xor()?;
+            left = self.bitwise(left, right, column, |a, b| Some(a | b))?;
+        }
+        Ok(left)
+    }
+
+    fn bit_xor(&mut self) -> Result<Value, CalcError> {
+        let mut left = self.bit_and()?;
+        while let Some(column) = self.eat_word("xor") {
+            let right = self.bit_and()?;
+            left = self.bitwise(left, right, column, |a, b| Some(a ^ b))?;
+        }
+        Ok(left)
+    }
+
+    fn bit_and(&mut self) -> Result<Value, CalcError> {
+        let mut left = self.shift()?;
+        while let Some(column) = self.eat("&") {
+            let right = self.shift()?;
+            left = self.bitwise(left, right, column, |a, b| Some(a & b))?;
+        }
+        Ok(left)
+    }
+
+    fn shift(&mut self) -> Result<Value, CalcError> {
+        let mut left = self.additive()?;
+        loop {
+            if let Some(column) = self.eat("<<") {
+                let right = self.additive()?;
+                left = self.bitwise(left, right, column, |a, b| a.checked_shl(u32::try_from(b).ok()?))?;
+            } else if let Some(column) = self.eat(">>") {
+                let right = self.additive()?;
+                left = self.bitwise(left, right, column, |a, b| a.checked_shr(u32::try_from(b).ok()?))?;
+            } else {
+                return Ok(left);
+            }
+        }
+    }
+
+    fn additive(&mut self) -> Result<Value, CalcError> {
+        let mut left = self.multiplicative()?;
+        loop {
+            if let Some(column) = self.eat("+") {
+                let right = self.multiplicative()?;
+                left = self.arithmetic(left, right, column, i64::checked_add, |a, b| a + b)?;
+            } else if let Some(column) = self.eat("-") {
+                let right = self.multiplicative()?;
+                left = self.arithmetic(left, right, column, i64::checked_sub, |a, b| a - b)?;
+            } else {
+                return Ok(left);
+            }
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Value, CalcError> {
+        let mut left = self.unary()?;
+        loop {
+            if let Some(column) = self.eat("*") {
+                let right = self.unary()?;
+                left = self.arithmetic(left, right, column, i64::checked_mul, |a, b| a * b)?;
+            } else if let Some(column) = self.eat("/") {
+                let right = self.unary()?;
+                self.check_divisor(right, column)?;
+                left = self.arithmetic(left, right, column, i64::checked_div, |a, b| a / b)?;
+            } else if let Some(column) = self.eat("%") {
+                let right = self.unary()?;
+                self.check_divisor(right, column)?;
+                left = self.arithmetic(left, right, column, i64::checked_rem, libm::fmod)?;
+            } else {
+                return Ok(left);
+            }
+        }
+    }
+
+    /// Every nested part of an expression comes through here, so this is where the depth is
+    /// counted.
+    fn unary(&mut self) -> Result<Value, CalcError> {
+        if self.depth >= MAX_DEPTH {
+            let column = self.tokens.get(self.position).map_or(self.end + 1, |token| token.column);
+            return self.error(column, "expression too deeply nested");
+        }
+        self.depth += 1;
+        let value = self.prefixed();
+        self.depth -= 1;
+        value
+    }
+
+    fn prefixed(&mut self) -> Result<Value, CalcError> {
+        if let Some(column) = self.eat("-") {
+            return match self.unary()? {
+                Value::Int(value) => match value.checked_neg() {
+                    Some(value) => Ok(Value::Int(value)),
+                    None => self.error(column, "the result is too big"),
+                },
+                Value::Float(value) => Ok(Value::Float(-value)),
+            };
+        }
+        if self.eat("+").is_some() {
+            return self.unary();
+        }
+        if let Some(column) = self.eat("~") {
+            let value = self.unary()?;
+            return self.bitwise(value, value, column, |a, _| Some(!a));
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Value, CalcError> {
+        let base = self.primary()?;
+        let Some(column) = self.eat("^") else {
+            return Ok(base);
+        };
+        // Right-associative, and tighter than a prefix minus on the left: -2^2 is -4.
+        let exponent = self.unary()?;
+        match (base, exponent) {
+            (Value::Int(_), Value::Int(exponent)) if exponent < 0 => {
+                self.error(column, "negative powers need decimals; drop -i")
+            }
+            (Value::Int(base), Value::Int(exponent)) => {
+                match u32::try_from(exponent).ok().and_then(|exponent| base.checked_pow(exponent)) {
+                    Some(value) => Ok(Value::Int(value)),
+                    None => self.error(column, "the result is too big"),
+                }
+            }
+            (base, exponent) => Ok(Value::Float(libm::pow(float(base), float(exponent)))),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Value, CalcError> {
+        let Some(token) = self.tokens.get(self.position).cloned() else {
+            return self.error(self.end + 1, "expected a number, but the expression ended");
+        };
+        self.position += 1;
+        match token.kind {
+            Kind::Number(value) => Ok(value),
+            Kind::Operator("(") => {
+                let value = self.bit_or()?;
+                if self.eat(")").is_none() {
+                    let column = self.tokens.get(self.position).map_or(self.end + 1, |token| token.column);
+                    return self.error(column, format!("expected `)` to close the `(` at column {}", token.column));
+                }
+                Ok(value)
+            }
+            Kind::Ident(name) if self.eat("(").is_some() => self.call(&name, token.column),
+            Kind::Ident(name) => self.constant(&name, token.column),
+            Kind::Operator(operator) => self.error(token.column, format!("expected a number, but found `{operator}`")),
+        }
+    }
+
+    fn constant(&self, name: &str, column: usize) -> Result<Value, CalcError> {
+        let value = match name {
+            "pi" => Number::Float(core::f64::consts::PI),
+            "e" => Number::Float(core::f64::consts::E),
+            "ans" => match self.ans {
+                Some(ans) => ans,
+                None => return self.error(column, "there is no previous answer yet"),
+            },
+            _ => return self.error(column, format!("unknown constant `{name}`")),
+        };
+        match (self.mode, value) {
+            (Mode::Float, Number::Int(value)) => Ok(Value::Float(value as f64)),
+            (Mode::Float, Number::Float(value)) => Ok(Value::Float(value)),
+            (Mode::Integer, Number::Int(value)) => Ok(Value::Int(value)),
+            (Mode::Integer, Number::Float(value)) => match whole(value) {
+                Some(value) => Ok(Value::Int(value)),
+                None => self.error(column, format!("`{name}` is not an integer; drop -i to use it")),
+            },
+        }
+    }
+
+    /// Calls a function, the `(` having been consumed.
+    fn call(&mut self, name: &str, column: usize) -> Result<Value, CalcError> {
+        let mut args = Vec::new();
+        if self.eat(")").is_none() {
+            loop {
+                args.push(self.bit_or()?);
+                if self.eat(")").is_some() {
+                    break;
+                }
+                if self.eat(",").is_none() {
+                    let column = self.tokens.get(self.position).map_or(self.end + 1, |token| token.column);
+                    return self.error(column, format!("expected `,` or `)` in the call to `{name}`"));
+                }
+            }
+        }
+
+        let expected = match name {
+            "min" | "max" => 2,
+            "sqrt" | "sin" | "cos" | "tan" | "log" | "ln" | "exp" | "abs" | "floor" | "ceil" | "round" => 1,
+            _ => return self.error(column, format!("unknown function `{name}`")),
+        };
+        if args.len() != expected {
+            return self.error(column, format!("`{name}` takes {expected} argument(s), not {}", args.len()));
+        }
+
+        match (name, args[0]) {
+            ("abs", Value::Int(value)) => match value.checked_abs() {
+                Some(value) => Ok(Value::Int(value)),
+                None => self.error(column, "the result is too big"),
+            },
+            ("min", Value::Int(a)) => Ok(Value::Int(a.min(int(args[1])))),
+            ("max", Value::Int(a)) => Ok(Value::Int(a.max(int(args[1])))),
+            ("floor" | "ceil" | "round", Value::Int(value)) => Ok(Value::Int(value)),
+            ("sqrt", Value::Int(value)) if value >= 0 => Ok(Value::Int(libm::sqrt(value as f64) as i64)),
+            (_, Value::Int(_)) if name != "sqrt" => {
+                self.error(column, format!("`{name}` needs decimals; drop -i to use it"))
+            }
+            (_, value) => {
+                let x = float(value);
+                let result = match name {
+                    "sqrt" if x < 0.0 => return self.error(column, "cannot take the square root of a negative number"),
+                    "log" | "ln" if x <= 0.0 => return self.error(column, format!("`{name}` needs a number above zero")),
+                    "sqrt" => libm::sqrt(x),
+                    "sin" => libm::sin(x),
+                    "cos" => libm::cos(x),
+                    "tan" => libm::tan(x),
+                    "log" => libm::log10(x),
+                    "ln" => libm::log(x),
+                    "exp" => libm::exp(x),
+                    "abs" => libm::fabs(x),
+                    "floor" => libm::floor(x),
+                    "ceil" => libm::ceil(x),
+                    "round" => libm::round(x),
+                    "min" => libm::fmin(x, float(args[1])),
+                    _ => libm::fmax(x, float(args[1])),
+                };
+                Ok(Value::Float(result))
+            }
+        }
+    }
+
+    fn check_divisor(&self, divisor: Value, column: usize) -> Result<(), CalcError> {
+        if divisor == Value::Int(0) || divisor == Value::Float(0.0) {
+            return self.error(column, "division by zero");
+        }
+        Ok(())
+    }
+
+    /// Applies an arithmetic operator, checking integers for overflow.
+    fn arithmetic(
+        &self,
+        left: Value,
+        right: Value,
+        column: usize,
+        integers: fn(i64, i64) -> Option<i64>,
+        floats: fn(f64, f64) -> f64,
+    ) -> Result<Value, CalcError> {
+        match (left, right) {
+            (Value::Int(a), Value::Int(b)) => match integers(a, b) {
+                Some(value) => Ok(Value::Int(value)),
+                None => self.error(column, "the result is too big"),
+            },
+            (a, b) => Ok(Value::Float(floats(float(a), float(b)))),
+        }
+    }
+
+    /// Applies a bitwise operator, which only works on whole numbers.
+    fn bitwise(
+        &self,
+        left: Value,
+        right: Value,
+        column: usize,
+        operator: fn(i64, i64) -> Option<i64>,
+    ) -> Result<Value, CalcError> {
+        let (Some(a), Some(b)) = (integral(left), integral(right)) else {
+            return self.error(column, "bitwise operators only work on whole numbers");
+        };
+        let Some(result) = operator(a, b) else {
+            return self.error(column, "the shift is out of range");
+        };
+        Ok(match self.mode {
+            Mode::Integer => Value::Int(result),
+            Mode::Float => Value::Float(result as f64),
+        })
+    }
+}
+
+/// Returns the `&'static str` in [OPERATORS] equal to `operator`.
+fn operator_str(operator: &str) -> Option<&'static str> {
+    OPERATORS.iter().copied().find(|candidate| *candidate == operator)
+}
+
+fn float(value: Value) -> f64 {
+    match value {
+        Value::Int(value) => value as f64,
+        Value::Float(value) => value,
+    }
+}
+
+/// The value of an integer-mode argument. Everything is an `Int` in integer mode.
+fn int(value: Value) -> i64 {
+    match value {
+        Value::Int(value) => value,
+        Value::Float(value) => value as i64,
+    }
+}
+
+/// Returns the value as an integer, if it is a whole number that fits.
+fn whole(value: f64) -> Option<i64> {
+    (libm::trunc(value) == value && value.abs() < i64::MAX as f64).then_some(value as i64)
+}
+
+fn integral(value: Value) -> Option<i64> {
+    match value {
+        Value::Int(value) => Some(value),
+        Value::Float(value) => whole(value),
+    }
+}
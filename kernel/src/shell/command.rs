@@ -0,0 +1,152 @@
+use crate::{
+    framebuffer::_print,
+    shell::{completion::{ArgKind, OptionSpec}, pager::AutoPager, session::Session}
+};
+use alloc::{format, string::String};
+use core::fmt::{self, Write};
+
+/// A command that can be run from the shell.
+///
+/// Commands are added to the [registry](crate::shell::registry), which is what the shell looks
+/// names up in, and what `help` and Tab completion are generated from.
+pub trait Command: Send + Sync {
+    /// The name the command is run by.
+    fn name(&self) -> &'static str;
+
+    /// Other names the command can be run by.
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// A one-line synopsis of the command's arguments, e.g. `echo [-c <color>] <message>`.
+    fn usage(&self) -> &'static str;
+
+    /// A short description of what the command does.
+    fn description(&self) -> &'static str;
+
+    /// Options that take a value, for Tab completion.
+    fn options(&self) -> &'static [OptionSpec] {
+        &[]
+    }
+
+    /// What the command's other arguments are, for Tab completion.
+    fn operands(&self) -> ArgKind {
+        ArgKind::Text
+    }
+
+    /// Runs the command.
+    ///
+    /// ### params:
+    /// - `args`: the arguments, not including the command's name.
+    /// - `io`: the command's input and output, and the session it runs in.
+    ///
+    /// ### returns:
+    /// - `ExitStatus`: how it went, which is what `$?`, `&&` and `||` look at.
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus;
+
+    /// Whether the command's output should go to the pager if it is run at the prompt, and turns
+    /// out to be taller than the screen. Commands that read keys themselves must not.
+    fn pages_output(&self) -> bool {
+        false
+    }
+
+    /// Returns true if `name` refers to this command.
+    fn is_called(&self, name: &str) -> bool {
+        self.name() == name || self.aliases().contains(&name)
+    }
+}
+
+/// The status a command exits with. Zero means success, and anything else some kind of failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ExitStatus(pub u8);
+
+impl ExitStatus {
+    /// The command did what it was asked to.
+    pub const SUCCESS: ExitStatus = ExitStatus(0);
+    /// The command failed.
+    pub const FAILURE: ExitStatus = ExitStatus(1);
+    /// The command was misused, or wants to warn the user about something.
+    pub const WARNING: ExitStatus = ExitStatus(2);
+    /// There is no command by that name.
+    pub const NOT_FOUND: ExitStatus = ExitStatus(127);
+
+    /// Returns true if this is [ExitStatus::SUCCESS].
+    pub fn is_success(self) -> bool {
+        self == Self::SUCCESS
+    }
+
+    /// Returns the numeric code, as `$?` shows it.
+    pub fn code(self) -> u8 {
+        self.0
+    }
+}
+
+impl From<bool> for ExitStatus {
+    /// Turns a condition into a status: success if it holds, and failure if not.
+    fn from(condition: bool) -> Self {
+        if condition { Self::SUCCESS } else { Self::FAILURE }
+    }
+}
+
+/// Everything a running command can reach: its input and output, and the session it runs in.
+pub struct Io<'a> {
+    pub out: &'a mut Output,
+    /// Where error messages go. It is always the screen, so they are seen rather than piped into
+    /// the next command or written to a file with `out`.
+    pub err: Output,
+    /// Text piped or redirected into the command, if there is any.
+    pub input: Option<String>,
+    pub session: &'a mut Session,
+}
+
+impl Io<'_> {
+    /// Checks that the command's output goes straight to the screen, for full-screen commands
+    /// that draw on it and read keys, saying so if it doesn't.
+    ///
+    /// ### params:
+    /// - `name`: the command's name, to start the message with.
+    pub fn require_screen(&mut self, name: &str) -> bool {
+        if matches!(self.out, Output::Screen) {
+            return true;
+        }
+        writeln!(self.err, "{name}: this needs the screen, so its output can't be piped or redirected");
+        false
+    }
+}
+
+/// Where a command's output goes.
+pub enum Output {
+    /// Straight to the screen.
+    Screen,
+    /// Into a string, to be piped into another command, written to a file, or substituted by
+    /// `$(command)`.
+    Buffer(String),
+    /// To the screen, until there is more than fits on it; then into the pager. For commands that
+    /// [page their output](Command::pages_output).
+    Paged(AutoPager),
+}
+
+impl Output {
+    /// Writes formatted output. This lets commands use `write!` and `writeln!` on an `Output`.
+    pub fn write_fmt(&mut self, args: fmt::Arguments) {
+        match self {
+            Output::Screen => _print(args),
+            // Writing to a String cannot fail.
+            Output::Buffer(buffer) => _ = buffer.write_fmt(args),
+            Output::Paged(pager) => pager.write(&format!("{args}")),
+        }
+    }
+
+    /// Writes a string to the output.
+    pub fn write_str(&mut self, text: &str) {
+        self.write_fmt(format_args!("{text}"));
+    }
+
+    /// Returns what was written to a [Output::Buffer], or nothing for the screen.
+    pub fn into_string(self) -> String {
+        match self {
+            Output::Screen | Output::Paged(_) => String::new(),
+            Output::Buffer(buffer) => buffer,
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::shell::command::{Command, ExitStatus, Io, Output};
+use alloc::string::String;
+
+/// Returns true if `name` can be an alias: something the shell reads as a plain word, with no
+/// `/` (which would make it a path) or `=` in it.
+fn is_valid_alias(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && !name.chars().any(|ch| ch.is_whitespace() || "/=$'\"\\|&;<>()#".contains(ch))
+}
+
+/// Prints an alias the way it would be defined, so the output can be run again.
+fn print_alias(out: &mut Output, name: &str, value: &str) {
+    writeln!(out, "alias {}='{}'", name, value.replace('\'', "'\\''"));
+}
+
+pub struct Alias;
+
+impl Command for Alias {
+    fn name(&self) -> &'static str {
+        "alias"
+    }
+
+    fn usage(&self) -> &'static str {
+        "alias [name[=command]...]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Defines shorthands for commands, or lists them"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        if args.is_empty() {
+            for (name, value) in &io.session.aliases {
+                print_alias(io.out, name, value);
+            }
+            return ExitStatus::SUCCESS;
+        }
+
+        let mut status = ExitStatus::SUCCESS;
+        for arg in args {
+            match arg.split_once('=') {
+                Some((name, value)) if is_valid_alias(name) => {
+                    io.session.aliases.insert(String::from(name), String::from(value));
+                }
+                Some((name, _)) => {
+                    writeln!(io.err, "alias: `{name}': not a valid alias name");
+                    status = ExitStatus::FAILURE;
+                }
+                None => match io.session.aliases.get(arg.as_str()) {
+                    Some(value) => print_alias(io.out, arg, value),
+                    None => {
+                        writeln!(io.err, "alias: {arg}: not found");
+                        status = ExitStatus::FAILURE;
+                    }
+                },
+            }
+        }
+        status
+    }
+}
+
+pub struct Unalias;
+
+impl Command for Unalias {
+    fn name(&self) -> &'static str {
+        "unalias"
+    }
+
+    fn usage(&self) -> &'static str {
+        "unalias [-a] <name...>"
+    }
+
+    fn description(&self) -> &'static str {
+        "Removes aliases, or all of them with -a"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        match args {
+            [] => {
+                writeln!(io.err, "Usage: {}", self.usage());
+                ExitStatus::FAILURE
+            }
+            [flag] if flag == "-a" => {
+                io.session.aliases.clear();
+                ExitStatus::SUCCESS
+            }
+            names => {
+                let mut status = ExitStatus::SUCCESS;
+                for name in names {
+                    if io.session.aliases.remove(name.as_str()).is_none() {
+                        writeln!(io.err, "unalias: {name}: not found");
+                        status = ExitStatus::FAILURE;
+                    }
+                }
+                status
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{
+    framebuffer::{color::ColoredWriting, global_writer::clear_screen, ConsoleColor},
+    shell::{
+        command::{Command, ExitStatus, Io},
+        completion::{ArgKind, OptionSpec},
+        registry
+    }
+};
+use alloc::{string::String, vec::Vec};
+
+pub struct Help;
+
+impl Command for Help {
+    fn name(&self) -> &'static str {
+        "help"
+    }
+
+    fn usage(&self) -> &'static str {
+        "help [command]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists the available commands, or explains one of them"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Command
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        if let Some(name) = args.first() {
+            let Some(command) = registry::find(name) else {
+                writeln!(io.err, "help: {name} is not a valid command");
+                return ExitStatus::FAILURE;
+            };
+            writeln!(io.out, "Usage: {}", command.usage());
+            writeln!(io.out, "{}", command.description());
+            if !command.aliases().is_empty() {
+                writeln!(io.out, "Aliases: {}", command.aliases().join(", "));
+            }
+            return ExitStatus::SUCCESS;
+        }
+
+        let commands = registry::all();
+        let width = commands.iter().map(|command| command.name().len()).max().unwrap_or(0);
+        writeln!(io.out, "Available commands:");
+        for command in commands {
+            writeln!(io.out, "  {:<width$}  {}", command.name(), command.description());
+        }
+        writeln!(io.out, "Run `help <command>` to learn more about a command, or `man <command>` to read its manual.");
+        writeln!(io.out, "Try running ls...");
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Clear;
+
+impl Command for Clear {
+    fn name(&self) -> &'static str {
+        "clear"
+    }
+
+    fn usage(&self) -> &'static str {
+        "clear"
+    }
+
+    fn description(&self) -> &'static str {
+        "Clears the screen"
+    }
+
+    fn run(&self, _args: &[String], _io: &mut Io) -> ExitStatus {
+        clear_screen();
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Echo;
+
+impl Command for Echo {
+    fn name(&self) -> &'static str {
+        "echo"
+    }
+
+    fn usage(&self) -> &'static str {
+        "echo [-c <color>] <message>"
+    }
+
+    fn description(&self) -> &'static str {
+        "Prints a message, optionally in color"
+    }
+
+    fn options(&self) -> &'static [OptionSpec] {
+        &[OptionSpec { flag: "-c", value: ArgKind::Color }]
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        if args.is_empty() {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        }
+
+        // Check for the color switch: echo -c <color> <message>
+        if args[0] == "-c" && args.len() > 2 {
+            let Some(color) = ConsoleColor::from_name(&args[1]) else {
+                let names: Vec<&str> = ConsoleColor::ALL.iter().map(ConsoleColor::name).collect();
+                writeln!(io.err, "Invalid color. Supported: {}.", names.join(", "));
+                return ExitStatus::FAILURE;
+            };
+            let message = args[2..].join(" ");
+            writeln!(io.out, "{}", message.fg(color));
+        } else {
+            let message = args.join(" ");
+            writeln!(io.out, "{message}");
+        }
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct History;
+
+impl Command for History {
+    fn name(&self) -> &'static str {
+        "history"
+    }
+
+    fn usage(&self) -> &'static str {
+        "history [-c | <count>]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists the commands run so far, or clears them with -c"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let history = &mut io.session.history;
+        let count = match args.first().map(String::as_str) {
+            None => history.len(),
+            Some("-c") => {
+                history.clear();
+                io.session.save_history();
+                return ExitStatus::SUCCESS;
+            }
+            Some(count) => match count.parse::<usize>() {
+                Ok(count) => count.min(history.len()),
+                Err(_) => {
+                    writeln!(io.err, "Usage: {}", self.usage());
+                    return ExitStatus::FAILURE;
+                }
+            },
+        };
+        let skip = history.len() - count;
+        for (number, entry) in history.iter().enumerate().skip(skip) {
+            writeln!(io.out, "{:>5}  {}", number + 1, entry);
+        }
+        ExitStatus::SUCCESS
+    }
+}
@@ -0,0 +1,56 @@
+use crate::shell::{
+    calc::{self, Mode},
+    command::{Command, ExitStatus, Io}
+};
+use alloc::string::String;
+
+pub struct Calc;
+
+impl Command for Calc {
+    fn name(&self) -> &'static str {
+        "calc"
+    }
+
+    fn usage(&self) -> &'static str {
+        "calc [-i | -f] <expression>"
+    }
+
+    fn description(&self) -> &'static str {
+        "Evaluates an arithmetic expression"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let (mode, rest) = match args.first().map(String::as_str) {
+            Some("-i") => (Mode::Integer, &args[1..]),
+            Some("-f") => (Mode::Float, &args[1..]),
+            _ => (Mode::Float, args),
+        };
+        let expression = if rest.is_empty() {
+            io.input.take().unwrap_or_default().trim().replace('\n', " ")
+        } else {
+            rest.join(" ")
+        };
+        if expression.is_empty() {
+            writeln!(io.err, "Usage: {}", self.usage());
+            writeln!(io.err, "Operators: + - * / % ^ & | xor ~ << >> and parentheses");
+            writeln!(io.err, "Functions: sqrt sin cos tan log ln exp abs floor ceil round min max");
+            writeln!(io.err, "Constants: pi e ans. Quote expressions using | & < or >.");
+            return ExitStatus::FAILURE;
+        }
+
+        match calc::evaluate(&expression, mode, io.session.calc_ans) {
+            Ok(answer) => {
+                io.session.calc_ans = Some(answer);
+                writeln!(io.out, "{answer}");
+                ExitStatus::SUCCESS
+            }
+            Err(error) => {
+                let padding: String = (1..error.column).map(|_| ' ').collect();
+                writeln!(io.err, "{expression}");
+                writeln!(io.err, "{padding}^");
+                writeln!(io.err, "calc: column {}: {}", error.column, error.message);
+                ExitStatus::FAILURE
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::{
+    debugger,
+    shell::command::{Command, ExitStatus, Io}
+};
+use alloc::string::String;
+
+pub struct Kdb;
+
+impl Command for Kdb {
+    fn name(&self) -> &'static str {
+        "kdb"
+    }
+
+    fn usage(&self) -> &'static str {
+        "kdb [faults [on|off]]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Stops in the kernel debugger, or sets whether faults stop there before panicking"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        if !io.session.is_root() {
+            writeln!(io.err, "kdb: only root can use the debugger; try su first");
+            return ExitStatus::FAILURE;
+        }
+        match args {
+            [] => {
+                writeln!(io.out, "Stopping in the debugger: type help there, or continue to come back.");
+                debugger::breakpoint();
+            }
+            [faults] if faults == "faults" => {
+                let state = if debugger::catches_faults() { "on" } else { "off" };
+                writeln!(io.out, "Stopping on faults is {state}.");
+            }
+            [faults, state] if faults == "faults" && (state == "on" || state == "off") => {
+                debugger::set_catch_faults(state == "on");
+            }
+            _ => {
+                writeln!(io.err, "Usage: {}", self.usage());
+                return ExitStatus::FAILURE;
+            }
+        }
+        ExitStatus::SUCCESS
+    }
+}
@@ -0,0 +1,152 @@
+use crate::shell::{
+    command::{Command, ExitStatus, Io},
+    environment::is_valid_name
+};
+use alloc::string::String;
+
+/// Splits a `NAME=value` argument. An argument without `=` is just a name.
+fn split_assignment(arg: &str) -> (&str, Option<&str>) {
+    match arg.split_once('=') {
+        Some((name, value)) => (name, Some(value)),
+        None => (arg, None),
+    }
+}
+
+pub struct Set;
+
+impl Command for Set {
+    fn name(&self) -> &'static str {
+        "set"
+    }
+
+    fn usage(&self) -> &'static str {
+        "set [NAME=value...]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Sets shell variables, or lists them all"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        if args.is_empty() {
+            for (name, variable) in io.session.environment.iter() {
+                writeln!(io.out, "{}={}", name, variable.value);
+            }
+            return ExitStatus::SUCCESS;
+        }
+
+        let mut status = ExitStatus::SUCCESS;
+        for arg in args {
+            match split_assignment(arg) {
+                (name, Some(value)) if is_valid_name(name) => io.session.environment.set(name, value),
+                _ => {
+                    writeln!(io.err, "set: `{arg}': expected NAME=value");
+                    status = ExitStatus::FAILURE;
+                }
+            }
+        }
+        status
+    }
+}
+
+pub struct Unset;
+
+impl Command for Unset {
+    fn name(&self) -> &'static str {
+        "unset"
+    }
+
+    fn usage(&self) -> &'static str {
+        "unset <name...>"
+    }
+
+    fn description(&self) -> &'static str {
+        "Removes shell variables"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        if args.is_empty() {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        }
+        for name in args {
+            io.session.environment.unset(name);
+        }
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Export;
+
+impl Command for Export {
+    fn name(&self) -> &'static str {
+        "export"
+    }
+
+    fn usage(&self) -> &'static str {
+        "export [NAME[=value]...]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Adds shell variables to the environment, or lists the environment"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        if args.is_empty() {
+            for (name, variable) in io.session.environment.iter().filter(|(_, variable)| variable.exported) {
+                writeln!(io.out, "export {}={}", name, variable.value);
+            }
+            return ExitStatus::SUCCESS;
+        }
+
+        let mut status = ExitStatus::SUCCESS;
+        for arg in args {
+            let (name, value) = split_assignment(arg);
+            if !is_valid_name(name) {
+                writeln!(io.err, "export: `{name}': not a valid name");
+                status = ExitStatus::FAILURE;
+                continue;
+            }
+            if let Some(value) = value {
+                io.session.environment.set(name, value);
+            }
+            io.session.environment.export(name);
+        }
+        status
+    }
+}
+
+pub struct Env;
+
+impl Command for Env {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    fn usage(&self) -> &'static str {
+        "env"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists the environment: the variables that have been exported"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn run(&self, _args: &[String], io: &mut Io) -> ExitStatus {
+        for (name, variable) in io.session.environment.iter().filter(|(_, variable)| variable.exported) {
+            writeln!(io.out, "{}={}", name, variable.value);
+        }
+        ExitStatus::SUCCESS
+    }
+}
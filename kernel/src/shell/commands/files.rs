@@ -0,0 +1,193 @@
+use crate::{
+    framebuffer,
+    fs,
+    shell::{
+        command::{Command, ExitStatus, Io},
+        completion::ArgKind,
+        editor,
+        markdown,
+        pager
+    }
+};
+use alloc::{string::String, vec::Vec};
+
+pub struct Cat;
+
+impl Command for Cat {
+    fn name(&self) -> &'static str {
+        "cat"
+    }
+
+    fn usage(&self) -> &'static str {
+        "cat [file...]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Prints files, or its input if no file is given"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Path
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let Some(text) = super::read_input(self.name(), args, io) else {
+            return ExitStatus::FAILURE;
+        };
+        io.out.write_str(&text);
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Ls;
+
+impl Command for Ls {
+    fn name(&self) -> &'static str {
+        "ls"
+    }
+
+    fn usage(&self) -> &'static str {
+        "ls [-a] [directory]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists files, hiding the ones whose name starts with a dot unless -a is given"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Path
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let all = args.first().is_some_and(|arg| arg == "-a");
+        let rest = if all { &args[1..] } else { args };
+        if rest.len() > 1 {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        }
+
+        let mut directory = fs::normalize(rest.first().map_or("/", String::as_str));
+        if !directory.ends_with('/') {
+            directory.push('/');
+        }
+        let paths: Vec<String> = fs::with_store(|store| {
+            store
+                .paths()
+                .filter_map(|path| path.strip_prefix(directory.as_str()))
+                .filter(|name| all || !name.starts_with('.'))
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+        for path in paths {
+            writeln!(io.out, "{path}");
+        }
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Md;
+
+impl Command for Md {
+    fn name(&self) -> &'static str {
+        "md"
+    }
+
+    fn usage(&self) -> &'static str {
+        "md [file...]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Renders Markdown files, or its input if no file is given, wrapped to the screen"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Path
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let Some(text) = super::read_input(self.name(), args, io) else {
+            return ExitStatus::FAILURE;
+        };
+        io.out.write_str(&markdown::render(&text, framebuffer::columns()));
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Less;
+
+impl Command for Less {
+    fn name(&self) -> &'static str {
+        "less"
+    }
+
+    fn usage(&self) -> &'static str {
+        "less [file...]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows files, or its input if no file is given, a screen at a time"
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Path
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let Some(text) = super::read_input(self.name(), args, io) else {
+            return ExitStatus::FAILURE;
+        };
+        let title = if args.is_empty() { String::from("(input)") } else { args.join(" ") };
+        pager::show(&text, &title, io.out);
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Edit;
+
+impl Command for Edit {
+    fn name(&self) -> &'static str {
+        "edit"
+    }
+
+    fn usage(&self) -> &'static str {
+        "edit <file>"
+    }
+
+    fn description(&self) -> &'static str {
+        "Edits a file in a full-screen editor, creating it when saved if it doesn't exist"
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Path
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let [path] = args else {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        };
+        let path = fs::normalize(path);
+        if !io.session.can_write(&path) {
+            writeln!(io.err, "edit: {path}: Permission denied; only root can change files in another user's home");
+            return ExitStatus::FAILURE;
+        }
+        if !io.require_screen(self.name()) {
+            return ExitStatus::FAILURE;
+        }
+        editor::edit(&path);
+        ExitStatus::SUCCESS
+    }
+}
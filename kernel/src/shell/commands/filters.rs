@@ -0,0 +1,307 @@
+//! Commands that transform text, to put in the middle of a pipeline.
+use crate::shell::{
+    command::{Command, ExitStatus, Io},
+    completion::ArgKind
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec
+};
+
+/// Splits the leading `-x` flags off of `args`.
+///
+/// ### returns:
+/// - `Ok((flags, rest))`: the flags, and the arguments after them.
+/// - `Err(flag)`: the first flag that is not in `allowed`.
+fn split_flags<'a>(args: &'a [String], allowed: &[&str]) -> Result<(Vec<&'a str>, &'a [String]), &'a str> {
+    let count = args.iter().take_while(|arg| arg.len() > 1 && arg.starts_with('-')).count();
+    let flags: Vec<&str> = args[..count].iter().map(String::as_str).collect();
+    match flags.iter().find(|flag| !allowed.contains(flag)) {
+        Some(flag) => Err(flag),
+        None => Ok((flags, &args[count..])),
+    }
+}
+
+/// Reads the `-n <count>` option of `head` and `tail`.
+///
+/// ### returns:
+/// - `Some((count, rest))`: the number of lines (10 if not given), and the remaining arguments.
+/// - `None`: if the option is malformed.
+fn line_count(args: &[String]) -> Option<(usize, &[String])> {
+    match args {
+        [flag, count, rest @ ..] if flag == "-n" => Some((count.parse().ok()?, rest)),
+        [flag, ..] if flag == "-n" => None,
+        rest => Some((10, rest)),
+    }
+}
+
+pub struct Grep;
+
+impl Command for Grep {
+    fn name(&self) -> &'static str {
+        "grep"
+    }
+
+    fn usage(&self) -> &'static str {
+        "grep [-i] [-v] [-n] <pattern> [file...]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Prints the lines that contain a pattern"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Path
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let Ok((flags, [pattern, files @ ..])) = split_flags(args, &["-i", "-v", "-n"]) else {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        };
+        let ignore_case = flags.contains(&"-i");
+        let invert = flags.contains(&"-v");
+        let numbered = flags.contains(&"-n");
+        let Some(text) = super::read_input(self.name(), files, io) else {
+            return ExitStatus::FAILURE;
+        };
+
+        let pattern = if ignore_case { pattern.to_lowercase() } else { pattern.clone() };
+        let mut found = false;
+        for (number, line) in text.lines().enumerate() {
+            let matches = if ignore_case {
+                line.to_lowercase().contains(&pattern)
+            } else {
+                line.contains(&pattern)
+            };
+            if matches != invert {
+                found = true;
+                if numbered {
+                    writeln!(io.out, "{}:{}", number + 1, line);
+                } else {
+                    writeln!(io.out, "{line}");
+                }
+            }
+        }
+        // Like grep, finding nothing counts as a failure.
+        ExitStatus::from(found)
+    }
+}
+
+pub struct Wc;
+
+impl Command for Wc {
+    fn name(&self) -> &'static str {
+        "wc"
+    }
+
+    fn usage(&self) -> &'static str {
+        "wc [-l] [-w] [-c] [file...]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Counts lines, words and bytes"
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Path
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let Ok((flags, files)) = split_flags(args, &["-l", "-w", "-c"]) else {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        };
+        let Some(text) = super::read_input(self.name(), files, io) else {
+            return ExitStatus::FAILURE;
+        };
+
+        let counts = [
+            ("-l", text.lines().count()),
+            ("-w", text.split_whitespace().count()),
+            ("-c", text.len()),
+        ];
+        let shown: Vec<String> = counts
+            .iter()
+            .filter(|(flag, _)| flags.is_empty() || flags.contains(flag))
+            .map(|(_, count)| count.to_string())
+            .collect();
+        writeln!(io.out, "{}", shown.join(" "));
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Head;
+
+impl Command for Head {
+    fn name(&self) -> &'static str {
+        "head"
+    }
+
+    fn usage(&self) -> &'static str {
+        "head [-n <count>] [file...]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Prints the first lines of its input"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Path
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let Some((count, files)) = line_count(args) else {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        };
+        let Some(text) = super::read_input(self.name(), files, io) else {
+            return ExitStatus::FAILURE;
+        };
+        for line in text.lines().take(count) {
+            writeln!(io.out, "{line}");
+        }
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Tail;
+
+impl Command for Tail {
+    fn name(&self) -> &'static str {
+        "tail"
+    }
+
+    fn usage(&self) -> &'static str {
+        "tail [-n <count>] [file...]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Prints the last lines of its input"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Path
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let Some((count, files)) = line_count(args) else {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        };
+        let Some(text) = super::read_input(self.name(), files, io) else {
+            return ExitStatus::FAILURE;
+        };
+        let lines: Vec<&str> = text.lines().collect();
+        for line in &lines[lines.len().saturating_sub(count)..] {
+            writeln!(io.out, "{line}");
+        }
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Sort;
+
+impl Command for Sort {
+    fn name(&self) -> &'static str {
+        "sort"
+    }
+
+    fn usage(&self) -> &'static str {
+        "sort [-r] [file...]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Sorts lines, in reverse with -r"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Path
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let Ok((flags, files)) = split_flags(args, &["-r"]) else {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        };
+        let Some(text) = super::read_input(self.name(), files, io) else {
+            return ExitStatus::FAILURE;
+        };
+        let mut lines: Vec<&str> = text.lines().collect();
+        lines.sort_unstable();
+        if flags.contains(&"-r") {
+            lines.reverse();
+        }
+        for line in lines {
+            writeln!(io.out, "{line}");
+        }
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Uniq;
+
+impl Command for Uniq {
+    fn name(&self) -> &'static str {
+        "uniq"
+    }
+
+    fn usage(&self) -> &'static str {
+        "uniq [-c] [file...]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Drops repeated lines, counting them with -c"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Path
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let Ok((flags, files)) = split_flags(args, &["-c"]) else {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        };
+        let Some(text) = super::read_input(self.name(), files, io) else {
+            return ExitStatus::FAILURE;
+        };
+
+        // Like uniq, only adjacent repeats are merged; sort first to merge them all.
+        let mut runs: Vec<(&str, usize)> = Vec::new();
+        for line in text.lines() {
+            match runs.last_mut() {
+                Some((last, count)) if *last == line => *count += 1,
+                _ => runs.push((line, 1)),
+            }
+        }
+        for (line, count) in runs {
+            if flags.contains(&"-c") {
+                writeln!(io.out, "{count:>7} {line}");
+            } else {
+                writeln!(io.out, "{line}");
+            }
+        }
+        ExitStatus::SUCCESS
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{
+    games::{snake, tetris},
+    shell::command::{Command, ExitStatus, Io}
+};
+use alloc::string::String;
+
+pub struct Snake;
+
+impl Command for Snake {
+    fn name(&self) -> &'static str {
+        "snake"
+    }
+
+    fn usage(&self) -> &'static str {
+        "snake [-a]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Plays Snake, or watches the autopilot play it with -a"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let autopilot = match args {
+            [] => false,
+            [flag] if flag == "-a" => true,
+            _ => {
+                writeln!(io.err, "Usage: {}", self.usage());
+                return ExitStatus::FAILURE;
+            }
+        };
+        if !io.require_screen(self.name()) {
+            return ExitStatus::FAILURE;
+        }
+        snake::play(autopilot, io.session.high_scores.entry(self.name()).or_default());
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Tetris;
+
+impl Command for Tetris {
+    fn name(&self) -> &'static str {
+        "tetris"
+    }
+
+    fn usage(&self) -> &'static str {
+        "tetris"
+    }
+
+    fn description(&self) -> &'static str {
+        "Plays Tetris"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        if !args.is_empty() {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        }
+        if !io.require_screen(self.name()) {
+            return ExitStatus::FAILURE;
+        }
+        tetris::play(io.session.high_scores.entry(self.name()).or_default());
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct HighScores;
+
+impl Command for HighScores {
+    fn name(&self) -> &'static str {
+        "highscores"
+    }
+
+    fn usage(&self) -> &'static str {
+        "highscores"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists the best score in each game played since logging in"
+    }
+
+    fn run(&self, _args: &[String], io: &mut Io) -> ExitStatus {
+        if io.session.high_scores.is_empty() {
+            writeln!(io.out, "No games played yet. Try snake or tetris.");
+            return ExitStatus::SUCCESS;
+        }
+        let width = io.session.high_scores.keys().map(|name| name.len()).max().unwrap_or(0);
+        for (name, score) in &io.session.high_scores {
+            writeln!(io.out, "{name:<width$}  {score}");
+        }
+        ExitStatus::SUCCESS
+    }
+}
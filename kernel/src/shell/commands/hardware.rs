@@ -0,0 +1,225 @@
+use crate::{
+    allocator,
+    interrupts::{self, VECTORS},
+    memory,
+    shell::command::{Command, ExitStatus, Io},
+    sysinfo::{self, format_bytes}
+};
+use alloc::{format, string::String, vec::Vec};
+use bootloader_api::info::MemoryRegionKind;
+
+/// Says that what the machine was like at boot isn't known, for commands that need it.
+fn no_machine(name: &str, io: &mut Io) -> ExitStatus {
+    writeln!(io.err, "{name}: nothing was recorded about the machine at boot");
+    ExitStatus::FAILURE
+}
+
+pub struct Lscpu;
+
+impl Command for Lscpu {
+    fn name(&self) -> &'static str {
+        "lscpu"
+    }
+
+    fn usage(&self) -> &'static str {
+        "lscpu"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows what the CPU is, and what it can do, as cpuid tells it"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        if !args.is_empty() {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        }
+        let cpu = sysinfo::cpu();
+        writeln!(io.out, "Vendor:         {}", cpu.vendor);
+        if let Some(brand) = &cpu.brand {
+            writeln!(io.out, "Model name:     {brand}");
+        }
+        writeln!(io.out, "Family:         {}", cpu.family);
+        writeln!(io.out, "Model:          {}", cpu.model);
+        writeln!(io.out, "Stepping:       {}", cpu.stepping);
+        if let Some((physical, virtual_bits)) = cpu.address_bits {
+            writeln!(io.out, "Address sizes:  {physical} bits physical, {virtual_bits} bits virtual");
+        }
+        if let Some(hypervisor) = &cpu.hypervisor {
+            writeln!(io.out, "Hypervisor:     {hypervisor}");
+        }
+        if let Some(machine) = sysinfo::machine() {
+            let ids: Vec<String> = machine.apic_ids.iter().map(|id| format!("{id}")).collect();
+            writeln!(io.out, "CPUs:           {}, with APIC IDs {}", machine.processors, ids.join(", "));
+        }
+        writeln!(io.out, "Running on:     APIC ID {}", cpu.apic_id);
+        writeln!(io.out, "Features:       {}", cpu.features.join(" "));
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Meminfo;
+
+impl Command for Meminfo {
+    fn name(&self) -> &'static str {
+        "meminfo"
+    }
+
+    fn usage(&self) -> &'static str {
+        "meminfo [-v]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows the memory map by kind, the frames handed out, and the heap; -v lists every region"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let verbose = match args {
+            [] => false,
+            [flag] if flag == "-v" => true,
+            _ => {
+                writeln!(io.err, "Usage: {}", self.usage());
+                return ExitStatus::FAILURE;
+            }
+        };
+        let Some(machine) = sysinfo::machine() else {
+            return no_machine(self.name(), io);
+        };
+
+        // Each kind of region, in the order they first come up, with how many there are and their
+        // total size.
+        let mut kinds: Vec<(String, usize, u64)> = Vec::new();
+        for region in &machine.memory_regions {
+            let name = sysinfo::region_kind_name(region.kind);
+            let len = region.end - region.start;
+            match kinds.iter_mut().find(|(kind, _, _)| *kind == name) {
+                Some((_, count, size)) => {
+                    *count += 1;
+                    *size += len;
+                }
+                None => kinds.push((name, 1, len)),
+            }
+        }
+        let width = kinds.iter().map(|(name, _, _)| name.len()).max().unwrap_or(0).max(4);
+        writeln!(io.out, "{:<width$}  Regions  Size", "Kind");
+        for (name, count, size) in &kinds {
+            writeln!(io.out, "{name:<width$}  {count:>7}  {}", format_bytes(*size));
+        }
+        if verbose {
+            writeln!(io.out);
+            for region in &machine.memory_regions {
+                writeln!(
+                    io.out,
+                    "{:#014x}-{:#014x}  {:>10}  {}",
+                    region.start,
+                    region.end,
+                    format_bytes(region.end - region.start),
+                    sysinfo::region_kind_name(region.kind)
+                );
+            }
+        }
+
+        writeln!(io.out);
+        writeln!(io.out, "RAM:     {} usable of {}", format_bytes(machine.usable_memory), format_bytes(machine.total_memory));
+        let frames: u64 = machine
+            .memory_regions
+            .iter()
+            .filter(|region| region.kind == MemoryRegionKind::Usable)
+            .map(|region| (region.end - region.start).div_ceil(4096))
+            .sum();
+        let allocated = memory::frames_allocated();
+        writeln!(
+            io.out,
+            "Frames:  {allocated} of {frames} usable 4 KiB frames allocated, {} free",
+            frames.saturating_sub(allocated)
+        );
+        let used = allocator::heap_used();
+        let size = allocator::HEAP_SIZE;
+        writeln!(
+            io.out,
+            "Heap:    {} used of {} at {:#x}, {} free ({}%)",
+            format_bytes(used as u64),
+            format_bytes(size as u64),
+            allocator::HEAP_START,
+            format_bytes((size - used) as u64),
+            (size - used) * 100 / size
+        );
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Lsirq;
+
+impl Command for Lsirq {
+    fn name(&self) -> &'static str {
+        "lsirq"
+    }
+
+    fn usage(&self) -> &'static str {
+        "lsirq"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists the interrupt vectors the kernel handles, and how often each has come in"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        if !args.is_empty() {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        }
+        let width = VECTORS.iter().map(|vector| vector.name.len()).max().unwrap_or(0);
+        writeln!(io.out, "Vector  {:>10}  {:<width$}  Handler", "Count", "Name");
+        for vector in &VECTORS {
+            writeln!(
+                io.out,
+                "{:#04x}    {:>10}  {:<width$}  {}",
+                vector.number,
+                interrupts::interrupt_count(vector.number),
+                vector.name,
+                vector.handler
+            );
+        }
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Lsacpi;
+
+impl Command for Lsacpi {
+    fn name(&self) -> &'static str {
+        "lsacpi"
+    }
+
+    fn usage(&self) -> &'static str {
+        "lsacpi"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists the ACPI tables the firmware provides, from their headers"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        if !args.is_empty() {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        }
+        let Some(machine) = sysinfo::machine() else {
+            return no_machine(self.name(), io);
+        };
+        writeln!(io.out, "Table  Revision  OEM ID  OEM table  OEM revision  Length");
+        for table in &machine.acpi_tables {
+            writeln!(
+                io.out,
+                "{:<5}  {:>8}  {:<6}  {:<9}  {:>12}  {}",
+                table.signature,
+                table.revision,
+                table.oem_id,
+                table.oem_table_id,
+                table.oem_revision,
+                table.length
+            );
+        }
+        ExitStatus::SUCCESS
+    }
+}
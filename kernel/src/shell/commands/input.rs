@@ -0,0 +1,58 @@
+use crate::{
+    input::{self, EventKind, Kinds},
+    shell::command::{Command, ExitStatus, Io}
+};
+use alloc::string::String;
+use pc_keyboard::DecodedKey;
+
+/// The kinds of events `evtest` can be asked to show, by the names it knows them by.
+const KINDS: [(&str, Kinds); 4] = [
+    ("keys", Kinds::KEYS),
+    ("changes", Kinds::KEY_CHANGES),
+    ("mouse", Kinds::MOUSE),
+    ("serial", Kinds::SERIAL),
+];
+
+pub struct EvTest;
+
+impl Command for EvTest {
+    fn name(&self) -> &'static str {
+        "evtest"
+    }
+
+    fn usage(&self) -> &'static str {
+        "evtest [keys] [changes] [mouse] [serial]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows input events from the keyboard, mouse and serial port as they come, until Ctrl+C"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let mut shown = if args.is_empty() { Some(Kinds::ALL) } else { None };
+        for arg in args {
+            let Some(&(_, kinds)) = KINDS.iter().find(|(name, _)| name == arg) else {
+                writeln!(io.err, "Usage: {}", self.usage());
+                return ExitStatus::FAILURE;
+            };
+            shown = Some(shown.map_or(kinds, |shown| shown | kinds));
+        }
+        let shown = shown.unwrap_or(Kinds::ALL);
+
+        // Keys are always read, for Ctrl+C to stop, even when they aren't shown.
+        let input = input::focus(shown | Kinds::KEYS);
+        writeln!(io.out, "Waiting for input events. Ctrl+C to stop.");
+        loop {
+            let event = input.read_event();
+            if let EventKind::Key(press) = event.kind {
+                if press.key == DecodedKey::Unicode('\x03') {
+                    return ExitStatus::SUCCESS;
+                }
+            }
+            if shown.contains(event.kind.kinds()) {
+                let time = event.time;
+                writeln!(io.out, "[{:>5}.{:03}] {}", time.as_secs(), time.subsec_millis(), event.kind);
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{
+    keyboard::{self, i8042, LAYOUTS},
+    shell::{
+        command::{Command, ExitStatus, Io},
+        session::KEYMAP
+    }
+};
+use alloc::string::String;
+
+pub struct LoadKeys;
+
+impl Command for LoadKeys {
+    fn name(&self) -> &'static str {
+        "loadkeys"
+    }
+
+    fn usage(&self) -> &'static str {
+        "loadkeys [layout]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Switches the keyboard layout, or lists the layouts there are"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        match args {
+            [] => {
+                let current = keyboard::layout();
+                for (name, description) in LAYOUTS {
+                    let marker = if name == current { '*' } else { ' ' };
+                    writeln!(io.out, "{marker} {name:<8} {description}");
+                }
+                ExitStatus::SUCCESS
+            }
+            [layout] => {
+                if keyboard::set_layout(layout) {
+                    io.session.environment.set(KEYMAP, layout);
+                    ExitStatus::SUCCESS
+                } else {
+                    writeln!(io.err, "loadkeys: there is no layout called {layout}; run loadkeys to list them");
+                    ExitStatus::FAILURE
+                }
+            }
+            _ => {
+                writeln!(io.err, "Usage: {}", self.usage());
+                ExitStatus::FAILURE
+            }
+        }
+    }
+}
+
+pub struct KbdRate;
+
+impl KbdRate {
+    /// Parses a rate in characters a second, like `10.9`, into tenths of one.
+    fn parse_rate(text: &str) -> Option<u16> {
+        let rate: f32 = text.parse().ok()?;
+        let tenths = rate * 10.0 + 0.5;
+        (f32::from(i8042::RATES[31])..=f32::from(i8042::RATES[0]) + 0.5).contains(&tenths).then_some(tenths as u16)
+    }
+}
+
+impl Command for KbdRate {
+    fn name(&self) -> &'static str {
+        "kbdrate"
+    }
+
+    fn usage(&self) -> &'static str {
+        "kbdrate [-r rate] [-d delay]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Sets how fast held keys repeat, and how long until they start"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        if i8042::controller().is_none() {
+            writeln!(io.err, "kbdrate: the keyboard controller couldn't be set up, so its rate can't be changed");
+            return ExitStatus::FAILURE;
+        }
+        let (mut rate, mut delay) = i8042::typematic();
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let Some(value) = args.next() else {
+                writeln!(io.err, "Usage: {}", self.usage());
+                return ExitStatus::FAILURE;
+            };
+            match flag.as_str() {
+                "-r" => match Self::parse_rate(value) {
+                    Some(tenths) => rate = tenths,
+                    None => {
+                        writeln!(io.err, "kbdrate: the rate must be from 2 to 30 characters a second, not {value}");
+                        return ExitStatus::FAILURE;
+                    }
+                },
+                "-d" => match value.parse() {
+                    Ok(milliseconds @ 250..=1000) => delay = milliseconds,
+                    _ => {
+                        writeln!(io.err, "kbdrate: the delay must be from 250 to 1000 ms, not {value}");
+                        return ExitStatus::FAILURE;
+                    }
+                },
+                _ => {
+                    writeln!(io.err, "Usage: {}", self.usage());
+                    return ExitStatus::FAILURE;
+                }
+            }
+        }
+
+        i8042::set_typematic(rate, delay);
+        let (rate, delay) = i8042::typematic();
+        writeln!(io.out, "Typematic rate {}.{} cps, after {delay} ms", rate / 10, rate % 10);
+        ExitStatus::SUCCESS
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{
+    content,
+    framebuffer,
+    shell::{
+        command::{Command, ExitStatus, Io},
+        completion::ArgKind,
+        markdown,
+        pager,
+        registry
+    }
+};
+use alloc::{
+    format,
+    string::String,
+    vec::Vec
+};
+
+pub struct Man;
+
+impl Command for Man {
+    fn name(&self) -> &'static str {
+        "man"
+    }
+
+    fn usage(&self) -> &'static str {
+        "man <command>"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows the manual page for a command"
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Command
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let [name] = args else {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        };
+        // Look the command up first, so its aliases lead to its page too.
+        let command = registry::find(name);
+        let page_name = command.as_ref().map_or(name.as_str(), |command| command.name());
+        let text = match (content::man_page(page_name), command) {
+            (Some(page), _) => String::from(page.text),
+            // Commands without a page of their own, like the portfolio sections, get one made
+            // from what they say about themselves.
+            (None, Some(command)) => generated_page(command.as_ref()),
+            (None, None) => {
+                writeln!(io.err, "man: no manual entry for {name}");
+                return ExitStatus::FAILURE;
+            }
+        };
+        let rendered = markdown::render(&text, framebuffer::columns());
+        pager::show(&rendered, &format!("man {page_name}"), io.out);
+        ExitStatus::SUCCESS
+    }
+}
+
+/// Writes a manual page for a command that has none, from its usage and description.
+fn generated_page(command: &dyn Command) -> String {
+    let mut page = format!(
+        "# {} - {}\n\n## SYNOPSIS\n\n~~~\n{}\n~~~\n\n## DESCRIPTION\n\n{}.\n",
+        command.name(),
+        command.description(),
+        command.usage(),
+        command.description()
+    );
+    if !command.aliases().is_empty() {
+        page.push_str(&format!("\n## ALIASES\n\n{}\n", command.aliases().join(", ")));
+    }
+    page
+}
+
+pub struct Apropos;
+
+impl Command for Apropos {
+    fn name(&self) -> &'static str {
+        "apropos"
+    }
+
+    fn usage(&self) -> &'static str {
+        "apropos <keyword...>"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists the commands whose name or manual page summary mentions any of the keywords"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        if args.is_empty() {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        }
+        let keywords: Vec<String> = args.iter().map(|keyword| keyword.to_lowercase()).collect();
+
+        let mut found = Vec::new();
+        for command in registry::all() {
+            let summary = content::man_page(command.name()).map_or(command.description(), |page| page.summary);
+            let name = command.name().to_lowercase();
+            let lowercase = summary.to_lowercase();
+            if keywords.iter().any(|keyword| name.contains(keyword.as_str()) || lowercase.contains(keyword.as_str())) {
+                found.push((command.name(), summary));
+            }
+        }
+        if found.is_empty() {
+            writeln!(io.err, "apropos: nothing appropriate");
+            return ExitStatus::FAILURE;
+        }
+        let width = found.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        for (name, summary) in found {
+            writeln!(io.out, "{name:<width$} - {summary}");
+        }
+        ExitStatus::SUCCESS
+    }
+}
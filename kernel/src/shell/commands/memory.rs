@@ -0,0 +1,269 @@
+use crate::{
+    memory::inspect::{self, parse_number, AccessError},
+    shell::command::{Command, ExitStatus, Io}
+};
+use alloc::{format, string::String, vec::Vec};
+use x86_64::VirtAddr;
+
+/// The most `hexdump` shows at once.
+const MAX_DUMP: usize = 64 * 1024;
+
+/// Takes the `-p` flag off the front of `args`.
+///
+/// ### returns:
+/// - whether it was there, and the rest of the arguments.
+fn physical_flag(args: &[String]) -> (bool, &[String]) {
+    match args.split_first() {
+        Some((flag, rest)) if flag == "-p" => (true, rest),
+        _ => (false, args),
+    }
+}
+
+/// Parses the address argument of a command, and turns it into the virtual address to access,
+/// saying what is wrong with it if that can't be done.
+fn address(name: &str, text: &str, physical: bool, io: &mut Io) -> Option<VirtAddr> {
+    let Some(address) = parse_number(text) else {
+        writeln!(io.err, "{name}: {text} is not a number");
+        return None;
+    };
+    inspect::resolve(address, physical).map_err(|error| report(name, error, io)).ok()
+}
+
+/// Parses an access size: 1, 2, 4 or 8 bytes, or 8 if it isn't given.
+fn size(name: &str, text: Option<&String>, io: &mut Io) -> Option<usize> {
+    let Some(text) = text else {
+        return Some(8);
+    };
+    match text.parse() {
+        Ok(size @ (1 | 2 | 4 | 8)) => Some(size),
+        _ => {
+            writeln!(io.err, "{name}: the size must be 1, 2, 4 or 8 bytes, not {text}");
+            None
+        }
+    }
+}
+
+fn report(name: &str, error: AccessError, io: &mut Io) {
+    writeln!(io.err, "{name}: {error}");
+}
+
+/// Checks that root is running a command that can see or change any memory.
+fn is_root(name: &str, io: &mut Io) -> bool {
+    if io.session.is_root() {
+        return true;
+    }
+    writeln!(io.err, "{name}: only root can look at kernel memory; try su first");
+    false
+}
+
+pub struct Peek;
+
+impl Command for Peek {
+    fn name(&self) -> &'static str {
+        "peek"
+    }
+
+    fn usage(&self) -> &'static str {
+        "peek [-p] <address> [size]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Reads 1, 2, 4 or 8 bytes of memory, at a virtual address or a physical one with -p"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let (physical, args) = physical_flag(args);
+        if !(1..=2).contains(&args.len()) {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        }
+        if !is_root(self.name(), io) {
+            return ExitStatus::FAILURE;
+        }
+        let (Some(address), Some(size)) = (address(self.name(), &args[0], physical, io), size(self.name(), args.get(1), io))
+        else {
+            return ExitStatus::FAILURE;
+        };
+        match inspect::peek(address, size) {
+            Ok(value) => {
+                writeln!(io.out, "{:#018x}: {:#0width$x} ({value})", address.as_u64(), value, width = size * 2 + 2);
+                ExitStatus::SUCCESS
+            }
+            Err(error) => {
+                report(self.name(), error, io);
+                ExitStatus::FAILURE
+            }
+        }
+    }
+}
+
+pub struct Poke;
+
+impl Command for Poke {
+    fn name(&self) -> &'static str {
+        "poke"
+    }
+
+    fn usage(&self) -> &'static str {
+        "poke [-p] <address> <value> [size]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Writes 1, 2, 4 or 8 bytes of memory, at a virtual address or a physical one with -p"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let (physical, args) = physical_flag(args);
+        if !(2..=3).contains(&args.len()) {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        }
+        if !is_root(self.name(), io) {
+            return ExitStatus::FAILURE;
+        }
+        let (Some(address), Some(size)) = (address(self.name(), &args[0], physical, io), size(self.name(), args.get(2), io))
+        else {
+            return ExitStatus::FAILURE;
+        };
+        let Some(value) = parse_number(&args[1]) else {
+            writeln!(io.err, "poke: {} is not a number", args[1]);
+            return ExitStatus::FAILURE;
+        };
+        if size < 8 && value >> (size * 8) != 0 {
+            writeln!(io.err, "poke: {value:#x} doesn't fit in {size} byte{}", if size == 1 { "" } else { "s" });
+            return ExitStatus::FAILURE;
+        }
+        // Root asked for exactly this write.
+        match unsafe { inspect::poke(address, value, size) } {
+            Ok(()) => ExitStatus::SUCCESS,
+            Err(error) => {
+                report(self.name(), error, io);
+                ExitStatus::FAILURE
+            }
+        }
+    }
+}
+
+pub struct Hexdump;
+
+impl Command for Hexdump {
+    fn name(&self) -> &'static str {
+        "hexdump"
+    }
+
+    fn usage(&self) -> &'static str {
+        "hexdump [-p] <address> <length>"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows memory as hex and ASCII, at a virtual address or a physical one with -p"
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let (physical, args) = physical_flag(args);
+        let [address_arg, length] = args else {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        };
+        if !is_root(self.name(), io) {
+            return ExitStatus::FAILURE;
+        }
+        let Some(address) = address(self.name(), address_arg, physical, io) else {
+            return ExitStatus::FAILURE;
+        };
+        let length = match parse_number(length) {
+            Some(length) if length as usize <= MAX_DUMP => length as usize,
+            Some(_) => {
+                writeln!(io.err, "hexdump: at most {MAX_DUMP} bytes can be shown at once");
+                return ExitStatus::FAILURE;
+            }
+            None => {
+                writeln!(io.err, "hexdump: {length} is not a number");
+                return ExitStatus::FAILURE;
+            }
+        };
+        let bytes = match inspect::read(address, length) {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                report(self.name(), error, io);
+                return ExitStatus::FAILURE;
+            }
+        };
+
+        for (line, chunk) in bytes.chunks(16).enumerate() {
+            writeln!(io.out, "{}", inspect::dump_line(address.as_u64() + line as u64 * 16, chunk));
+        }
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct PageWalk;
+
+impl Command for PageWalk {
+    fn name(&self) -> &'static str {
+        "pagewalk"
+    }
+
+    fn usage(&self) -> &'static str {
+        "pagewalk <address>"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows each page table entry on the way to a virtual address, and where it leads"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let [address_arg] = args else {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        };
+        let Some(address) = address(self.name(), address_arg, false, io) else {
+            return ExitStatus::FAILURE;
+        };
+
+        let walk = inspect::walk(address);
+        if walk.steps.is_empty() {
+            report(self.name(), AccessError::NoPhysicalMapping, io);
+            return ExitStatus::FAILURE;
+        }
+        writeln!(io.out, "Virtual address {:#x}", address.as_u64());
+        for step in &walk.steps {
+            let mut flags: Vec<&str> = step.flags().iter_names().map(|(name, _)| name).collect();
+            if flags.is_empty() {
+                flags.push("(none)");
+            }
+            let table = format!("{}[{}]", step.table_name(), step.index);
+            writeln!(
+                io.out,
+                "  {table:<10} in {:#012x}: {:#018x}  {}",
+                step.table.as_u64(),
+                step.entry,
+                flags.join(" ")
+            );
+        }
+        let Some(mapping) = walk.mapping else {
+            let last = walk.steps[walk.steps.len() - 1];
+            writeln!(io.out, "Not mapped: the {} entry is not present", last.table_name());
+            return ExitStatus::FAILURE;
+        };
+        let size = match mapping.page_size {
+            0x1000 => "4 KiB",
+            0x20_0000 => "2 MiB",
+            _ => "1 GiB",
+        };
+        let mut access = Vec::from(["read"]);
+        if mapping.writable {
+            access.push("write");
+        }
+        if mapping.executable {
+            access.push("execute");
+        }
+        let mode = if mapping.user { "user and kernel" } else { "kernel only" };
+        writeln!(io.out, "Physical address {:#x}, in a {size} page: {}, {mode}", mapping.physical.as_u64(), access.join("/"));
+        ExitStatus::SUCCESS
+    }
+}
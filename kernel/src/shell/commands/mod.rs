@@ -0,0 +1,126 @@
+//! The commands that ship with the shell.
+mod alias;
+mod builtin;
+mod calc;
+mod debugger;
+mod env;
+mod files;
+mod filters;
+mod games;
+mod hardware;
+mod input;
+mod keyboard;
+mod man;
+mod memory;
+pub(crate) mod names;
+mod portfolio;
+mod script;
+mod test;
+mod users;
+
+use crate::{content, fs, serial_println, shell::command::{Command, Io}};
+use alloc::{string::String, sync::Arc, vec, vec::Vec};
+
+/// Returns the commands every shell starts out with: the builtins, and a command for each
+/// portfolio [section](crate::content::section).
+pub(crate) fn builtins() -> Vec<Arc<dyn Command>> {
+    let mut commands: Vec<Arc<dyn Command>> = vec![
+        Arc::new(builtin::Help),
+        Arc::new(builtin::Clear),
+        Arc::new(builtin::Echo),
+        Arc::new(builtin::History),
+        Arc::new(man::Man),
+        Arc::new(man::Apropos),
+        Arc::new(calc::Calc),
+        Arc::new(env::Set),
+        Arc::new(env::Unset),
+        Arc::new(env::Export),
+        Arc::new(env::Env),
+        Arc::new(alias::Alias),
+        Arc::new(alias::Unalias),
+        Arc::new(users::WhoAmI),
+        Arc::new(users::Su),
+        Arc::new(users::Passwd),
+        Arc::new(users::UserAdd),
+        Arc::new(users::Logout),
+        Arc::new(files::Cat),
+        Arc::new(files::Ls),
+        Arc::new(files::Md),
+        Arc::new(files::Less),
+        Arc::new(files::Edit),
+        Arc::new(filters::Grep),
+        Arc::new(filters::Wc),
+        Arc::new(filters::Head),
+        Arc::new(filters::Tail),
+        Arc::new(filters::Sort),
+        Arc::new(filters::Uniq),
+        Arc::new(memory::Peek),
+        Arc::new(memory::Poke),
+        Arc::new(memory::Hexdump),
+        Arc::new(memory::PageWalk),
+        Arc::new(debugger::Kdb),
+        Arc::new(hardware::Lscpu),
+        Arc::new(hardware::Meminfo),
+        Arc::new(hardware::Lsirq),
+        Arc::new(hardware::Lsacpi),
+        Arc::new(keyboard::LoadKeys),
+        Arc::new(keyboard::KbdRate),
+        Arc::new(input::EvTest),
+        Arc::new(games::Snake),
+        Arc::new(games::Tetris),
+        Arc::new(games::HighScores),
+        Arc::new(script::Run),
+        Arc::new(test::True),
+        Arc::new(test::False),
+        Arc::new(test::Test),
+        Arc::new(portfolio::Exit),
+        Arc::new(portfolio::Bye),
+    ];
+    for command in &commands {
+        check_listed(command.as_ref());
+    }
+    // The build checks this too, so it only happens with an initramfs from another build.
+    for section in content::sections() {
+        let mut names = core::iter::once(&section.name).chain(&section.aliases);
+        if let Some(name) = names.find(|name| commands.iter().any(|command| command.is_called(name))) {
+            serial_println!("portfolio section `{}` uses `{}`, which a builtin has, skipping it", section.name, name);
+            continue;
+        }
+        commands.push(Arc::new(portfolio::SectionCommand(section)));
+    }
+    commands
+}
+
+/// Stops the kernel if `command` isn't in [names::BUILTINS] as it is, since the build checks the
+/// portfolio sections against that list and would miss it.
+pub(crate) fn check_listed(command: &dyn Command) {
+    assert!(
+        names::is_listed(command.name(), command.aliases()),
+        "the builtin `{}` is missing from shell/commands/names.rs, or has other aliases there",
+        command.name()
+    );
+}
+
+/// Gathers a command's input: the files in `paths`, one after the other, or what was piped or
+/// redirected into it if there are none.
+///
+/// ### returns:
+/// - `Some(String)`: the input.
+/// - `None`: if one of the files does not exist, which has been reported under `name`.
+fn read_input(name: &str, paths: &[String], io: &mut Io) -> Option<String> {
+    if paths.is_empty() {
+        return Some(io.input.take().unwrap_or_default());
+    }
+    let mut text = String::new();
+    for path in paths {
+        let path = fs::normalize(path);
+        match fs::with_store(|store| store.read_to_string(&path)).flatten() {
+            Some(contents) => text.push_str(&contents),
+            None => {
+                writeln!(io.err, "{name}: {path}: No such file");
+                return None;
+            }
+        }
+    }
+    Some(text)
+}
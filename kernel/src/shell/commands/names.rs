@@ -0,0 +1,66 @@
+//! The names and aliases of the builtin commands.
+//!
+//! The build script shares this file, to check that no portfolio section takes a builtin's name,
+//! so the list has to be kept in step with the commands. The kernel checks that it is when the
+//! commands are registered, and stops if a command is missing from it.
+
+/// Every builtin command, by its name and aliases, including the ones registered from outside
+/// the shell, like `shutdown`.
+pub const BUILTINS: &[(&str, &[&str])] = &[
+    ("help", &[]),
+    ("clear", &[]),
+    ("echo", &[]),
+    ("history", &[]),
+    ("man", &[]),
+    ("apropos", &[]),
+    ("calc", &[]),
+    ("set", &[]),
+    ("unset", &[]),
+    ("export", &[]),
+    ("env", &[]),
+    ("alias", &[]),
+    ("unalias", &[]),
+    ("whoami", &[]),
+    ("su", &[]),
+    ("passwd", &[]),
+    ("useradd", &[]),
+    ("logout", &[]),
+    ("cat", &[]),
+    ("ls", &[]),
+    ("md", &[]),
+    ("less", &[]),
+    ("edit", &[]),
+    ("grep", &[]),
+    ("wc", &[]),
+    ("head", &[]),
+    ("tail", &[]),
+    ("sort", &[]),
+    ("uniq", &[]),
+    ("peek", &[]),
+    ("poke", &[]),
+    ("hexdump", &[]),
+    ("pagewalk", &[]),
+    ("kdb", &[]),
+    ("lscpu", &[]),
+    ("meminfo", &[]),
+    ("lsirq", &[]),
+    ("lsacpi", &[]),
+    ("loadkeys", &[]),
+    ("kbdrate", &[]),
+    ("evtest", &[]),
+    ("snake", &[]),
+    ("tetris", &[]),
+    ("highscores", &[]),
+    ("run", &[]),
+    ("true", &[]),
+    ("false", &[]),
+    ("test", &[]),
+    ("exit", &["execute66"]),
+    ("bye", &[]),
+    ("shutdown", &[]),
+];
+
+/// Returns true if [BUILTINS] lists a command called `name` with exactly these `aliases`.
+pub fn is_listed(name: &str, aliases: &[&str]) -> bool {
+    BUILTINS.iter().any(|(listed, listed_aliases)| *listed == name && *listed_aliases == aliases)
+}
@@ -0,0 +1,163 @@
+use crate::{
+    content::section::{Line, Section},
+    framebuffer::{self, color::ColoredWriting, ConsoleColor},
+    shell::{
+        command::{Command, ExitStatus, Io},
+        markdown
+    },
+    sysinfo
+};
+use alloc::string::{String, ToString};
+
+/// Spins for roughly `iterations` iterations, for dramatic effect.
+fn pause(iterations: u128) {
+    for _ in 0..iterations {
+        core::hint::spin_loop();
+    }
+}
+
+/// Replaces the [variables](crate::content::section::VARIABLES) in `text` with their values, as
+/// given by `value`. Anything in braces it has no value for is left as it is.
+fn substitute(text: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        match rest.find('}').and_then(|end| Some((value(&rest[..end])?, end))) {
+            Some((replacement, end)) => {
+                result.push_str(&replacement);
+                rest = &rest[end + 1..];
+            }
+            None => result.push('{'),
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// A portfolio [section](crate::content::section), as a command that prints it.
+pub struct SectionCommand(pub &'static Section<'static>);
+
+impl Command for SectionCommand {
+    fn name(&self) -> &'static str {
+        self.0.name
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &self.0.aliases
+    }
+
+    fn usage(&self) -> &'static str {
+        self.0.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.0.description
+    }
+
+    fn pages_output(&self) -> bool {
+        true
+    }
+
+    fn run(&self, _args: &[String], io: &mut Io) -> ExitStatus {
+        let mut color = None;
+        // A markdown section's text is gathered up to the next pause, and rendered all at once.
+        let mut source = String::new();
+        for line in &self.0.body {
+            match *line {
+                Line::Text(text) => {
+                    let text = substitute(text, |name| match name {
+                        "user" => Some(io.session.name.clone()),
+                        name => sysinfo::variable(name),
+                    });
+                    let text = text.replace("\\e", "\x1b");
+                    if self.0.markdown {
+                        source.push_str(&text);
+                        source.push('\n');
+                        continue;
+                    }
+                    match color {
+                        Some(color) => writeln!(io.out, "{}", text.fg(color)),
+                        None => writeln!(io.out, "{text}"),
+                    }
+                }
+                Line::Color(name) => color = name.and_then(ConsoleColor::from_name),
+                Line::Pause(iterations) => {
+                    self.render_markdown(&mut source, io);
+                    pause(iterations.into());
+                }
+            }
+        }
+        self.render_markdown(&mut source, io);
+        ExitStatus::SUCCESS
+    }
+}
+
+impl SectionCommand {
+    /// Prints the Markdown gathered in `source`, if there is any, and empties it.
+    fn render_markdown(&self, source: &mut String, io: &mut Io) {
+        if !source.trim().is_empty() {
+            io.out.write_str(&markdown::render(source, framebuffer::columns()));
+        }
+        source.clear();
+    }
+}
+
+pub struct Exit;
+
+impl Command for Exit {
+    fn name(&self) -> &'static str {
+        "exit"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["execute66"]
+    }
+
+    fn usage(&self) -> &'static str {
+        "exit"
+    }
+
+    fn description(&self) -> &'static str {
+        "Does not exit"
+    }
+
+    fn run(&self, _args: &[String], io: &mut Io) -> ExitStatus {
+        let format = "War! The Republic is crumbling under attacks by the ruthless Sith Lord, Count Dooku. There are heroes on both sides. Evil is everywhere.
+In a stunning move, the fiendish droid leader, General Grievous, has swept into the Republic capital and kidnapped Chancellor Palpatine, leader of the Galactic Senate.
+As the Separatist Droid Army attempts to flee the besieged capital with their valuable hostage, two Jedi Knights lead a desperate mission to rescue the captive Chancellor...\n";
+
+        for char in format.chars() {
+            write!(io.out, "{}", char.to_string().fg(ConsoleColor::BrightYellow));
+            pause(100_000);
+        }
+        writeln!(io.out, "(c) Whoever made star wars episode 3...");
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Bye;
+
+impl Command for Bye {
+    fn name(&self) -> &'static str {
+        "bye"
+    }
+
+    fn usage(&self) -> &'static str {
+        "bye"
+    }
+
+    fn description(&self) -> &'static str {
+        "Says goodbye"
+    }
+
+    fn run(&self, _args: &[String], io: &mut Io) -> ExitStatus {
+        writeln!(io.out, "Bye {}! See you later!", io.session.name);
+        writeln!(io.out, "Exiting...");
+        pause(5e6 as u128);
+        writeln!(io.out, "Exiting... (c) Agamjot Singh Bindra");
+        writeln!(io.out, "Bye!");
+        ExitStatus::SUCCESS
+    }
+}
@@ -0,0 +1,41 @@
+use crate::{
+    fs,
+    shell::{
+        command::{Command, ExitStatus, Io},
+        completion::ArgKind,
+        script
+    }
+};
+use alloc::string::String;
+
+pub struct Run;
+
+impl Command for Run {
+    fn name(&self) -> &'static str {
+        "run"
+    }
+
+    fn usage(&self) -> &'static str {
+        "run <script> [args...]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Runs an ash script"
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::Path
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let Some((path, args)) = args.split_first() else {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        };
+        let Some(source) = fs::with_store(|store| store.read_to_string(&fs::normalize(path))).flatten() else {
+            writeln!(io.err, "run: {path}: No such file");
+            return ExitStatus::FAILURE;
+        };
+        script::run_file(io.session, path, &source, args, io.out)
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    fs,
+    shell::command::{Command, ExitStatus, Io}
+};
+use alloc::{format, string::String};
+
+pub struct True;
+
+impl Command for True {
+    fn name(&self) -> &'static str {
+        "true"
+    }
+
+    fn usage(&self) -> &'static str {
+        "true"
+    }
+
+    fn description(&self) -> &'static str {
+        "Does nothing, successfully"
+    }
+
+    fn run(&self, _args: &[String], _io: &mut Io) -> ExitStatus {
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct False;
+
+impl Command for False {
+    fn name(&self) -> &'static str {
+        "false"
+    }
+
+    fn usage(&self) -> &'static str {
+        "false"
+    }
+
+    fn description(&self) -> &'static str {
+        "Does nothing, unsuccessfully"
+    }
+
+    fn run(&self, _args: &[String], _io: &mut Io) -> ExitStatus {
+        ExitStatus::FAILURE
+    }
+}
+
+pub struct Test;
+
+impl Command for Test {
+    fn name(&self) -> &'static str {
+        "test"
+    }
+
+    fn usage(&self) -> &'static str {
+        "test [!] <expression>"
+    }
+
+    fn description(&self) -> &'static str {
+        "Checks a condition: -z/-n <text>, -e <file>, <a> =/!= <b>, or <a> -eq/-ne/-lt/-le/-gt/-ge <b>"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let (negate, args) = match args {
+            [bang, rest @ ..] if bang == "!" => (true, rest),
+            _ => (false, args),
+        };
+        match evaluate(args) {
+            Ok(result) => ExitStatus::from(result != negate),
+            Err(message) => {
+                writeln!(io.err, "test: {message}");
+                ExitStatus::WARNING
+            }
+        }
+    }
+}
+
+/// Evaluates a `test` expression.
+fn evaluate(args: &[String]) -> Result<bool, String> {
+    match args {
+        [] => Ok(false),
+        [text] => Ok(!text.is_empty()),
+        [flag, operand] => match flag.as_str() {
+            "-z" => Ok(operand.is_empty()),
+            "-n" => Ok(!operand.is_empty()),
+            "-e" | "-f" => Ok(fs::with_store(|store| store.exists(&fs::normalize(operand))).unwrap_or(false)),
+            _ => Err(format!("{flag}: unknown unary operator")),
+        },
+        [left, operator, right] => match operator.as_str() {
+            "=" | "==" => Ok(left == right),
+            "!=" => Ok(left != right),
+            "-eq" | "-ne" | "-lt" | "-le" | "-gt" | "-ge" => {
+                let left = integer(left)?;
+                let right = integer(right)?;
+                Ok(match operator.as_str() {
+                    "-eq" => left == right,
+                    "-ne" => left != right,
+                    "-lt" => left < right,
+                    "-le" => left <= right,
+                    "-gt" => left > right,
+                    _ => left >= right,
+                })
+            }
+            _ => Err(format!("{operator}: unknown binary operator")),
+        },
+        _ => Err(String::from("too many arguments")),
+    }
+}
+
+fn integer(text: &str) -> Result<i64, String> {
+    text.parse()
+        .map_err(|_| format!("{text}: integer expected"))
+}
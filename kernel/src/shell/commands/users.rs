@@ -0,0 +1,236 @@
+use crate::{
+    fs,
+    shell::{
+        command::{Command, ExitStatus, Io, Output},
+        completion::ArgKind,
+        login::{self, read_password}
+    },
+    users::{self, UserError, GUEST}
+};
+use alloc::string::String;
+
+/// Asks for a new password twice, to catch typos.
+///
+/// ### returns:
+/// - `Some(String)`: the new password.
+/// - `None`: if the two didn't match or it was empty, which has been reported to `out`.
+fn read_new_password(name: &str, out: &mut Output) -> Option<String> {
+    match login::read_new_password() {
+        Ok(password) => Some(password),
+        Err(message) => {
+            writeln!(out, "{name}: {message}");
+            None
+        }
+    }
+}
+
+pub struct WhoAmI;
+
+impl Command for WhoAmI {
+    fn name(&self) -> &'static str {
+        "whoami"
+    }
+
+    fn usage(&self) -> &'static str {
+        "whoami"
+    }
+
+    fn description(&self) -> &'static str {
+        "Prints the name of the user logged in"
+    }
+
+    fn run(&self, _args: &[String], io: &mut Io) -> ExitStatus {
+        writeln!(io.out, "{}", io.session.name);
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Su;
+
+impl Command for Su {
+    fn name(&self) -> &'static str {
+        "su"
+    }
+
+    fn usage(&self) -> &'static str {
+        "su [user]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Becomes another user, root by default, until logout"
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::User
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let name = match args {
+            [] => "root",
+            [name] => name.as_str(),
+            _ => {
+                writeln!(io.err, "Usage: {}", self.usage());
+                return ExitStatus::FAILURE;
+            }
+        };
+        let Some(user) = users::find(name) else {
+            writeln!(io.err, "su: user {name} does not exist");
+            return ExitStatus::FAILURE;
+        };
+        if !io.session.is_root() && user.has_password() && !user.check_password(&read_password("Password: ")) {
+            writeln!(io.err, "su: Authentication failure");
+            return ExitStatus::FAILURE;
+        }
+        if user.password_expired() && !io.session.is_root() {
+            writeln!(io.err, "su: {name}'s password is the default one, and must be changed first");
+            let Some(password) = read_new_password(self.name(), &mut io.err) else {
+                return ExitStatus::FAILURE;
+            };
+            _ = users::with_users(|users| users.set_password(name, Some(&password)));
+        }
+        io.session.su(&user);
+        ExitStatus::SUCCESS
+    }
+}
+
+pub struct Passwd;
+
+impl Command for Passwd {
+    fn name(&self) -> &'static str {
+        "passwd"
+    }
+
+    fn usage(&self) -> &'static str {
+        "passwd [user]"
+    }
+
+    fn description(&self) -> &'static str {
+        "Changes your password, or any user's as root"
+    }
+
+    fn operands(&self) -> ArgKind {
+        ArgKind::User
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let name = match args {
+            [] => io.session.name.clone(),
+            [name] => name.clone(),
+            _ => {
+                writeln!(io.err, "Usage: {}", self.usage());
+                return ExitStatus::FAILURE;
+            }
+        };
+        if name != io.session.name && !io.session.is_root() {
+            writeln!(io.err, "passwd: only root can change another user's password");
+            return ExitStatus::FAILURE;
+        }
+        let Some(user) = users::find(&name) else {
+            writeln!(io.err, "passwd: user {name} does not exist");
+            return ExitStatus::FAILURE;
+        };
+        if name == GUEST && !io.session.is_root() {
+            writeln!(io.err, "passwd: the {GUEST} account is shared, so its password can't be changed");
+            return ExitStatus::FAILURE;
+        }
+        if !io.session.is_root() && !user.check_password(&read_password("Current password: ")) {
+            writeln!(io.err, "passwd: Authentication failure");
+            return ExitStatus::FAILURE;
+        }
+
+        let Some(password) = read_new_password(self.name(), &mut io.err) else {
+            return ExitStatus::FAILURE;
+        };
+        match users::with_users(|users| users.set_password(&name, Some(&password))) {
+            Ok(()) => {
+                writeln!(io.out, "passwd: password updated for {name}");
+                ExitStatus::SUCCESS
+            }
+            Err(error) => {
+                writeln!(io.err, "passwd: {error}");
+                ExitStatus::FAILURE
+            }
+        }
+    }
+}
+
+pub struct UserAdd;
+
+impl Command for UserAdd {
+    fn name(&self) -> &'static str {
+        "useradd"
+    }
+
+    fn usage(&self) -> &'static str {
+        "useradd <name>"
+    }
+
+    fn description(&self) -> &'static str {
+        "Adds a user, as root"
+    }
+
+    fn run(&self, args: &[String], io: &mut Io) -> ExitStatus {
+        let [name] = args else {
+            writeln!(io.err, "Usage: {}", self.usage());
+            return ExitStatus::FAILURE;
+        };
+        if !io.session.is_root() {
+            writeln!(io.err, "useradd: only root can add users; try su first");
+            return ExitStatus::FAILURE;
+        }
+        // Check the name before asking for a password, rather than after it has been typed twice.
+        let problem = if !users::is_valid_name(name) {
+            Some(UserError::InvalidName)
+        } else if users::find(name).is_some() {
+            Some(UserError::Exists)
+        } else {
+            None
+        };
+        if let Some(error) = problem {
+            writeln!(io.err, "useradd: {name}: {error}");
+            return ExitStatus::FAILURE;
+        }
+
+        let Some(password) = read_new_password(self.name(), &mut io.err) else {
+            return ExitStatus::FAILURE;
+        };
+        match users::with_users(|users| users.add(name, Some(&password)).map(|user| (user.uid, user.home.clone()))) {
+            Ok((uid, home)) => {
+                // Anyone could write there before the home had an owner, so start it empty.
+                fs::with_store(|store| store.remove_tree(&home));
+                writeln!(io.out, "useradd: added {name} with uid {uid}");
+                ExitStatus::SUCCESS
+            }
+            Err(error) => {
+                writeln!(io.err, "useradd: {name}: {error}");
+                ExitStatus::FAILURE
+            }
+        }
+    }
+}
+
+pub struct Logout;
+
+impl Command for Logout {
+    fn name(&self) -> &'static str {
+        "logout"
+    }
+
+    fn usage(&self) -> &'static str {
+        "logout"
+    }
+
+    fn description(&self) -> &'static str {
+        "Logs out, or goes back to the user you were before su"
+    }
+
+    fn run(&self, _args: &[String], io: &mut Io) -> ExitStatus {
+        let name = io.session.name.clone();
+        if io.session.logout() {
+            writeln!(io.out, "logout: {name}, back to {}", io.session.name);
+        } else {
+            writeln!(io.out, "Bye {name}!");
+        }
+        ExitStatus::SUCCESS
+    }
+}
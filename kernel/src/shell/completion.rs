@@ -0,0 +1,151 @@
+use crate::{
+    framebuffer::ConsoleColor,
+    fs,
+    shell::{command::Command, registry},
+    users
+};
+use alloc::{
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec
+};
+
+/// The kind of value an argument takes, which decides what Tab offers for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    /// Free text, which cannot be completed.
+    Text,
+    /// A path in the file store.
+    Path,
+    /// The name of a console color.
+    Color,
+    /// The name of a command.
+    Command,
+    /// The name of a user.
+    User,
+}
+
+/// An option that takes a value, like the `-c <color>` in `echo -c red hi`.
+pub struct OptionSpec {
+    pub flag: &'static str,
+    pub value: ArgKind,
+}
+
+/// The outcome of pressing Tab.
+pub enum Completion {
+    /// Nothing matches the word being typed.
+    None,
+    /// Text to append to the line.
+    Insert(String),
+    /// Several candidates match, and they share nothing more than what is already typed.
+    Ambiguous(Vec<String>),
+}
+
+/// Completes the last word of `line`.
+///
+/// ### params:
+/// - `line`: the line being edited, with the cursor at its end.
+/// - `commands`: every command, whose metadata decides what kind of argument is being typed.
+pub fn complete(line: &str, commands: &[Arc<dyn Command>]) -> Completion {
+    let word = line.rsplit(char::is_whitespace).next().unwrap_or("");
+    let previous: Vec<&str> = line[..line.len() - word.len()].split_whitespace().collect();
+
+    let mut candidates: Vec<String> = match previous.first() {
+        None => command_names(commands, word),
+        Some(name) => {
+            let kind = commands
+                .iter()
+                .find(|command| command.is_called(name))
+                .map_or(ArgKind::Text, |command| arg_kind(command.as_ref(), previous.last().copied()));
+            candidates_for(kind, word)
+        }
+    };
+    candidates.sort();
+    candidates.dedup();
+
+    match candidates.as_slice() {
+        [] => Completion::None,
+        [only] => {
+            let mut text = only[word.len()..].to_string();
+            // Leave the cursor inside a "directory", so the next Tab can carry on from there.
+            if !only.ends_with('/') {
+                text.push(' ');
+            }
+            Completion::Insert(text)
+        }
+        _ => {
+            let common = common_prefix(&candidates);
+            if common.len() > word.len() {
+                Completion::Insert(common[word.len()..].to_string())
+            } else {
+                Completion::Ambiguous(candidates)
+            }
+        }
+    }
+}
+
+/// Works out what kind of argument follows `previous` on a `command` line.
+fn arg_kind(command: &dyn Command, previous: Option<&str>) -> ArgKind {
+    command
+        .options()
+        .iter()
+        .find(|option| Some(option.flag) == previous)
+        .map_or(command.operands(), |option| option.value)
+}
+
+/// Returns the name and aliases of every command that start with `word`.
+fn command_names(commands: &[Arc<dyn Command>], word: &str) -> Vec<String> {
+    commands
+        .iter()
+        .flat_map(|command| core::iter::once(command.name()).chain(command.aliases().iter().copied()))
+        .filter(|name| name.starts_with(word))
+        .map(String::from)
+        .collect()
+}
+
+/// Returns everything of the given kind that starts with `word`.
+fn candidates_for(kind: ArgKind, word: &str) -> Vec<String> {
+    match kind {
+        ArgKind::Text => Vec::new(),
+        ArgKind::Color => ConsoleColor::ALL
+            .iter()
+            .map(ConsoleColor::name)
+            .filter(|name| name.starts_with(word))
+            .map(String::from)
+            .collect(),
+        ArgKind::Command => command_names(&registry::all(), word),
+        ArgKind::User => users::with_users(|users| {
+            users
+                .iter()
+                .filter(|user| user.name.starts_with(word))
+                .map(|user| user.name.clone())
+                .collect()
+        }),
+        ArgKind::Path => fs::with_store(|store| {
+            store
+                .paths()
+                .filter(|path| path.starts_with(word))
+                // Only complete up to the end of the next path component.
+                .map(|path| match path[word.len()..].find('/') {
+                    Some(slash) => path[..word.len() + slash + 1].to_string(),
+                    None => path.to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default(),
+    }
+}
+
+/// Returns the longest prefix shared by every candidate.
+fn common_prefix(candidates: &[String]) -> &str {
+    let first = &candidates[0];
+    let mut len = first.len();
+    for candidate in &candidates[1..] {
+        len = first
+            .char_indices()
+            .zip(candidate.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(candidate.len()), |((index, _), _)| index.min(len));
+    }
+    &first[..len]
+}
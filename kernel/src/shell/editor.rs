@@ -0,0 +1,522 @@
+//! A full-screen text editor, like nano, for `edit`.
+//!
+//! The file takes up the screen but for its last two rows: a status line saying where the cursor
+//! is, and a list of the keys. Lines wider than the screen aren't wrapped; the view scrolls
+//! sideways to follow the cursor instead.
+//!
+//! Only what changed is drawn again after a key: the line being typed on, or everything if lines
+//! were added, removed or scrolled. The console's cursor, moved with
+//! [set_cursor_position](crate::framebuffer::writer::FrameBufferWriter::set_cursor_position), is the
+//! editor's cursor.
+use crate::{
+    framebuffer::{self, global_writer::with_writer},
+    fs,
+    input::{self, Focus, Kinds},
+    shell::{command::Output, login, pager}
+};
+use alloc::{
+    format,
+    string::String,
+    vec::Vec
+};
+use core::fmt::Write;
+use pc_keyboard::{DecodedKey, KeyCode};
+
+/// The color of the status line, the same as the pager's.
+const STATUS_COLOR: &str = "\x1b[93m";
+
+/// The color of the keys in the list at the bottom of the screen.
+const KEY_COLOR: &str = "\x1b[96m";
+
+/// How far apart tab stops are. Tabs in a file are turned into spaces when it is opened.
+const TAB_WIDTH: usize = 4;
+
+/// The keys listed at the bottom of the screen, and what they do.
+const SHORTCUTS: [(&str, &str); 6] = [
+    ("^G", "Help"),
+    ("^O", "Save"),
+    ("^X", "Exit"),
+    ("^W", "Search"),
+    ("^K", "Cut"),
+    ("^U", "Paste"),
+];
+
+/// What Ctrl+G shows, in the pager.
+const HELP: &str = "\
+edit keys
+
+  Arrows               move the cursor
+  Home, End            go to the start or the end of the line
+  Page Up, Page Down   move a screen up or down
+  Enter                split the line at the cursor
+  Backspace, Delete    delete the character before or under the cursor
+  Tab                  insert spaces up to the next tab stop
+
+  Ctrl+O or Ctrl+S     save the file
+  Ctrl+X               leave, asking to save any changes first
+  Ctrl+W               search for text, ignoring case; an empty search finds the last one again
+  Ctrl+K               cut the line; cutting again right after adds the next line to it
+  Ctrl+U               paste the cut lines above the cursor
+  Ctrl+G               show these keys
+";
+
+/// Edits the file at `path`, which is created when first saved if it doesn't exist yet, until
+/// Ctrl+X is pressed. The screen is cleared for the shell afterwards.
+///
+/// ### params:
+/// - `path`: an absolute path, as [fs::normalize] makes.
+pub fn edit(path: &str) {
+    Editor::open(path).run();
+}
+
+/// How much of the screen has to be drawn again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Redraw {
+    /// Just the status line and the cursor.
+    Nothing,
+    /// The line the cursor is on, too.
+    Line,
+    All,
+}
+
+struct Editor<'a> {
+    path: &'a str,
+    /// The file, a line at a time, without the newlines. There is always at least one.
+    lines: Vec<Vec<char>>,
+    /// Whether the file ended with a newline, so saving it keeps it that way.
+    trailing_newline: bool,
+    row: usize,
+    column: usize,
+    /// The column moving up and down aims for. The cursor goes back to it after passing lines
+    /// shorter than that.
+    goal: usize,
+    /// The index of the line at the top of the screen.
+    top: usize,
+    /// The index of the column at the left of the screen.
+    left: usize,
+    /// How many lines of the file fit on the screen, above the status line.
+    height: usize,
+    columns: usize,
+    /// Whether there are changes that haven't been saved.
+    modified: bool,
+    /// What Ctrl+K cut last, for Ctrl+U to paste.
+    cut: Vec<Vec<char>>,
+    /// Set when the last key cut a line, so cutting another one adds to it rather than replacing it.
+    cutting: bool,
+    /// What was last searched for, in lowercase.
+    pattern: Option<String>,
+    /// Shown in the status line until the next key is pressed, like "Not found".
+    message: Option<String>,
+    redraw: Redraw,
+    input: Focus,
+}
+
+impl<'a> Editor<'a> {
+    fn open(path: &'a str) -> Self {
+        let text = fs::with_store(|store| store.read_to_string(path)).flatten();
+        let message = if text.is_none() { Some(String::from("New file")) } else { None };
+        let text = text.unwrap_or_default();
+        let mut lines: Vec<Vec<char>> = text.lines().map(expand_tabs).collect();
+        if lines.is_empty() {
+            lines.push(Vec::new());
+        }
+        Self {
+            path,
+            lines,
+            trailing_newline: text.is_empty() || text.ends_with('\n'),
+            row: 0,
+            column: 0,
+            goal: 0,
+            top: 0,
+            left: 0,
+            height: framebuffer::rows().saturating_sub(2).max(1),
+            columns: framebuffer::columns().max(1),
+            modified: false,
+            cut: Vec::new(),
+            cutting: false,
+            pattern: None,
+            message,
+            redraw: Redraw::All,
+            input: input::focus(Kinds::KEYS),
+        }
+    }
+
+    /// Handles keys until Ctrl+X is pressed, then clears the screen for the shell.
+    fn run(&mut self) {
+        framebuffer::global_writer::clear_screen();
+        loop {
+            self.scroll_to_cursor();
+            self.draw();
+            let key = self.input.read_key();
+            self.message = None;
+            let cutting = core::mem::take(&mut self.cutting);
+            match key {
+                // Ctrl+X
+                DecodedKey::Unicode('\x18') => {
+                    if self.confirm_exit() {
+                        break;
+                    }
+                }
+                // Ctrl+O or Ctrl+S
+                DecodedKey::Unicode('\x0f' | '\x13') => _ = self.save(),
+                // Ctrl+W
+                DecodedKey::Unicode('\x17') => self.search(),
+                // Ctrl+K
+                DecodedKey::Unicode('\x0b') => self.cut_line(cutting),
+                // Ctrl+U
+                DecodedKey::Unicode('\x15') => self.paste(),
+                // Ctrl+G
+                DecodedKey::Unicode('\x07') => {
+                    pager::show(HELP, "edit help", &mut Output::Screen);
+                    self.redraw = Redraw::All;
+                }
+                DecodedKey::Unicode('\n') => self.split_line(),
+                DecodedKey::Unicode('\x08') => self.backspace(),
+                DecodedKey::Unicode('\x7f') | DecodedKey::RawKey(KeyCode::Delete) => self.delete(),
+                DecodedKey::Unicode('\t') => {
+                    for _ in 0..TAB_WIDTH - self.column % TAB_WIDTH {
+                        self.insert(' ');
+                    }
+                }
+                DecodedKey::Unicode(ch) if ch.is_ascii() && !ch.is_ascii_control() => self.insert(ch),
+                DecodedKey::RawKey(KeyCode::ArrowLeft) => self.move_left(),
+                DecodedKey::RawKey(KeyCode::ArrowRight) => self.move_right(),
+                DecodedKey::RawKey(KeyCode::ArrowUp) => self.move_to_row(self.row.saturating_sub(1)),
+                DecodedKey::RawKey(KeyCode::ArrowDown) => self.move_to_row(self.row + 1),
+                DecodedKey::RawKey(KeyCode::PageUp) => self.move_to_row(self.row.saturating_sub(self.height)),
+                DecodedKey::RawKey(KeyCode::PageDown) => self.move_to_row(self.row + self.height),
+                DecodedKey::RawKey(KeyCode::Home) => self.move_to_column(0),
+                DecodedKey::RawKey(KeyCode::End) => self.move_to_column(self.lines[self.row].len()),
+                _ => {}
+            }
+        }
+        framebuffer::global_writer::clear_screen();
+    }
+
+    /// Notes that the file was changed, and how much of the screen that changed.
+    fn changed(&mut self, redraw: Redraw) {
+        self.modified = true;
+        self.redraw = self.redraw.max(redraw);
+    }
+
+    fn insert(&mut self, ch: char) {
+        self.lines[self.row].insert(self.column, ch);
+        self.column += 1;
+        self.goal = self.column;
+        self.changed(Redraw::Line);
+    }
+
+    /// Moves what is after the cursor onto a new line below.
+    fn split_line(&mut self) {
+        let rest = self.lines[self.row].split_off(self.column);
+        self.lines.insert(self.row + 1, rest);
+        self.row += 1;
+        self.column = 0;
+        self.goal = 0;
+        self.changed(Redraw::All);
+    }
+
+    /// Deletes the character before the cursor, or joins the line to the one above at its start.
+    fn backspace(&mut self) {
+        if self.column > 0 {
+            self.column -= 1;
+            self.lines[self.row].remove(self.column);
+            self.changed(Redraw::Line);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.column = self.lines[self.row].len();
+            self.lines[self.row].extend(line);
+            self.changed(Redraw::All);
+        }
+        self.goal = self.column;
+    }
+
+    /// Deletes the character under the cursor, or joins the next line on at the end of the line.
+    fn delete(&mut self) {
+        if self.column < self.lines[self.row].len() {
+            self.lines[self.row].remove(self.column);
+            self.changed(Redraw::Line);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].extend(next);
+            self.changed(Redraw::All);
+        }
+    }
+
+    /// Cuts the line the cursor is on, adding it to what was cut if `cutting` says the last key
+    /// cut a line too.
+    fn cut_line(&mut self, cutting: bool) {
+        if !cutting {
+            self.cut.clear();
+        }
+        if self.lines.len() == 1 {
+            self.cut.push(core::mem::take(&mut self.lines[0]));
+        } else {
+            self.cut.push(self.lines.remove(self.row));
+            self.row = self.row.min(self.lines.len() - 1);
+        }
+        self.column = 0;
+        self.goal = 0;
+        self.cutting = true;
+        self.changed(Redraw::All);
+    }
+
+    /// Pastes the lines that were cut above the cursor's line.
+    fn paste(&mut self) {
+        if self.cut.is_empty() {
+            self.message = Some(String::from("Nothing has been cut"));
+            return;
+        }
+        self.lines.splice(self.row..self.row, self.cut.iter().cloned());
+        self.row += self.cut.len();
+        self.column = 0;
+        self.goal = 0;
+        self.changed(Redraw::All);
+    }
+
+    fn move_left(&mut self) {
+        if self.column > 0 {
+            self.column -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.column = self.lines[self.row].len();
+        }
+        self.goal = self.column;
+    }
+
+    fn move_right(&mut self) {
+        if self.column < self.lines[self.row].len() {
+            self.column += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.column = 0;
+        }
+        self.goal = self.column;
+    }
+
+    /// Moves up or down to `row`, or the last line if there aren't that many, keeping to the goal
+    /// column as best it can.
+    fn move_to_row(&mut self, row: usize) {
+        self.row = row.min(self.lines.len() - 1);
+        self.column = self.goal.min(self.lines[self.row].len());
+    }
+
+    fn move_to_column(&mut self, column: usize) {
+        self.column = column;
+        self.goal = column;
+    }
+
+    /// Scrolls just far enough that the cursor is on the screen.
+    fn scroll_to_cursor(&mut self) {
+        let (top, left) = (self.top, self.left);
+        if self.row < self.top {
+            self.top = self.row;
+        } else if self.row >= self.top + self.height {
+            self.top = self.row + 1 - self.height;
+        }
+        if self.column < self.left {
+            self.left = self.column;
+        } else if self.column >= self.left + self.columns {
+            self.left = self.column + 1 - self.columns;
+        }
+        if (top, left) != (self.top, self.left) {
+            self.redraw = Redraw::All;
+        }
+    }
+
+    /// Writes the file back to where it came from.
+    ///
+    /// ### returns:
+    /// - `true` if it was saved.
+    fn save(&mut self) -> bool {
+        let lines: Vec<String> = self.lines.iter().map(|line| line.iter().collect()).collect();
+        let mut text = lines.join("\n");
+        if self.trailing_newline {
+            text.push('\n');
+        }
+        if fs::with_store(|store| store.write(self.path, text.as_bytes())).is_none() {
+            self.message = Some(String::from("There is no file system to save to"));
+            return false;
+        }
+        self.modified = false;
+        let plural = if lines.len() == 1 { "" } else { "s" };
+        self.message = Some(format!("Wrote {} line{plural} to {}", lines.len(), self.path));
+        true
+    }
+
+    /// Asks whether to save the changes, if there are any, before leaving.
+    ///
+    /// ### returns:
+    /// - `true` if the editor should be left.
+    fn confirm_exit(&mut self) -> bool {
+        if !self.modified {
+            return true;
+        }
+        self.message = Some(String::from("Save changes? y to save, n to throw them away, Esc to go back"));
+        self.draw_status();
+        loop {
+            match self.input.read_key() {
+                DecodedKey::Unicode('y' | 'Y') => return self.save(),
+                DecodedKey::Unicode('n' | 'N') => return true,
+                // Escape or Ctrl+C
+                DecodedKey::Unicode('\x1b' | '\x03') => {
+                    self.message = None;
+                    return false;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads what to search for on the status line, and goes to the next place it is after the
+    /// cursor. An empty search looks for the last one again.
+    fn search(&mut self) {
+        let prompt = match &self.pattern {
+            Some(pattern) => format!("Search [{pattern}]: "),
+            None => String::from("Search: "),
+        };
+        with_writer(|writer| {
+            writer.set_cursor_position(0, self.height);
+            writer.clear_row(self.height);
+        });
+        let pattern = login::read_line(&prompt, true);
+        if !pattern.is_empty() {
+            self.pattern = Some(pattern.to_lowercase());
+        }
+        self.find_next();
+    }
+
+    /// Goes to the next place the last search is found, looking down from the cursor and then
+    /// from the top of the file.
+    fn find_next(&mut self) {
+        let Some(pattern) = &self.pattern else {
+            self.message = Some(String::from("No previous search"));
+            return;
+        };
+        let pattern: Vec<char> = pattern.chars().collect();
+        let count = self.lines.len();
+        // The cursor's line is looked at twice: after the cursor first, and before it last.
+        for step in 0..=count {
+            let row = (self.row + step) % count;
+            let from = if step == 0 { self.column + 1 } else { 0 };
+            let Some(column) = find(&self.lines[row], &pattern, from) else {
+                continue;
+            };
+            if step == count && column == self.column {
+                self.message = Some(String::from("This is the only match"));
+            } else if self.row + step >= count {
+                self.message = Some(String::from("Search wrapped around"));
+            }
+            self.row = row;
+            self.move_to_column(column);
+            return;
+        }
+        self.message = Some(String::from("Not found"));
+    }
+
+    /// Draws what has changed since the last key, then the status line, and puts the cursor
+    /// where it belongs.
+    fn draw(&mut self) {
+        let redraw = core::mem::replace(&mut self.redraw, Redraw::Nothing);
+        match redraw {
+            Redraw::Nothing => {}
+            Redraw::Line => self.draw_line(self.row - self.top),
+            Redraw::All => {
+                for row in 0..self.height {
+                    self.draw_line(row);
+                }
+                self.draw_shortcuts();
+            }
+        }
+        self.draw_status();
+
+        let under_cursor = self.lines[self.row].get(self.column).copied().map(printable);
+        with_writer(|writer| {
+            writer.set_cursor_position(self.column - self.left, self.row - self.top);
+            writer.set_cursor_char(under_cursor);
+        });
+    }
+
+    /// Draws the line of the file that is on row `row` of the screen, if there is one.
+    fn draw_line(&self, row: usize) {
+        let text: Option<String> = self
+            .lines
+            .get(self.top + row)
+            .map(|line| line.iter().skip(self.left).take(self.columns).copied().map(printable).collect());
+        with_writer(|writer| {
+            writer.set_cursor_position(0, row);
+            writer.clear_row(row);
+            if let Some(text) = text {
+                _ = writer.write_str(&text);
+            }
+        });
+    }
+
+    /// Draws the status line: the message, if there is one, or the file's name and where the
+    /// cursor is in it.
+    fn draw_status(&self) {
+        let status = match &self.message {
+            Some(message) => message.clone(),
+            None => {
+                let modified = if self.modified { " (modified)" } else { "" };
+                let name = format!("{}{modified}", self.path);
+                let position = format!("line {}/{}, col {}", self.row + 1, self.lines.len(), self.column + 1);
+                let width = self.columns.saturating_sub(position.len() + 1);
+                format!("{name:<width$} {position}")
+            }
+        };
+        let status: String = status.chars().take(self.columns).map(printable).collect();
+        with_writer(|writer| {
+            writer.set_cursor_position(0, self.height);
+            writer.clear_row(self.height);
+            _ = write!(writer, "{STATUS_COLOR}{status}\x1b[0m");
+        });
+    }
+
+    /// Draws the list of keys on the last row.
+    fn draw_shortcuts(&self) {
+        let mut text = String::new();
+        for (key, action) in SHORTCUTS {
+            _ = write!(text, "{KEY_COLOR}{key}\x1b[0m {action}  ");
+        }
+        with_writer(|writer| {
+            writer.set_cursor_position(0, self.height + 1);
+            writer.clear_row(self.height + 1);
+            _ = writer.write_str(text.trim_end());
+        });
+    }
+}
+
+/// Returns the characters of `line`, with its tabs turned into spaces up to the next tab stop.
+fn expand_tabs(line: &str) -> Vec<char> {
+    let mut chars = Vec::new();
+    for ch in line.chars() {
+        if ch == '\t' {
+            chars.resize(chars.len() + TAB_WIDTH - chars.len() % TAB_WIDTH, ' ');
+        } else {
+            chars.push(ch);
+        }
+    }
+    chars
+}
+
+/// Returns `ch` if the console's font has it, and `?` otherwise.
+fn printable(ch: char) -> char {
+    if ch.is_ascii() && !ch.is_ascii_control() {
+        ch
+    } else {
+        '?'
+    }
+}
+
+/// Returns where `pattern` first comes in `line` at or after `from`, ignoring case.
+fn find(line: &[char], pattern: &[char], from: usize) -> Option<usize> {
+    let last = line.len().checked_sub(pattern.len())?;
+    (from..=last).find(|&start| {
+        line[start..start + pattern.len()]
+            .iter()
+            .zip(pattern)
+            .all(|(ch, pattern_ch)| ch.to_lowercase().eq(pattern_ch.to_lowercase()))
+    })
+}
@@ -0,0 +1,77 @@
+use alloc::{collections::BTreeMap, string::String};
+
+/// A shell variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variable {
+    pub value: String,
+    /// Whether the variable is part of the environment shown by `env`, rather than local to the
+    /// shell.
+    pub exported: bool,
+}
+
+/// The shell's variables, expanded by `$NAME` on the command line.
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    variables: BTreeMap<String, Variable>,
+}
+
+impl Environment {
+    pub const fn new() -> Self {
+        Self {
+            variables: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the value of a variable, if it is set.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.variables.get(name).map(|variable| variable.value.as_str())
+    }
+
+    /// Sets a variable. A variable that was exported stays exported.
+    pub fn set(&mut self, name: &str, value: &str) {
+        match self.variables.get_mut(name) {
+            Some(variable) => variable.value = String::from(value),
+            None => {
+                self.variables.insert(
+                    String::from(name),
+                    Variable {
+                        value: String::from(value),
+                        exported: false,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Marks a variable as exported, creating it empty if it is not set.
+    pub fn export(&mut self, name: &str) {
+        self.variables
+            .entry(String::from(name))
+            .or_insert_with(|| Variable {
+                value: String::new(),
+                exported: false,
+            })
+            .exported = true;
+    }
+
+    /// Removes a variable.
+    ///
+    /// ### returns:
+    /// - `bool`: true if the variable was set.
+    pub fn unset(&mut self, name: &str) -> bool {
+        self.variables.remove(name).is_some()
+    }
+
+    /// Returns an iterator over every variable, in order of name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Variable)> {
+        self.variables.iter().map(|(name, variable)| (name.as_str(), variable))
+    }
+}
+
+/// Returns true if `name` can be used as a variable name: letters, digits and underscores, not
+/// starting with a digit.
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
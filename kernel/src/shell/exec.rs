@@ -0,0 +1,278 @@
+//! Running command lines: pipelines, redirections and expansions, on behalf of a [Session].
+use crate::{
+    fs,
+    println,
+    shell::{
+        command::{ExitStatus, Io, Output},
+        lexer::{RedirectKind, Word, WordPart},
+        pager::AutoPager,
+        parser::{self, Condition, Pipeline, Stage},
+        registry,
+        script,
+        session::Session
+    }
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec
+};
+
+/// How many `$(command)` substitutions can run inside each other before the next is refused, so
+/// one that runs itself, through an alias, can't overflow the kernel stack. Each one takes about
+/// 5 KiB of it.
+const MAX_SUBSTITUTIONS: usize = 16;
+
+/// Runs a command line, and returns the status of the last command that ran.
+///
+/// The pipelines in the line run one after the other, skipping those whose `&&` or `||`
+/// condition does not hold. `$?` is kept up to date as they go. Aliases are expanded before
+/// anything runs.
+///
+/// ### params:
+/// - `session`: the session to run the line in.
+/// - `line`: the command line, which is parsed and expanded first.
+/// - `out`: where the output of each pipeline's last command goes.
+pub fn run_line(session: &mut Session, line: &str, out: &mut Output) -> ExitStatus {
+    let pipelines = match parser::parse(line) {
+        Ok(pipelines) => pipelines,
+        Err(error) => {
+            println!("ash: {}", error);
+            session.status = ExitStatus::FAILURE;
+            return session.status;
+        }
+    };
+    let pipelines = match expand_aliases(session, pipelines, &mut Vec::new()) {
+        Ok(pipelines) => pipelines,
+        Err(message) => {
+            println!("ash: {}", message);
+            session.status = ExitStatus::FAILURE;
+            return session.status;
+        }
+    };
+    for pipeline in &pipelines {
+        let runs = match pipeline.condition {
+            Condition::Always => true,
+            Condition::OnSuccess => session.status.is_success(),
+            Condition::OnFailure => !session.status.is_success(),
+        };
+        if runs {
+            session.status = run_pipeline(session, &pipeline.stages, out);
+        }
+    }
+    session.status
+}
+
+/// Replaces the aliases that commands start with by what they stand for.
+///
+/// An alias's value is parsed as a command line of its own, so it can hold options, pipes and
+/// even `;`, `&&` and `||`, and the words after the alias are added to its last command. An alias
+/// is not expanded again inside itself, so `alias ls='ls -a'` works.
+///
+/// ### params:
+/// - `expanding`: the aliases being expanded already, further out.
+fn expand_aliases(
+    session: &Session,
+    pipelines: Vec<Pipeline>,
+    expanding: &mut Vec<String>,
+) -> Result<Vec<Pipeline>, String> {
+    let mut expanded = Vec::new();
+    for pipeline in pipelines {
+        let single = pipeline.stages.len() == 1;
+        let mut stages = Vec::new();
+        for mut stage in pipeline.stages {
+            let value = match stage.words.first().map(|word| word.parts.as_slice()) {
+                Some([WordPart::Literal(name)]) if !expanding.contains(name) => {
+                    session.aliases.get(name).map(|value| (name.clone(), value))
+                }
+                _ => None,
+            };
+            let Some((name, value)) = value else {
+                stages.push(stage);
+                continue;
+            };
+
+            let parsed = parser::parse(value).map_err(|error| format!("alias {name}: {error}"))?;
+            expanding.push(name.clone());
+            let mut parsed = expand_aliases(session, parsed, expanding)?;
+            expanding.pop();
+
+            // Hand the rest of the command to the alias's last command.
+            stage.words.remove(0);
+            match parsed.last_mut().and_then(|pipeline| pipeline.stages.last_mut()) {
+                Some(last) => {
+                    last.words.append(&mut stage.words);
+                    last.redirects.append(&mut stage.redirects);
+                }
+                None => {
+                    stages.push(stage);
+                    continue;
+                }
+            }
+            if parsed.len() == 1 {
+                stages.append(&mut parsed[0].stages);
+            } else if single {
+                parsed[0].condition = pipeline.condition;
+                expanded.append(&mut parsed);
+            } else {
+                return Err(format!("alias {name}: an alias with ;, && or || cannot be part of a pipeline"));
+            }
+        }
+        if !stages.is_empty() {
+            expanded.push(Pipeline { condition: pipeline.condition, stages });
+        }
+    }
+    Ok(expanded)
+}
+
+/// Runs a pipeline, and returns the status of its last command.
+fn run_pipeline(session: &mut Session, stages: &[Stage], out: &mut Output) -> ExitStatus {
+    let mut status = ExitStatus::SUCCESS;
+    let mut piped = None;
+    for (index, stage) in stages.iter().enumerate() {
+        if index + 1 == stages.len() {
+            status = run_stage(session, stage, piped.take(), out);
+        } else {
+            let mut buffer = Output::Buffer(String::new());
+            status = run_stage(session, stage, piped.take(), &mut buffer);
+            piped = Some(buffer.into_string());
+        }
+    }
+    status
+}
+
+/// Runs one command of a pipeline, after applying its redirections.
+///
+/// ### params:
+/// - `stage`: the command.
+/// - `input`: the output of the previous command, if it is being piped in.
+/// - `out`: where the output goes, unless it is redirected to a file.
+fn run_stage(session: &mut Session, stage: &Stage, mut input: Option<String>, out: &mut Output) -> ExitStatus {
+    let mut target = None;
+    for redirect in &stage.redirects {
+        let path = match expand(session, &redirect.target) {
+            Ok(target) => fs::normalize(&target),
+            Err(message) => {
+                println!("ash: {}", message);
+                return ExitStatus::FAILURE;
+            }
+        };
+        match redirect.kind {
+            RedirectKind::Input => {
+                match fs::with_store(|store| store.read_to_string(&path)).flatten() {
+                    Some(text) => input = Some(text),
+                    None => {
+                        println!("ash: {}: No such file", path);
+                        return ExitStatus::FAILURE;
+                    }
+                }
+            }
+            RedirectKind::Output | RedirectKind::Append => {
+                if !session.can_write(&path) {
+                    println!("ash: {}: Permission denied; only root can write in another user's home", path);
+                    return ExitStatus::FAILURE;
+                }
+                target = Some((path, redirect.kind == RedirectKind::Append));
+            }
+        }
+    }
+
+    let Some((path, append)) = target else {
+        return run_command(session, stage, input, out);
+    };
+    let mut buffer = Output::Buffer(String::new());
+    let status = run_command(session, stage, input, &mut buffer);
+    let text = buffer.into_string();
+    let written = fs::with_store(|store| {
+        if append {
+            store.append(&path, text.as_bytes());
+        } else {
+            store.write(&path, text.as_bytes());
+        }
+    });
+    if written.is_none() {
+        println!("ash: {}: there is no file store to write to", path);
+        return ExitStatus::FAILURE;
+    }
+    status
+}
+
+/// Expands a command's words, then looks it up and runs it. A name with a `/` in it is a path
+/// to a script.
+fn run_command(session: &mut Session, stage: &Stage, input: Option<String>, out: &mut Output) -> ExitStatus {
+    let parts = match stage.words.iter().map(|word| expand(session, word)).collect::<Result<Vec<String>, String>>() {
+        Ok(parts) => parts,
+        Err(message) => {
+            println!("ash: {}", message);
+            return ExitStatus::FAILURE;
+        }
+    };
+    let Some((name, args)) = parts.split_first() else {
+        return ExitStatus::SUCCESS;
+    };
+    match registry::find(name) {
+        Some(command) if command.pages_output() && matches!(out, Output::Screen) => {
+            let mut paged = Output::Paged(AutoPager::new());
+            let status = command.run(args, &mut Io { out: &mut paged, err: Output::Screen, input, session });
+            if let Output::Paged(pager) = paged {
+                pager.finish(command.name());
+            }
+            status
+        }
+        Some(command) => command.run(args, &mut Io { out, err: Output::Screen, input, session }),
+        None if name.contains('/') => run_path(session, name, args, out),
+        None if ["touch", "cd", "mkdir"].contains(&name.as_str()) => {
+            println!("Bro... there is no filesystem... <add skull emoji here when emojis are supported... //todo>");
+            ExitStatus::WARNING
+        }
+        None => {
+            println!("{} is not a valid command", name);
+            ExitStatus::NOT_FOUND
+        }
+    }
+}
+
+/// Runs the file at `path` as a script, as long as it starts with a `#!` line.
+fn run_path(session: &mut Session, path: &str, args: &[String], out: &mut Output) -> ExitStatus {
+    match fs::with_store(|store| store.read_to_string(&fs::normalize(path))).flatten() {
+        Some(source) if source.starts_with("#!") => script::run_file(session, path, &source, args, out),
+        Some(_) => {
+            println!("ash: {}: not a script; scripts start with a #! line", path);
+            ExitStatus::FAILURE
+        }
+        None => {
+            println!("ash: {}: No such file", path);
+            ExitStatus::NOT_FOUND
+        }
+    }
+}
+
+/// Expands the variables and command substitutions in a word, giving the argument it stands
+/// for. Unset variables expand to nothing, and `$?` to the status of the last command.
+///
+/// ### returns:
+/// - `Ok(String)`: the argument.
+/// - `Err(String)`: why it couldn't be expanded: substitutions nested too deeply.
+fn expand(session: &mut Session, word: &Word) -> Result<String, String> {
+    let mut text = String::new();
+    for part in &word.parts {
+        match part {
+            WordPart::Literal(literal) => text.push_str(literal),
+            WordPart::Variable(name) if name == "?" => text.push_str(&session.status.code().to_string()),
+            WordPart::Variable(name) => text.push_str(session.variable(name).unwrap_or("")),
+            WordPart::Command(command) => {
+                if session.substitution_depth >= MAX_SUBSTITUTIONS {
+                    return Err(format!("$({command}): substitutions nested too deeply, more than {MAX_SUBSTITUTIONS}"));
+                }
+                let mut out = Output::Buffer(String::new());
+                session.substitution_depth += 1;
+                run_line(session, command, &mut out);
+                session.substitution_depth -= 1;
+                let output = out.into_string();
+                // Like other shells, drop the trailing newlines.
+                text.push_str(output.trim_end_matches('\n'));
+            }
+        }
+    }
+    Ok(text)
+}
@@ -0,0 +1,229 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec
+};
+
+/// The file in the user's home the history is saved to, so it survives between sessions.
+pub const HISTORY_FILE: &str = ".ash_history";
+
+/// How many commands are remembered before the oldest ones are dropped.
+const HISTORY_CAPACITY: usize = 500;
+
+/// The command history of a shell session.
+///
+/// Besides the list of commands, this keeps track of where the user currently is while cycling
+/// through it with the arrow keys, along with the line they were typing before they started.
+pub struct History {
+    entries: Vec<String>,
+    /// Index of the entry currently recalled with Up/Down, if any.
+    position: Option<usize>,
+    /// The line that was being typed before the user started cycling through the history.
+    draft: String,
+}
+
+impl History {
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            position: None,
+            draft: String::new(),
+        }
+    }
+
+    /// Adds a command to the history.
+    ///
+    /// Blank lines, and lines identical to the previous command, are not recorded.
+    pub fn push(&mut self, line: &str) {
+        self.position = None;
+        let line = line.trim();
+        if line.is_empty() || self.entries.last().is_some_and(|last| last == line) {
+            return;
+        }
+        if self.entries.len() == HISTORY_CAPACITY {
+            self.entries.remove(0);
+        }
+        self.entries.push(line.to_string());
+    }
+
+    /// Returns the number of commands in the history.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if nothing has been recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the command at `index` (0-based, oldest first).
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Returns an iterator over the commands, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(String::as_str)
+    }
+
+    /// Forgets every command.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.position = None;
+    }
+
+    /// Steps one command back in the history (the Up arrow).
+    ///
+    /// ### params:
+    /// - `current`: the line being edited, which is restored once the user steps past the newest
+    ///   entry again.
+    ///
+    /// ### returns:
+    /// - `Some(&str)`: the command to show.
+    /// - `None`: if there is nothing older.
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let index = match self.position {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(0) => return None,
+            Some(index) => index - 1,
+        };
+        self.position = Some(index);
+        Some(&self.entries[index])
+    }
+
+    /// Steps one command forward in the history (the Down arrow).
+    ///
+    /// ### returns:
+    /// - `Some(&str)`: the command to show, which is the saved draft once the newest entry has
+    ///   been passed.
+    /// - `None`: if the user is not cycling through the history.
+    pub fn newer(&mut self) -> Option<&str> {
+        let index = self.position?;
+        if index + 1 < self.entries.len() {
+            self.position = Some(index + 1);
+            Some(&self.entries[index + 1])
+        } else {
+            self.position = None;
+            Some(&self.draft)
+        }
+    }
+
+    /// Searches backwards for a command containing `query`.
+    ///
+    /// ### params:
+    /// - `query`: the text to look for.
+    /// - `before`: only entries older than this index are considered.
+    ///
+    /// ### returns:
+    /// - `Some(usize)`: the index of the newest matching entry.
+    /// - `None`: if no entry matches.
+    pub fn search(&self, query: &str, before: usize) -> Option<usize> {
+        let end = before.min(self.entries.len());
+        self.entries[..end].iter().rposition(|entry| entry.contains(query))
+    }
+
+    /// Performs history expansion on a line, replacing `!!` with the previous command,
+    /// `!n` with command number `n` and `!-n` with the n-th previous command.
+    ///
+    /// Quotes are followed as the [lexer](crate::shell::lexer) follows them: a `!` in single
+    /// quotes, or escaped with a backslash, is left alone.
+    ///
+    /// ### returns:
+    /// - `Ok(Some(String))`: the expanded line, if anything was expanded.
+    /// - `Ok(None)`: if the line contains no history references.
+    /// - `Err(String)`: a message naming the event that could not be found.
+    pub fn expand(&self, line: &str) -> Result<Option<String>, String> {
+        if !line.contains('!') {
+            return Ok(None);
+        }
+        let mut expanded = String::new();
+        let mut changed = false;
+        let mut chars = line.chars().peekable();
+        let (mut single_quoted, mut double_quoted) = (false, false);
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\'' if !double_quoted => single_quoted = !single_quoted,
+                '"' if !single_quoted => double_quoted = !double_quoted,
+                '\\' if !single_quoted => {
+                    expanded.push(ch);
+                    if let Some(escaped) = chars.next() {
+                        expanded.push(escaped);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            if ch != '!' || single_quoted {
+                expanded.push(ch);
+                continue;
+            }
+            let event = match chars.peek() {
+                Some('!') => {
+                    chars.next();
+                    self.entries.last().ok_or_else(|| String::from("!!: event not found"))?
+                }
+                Some(&next) if next == '-' || next.is_ascii_digit() => {
+                    let mut spec = String::new();
+                    if next == '-' {
+                        spec.push(next);
+                        chars.next();
+                    }
+                    while let Some(&digit) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                        spec.push(digit);
+                        chars.next();
+                    }
+                    self.event(&spec)
+                        .ok_or_else(|| format!("!{spec}: event not found"))?
+                }
+                _ => {
+                    expanded.push(ch);
+                    continue;
+                }
+            };
+            expanded.push_str(event);
+            changed = true;
+        }
+        Ok(changed.then_some(expanded))
+    }
+
+    /// Resolves the number after a `!`: `n` is the n-th command (1-based, as numbered by the
+    /// `history` builtin), `-n` is the n-th previous one.
+    fn event(&self, spec: &str) -> Option<&String> {
+        if let Some(back) = spec.strip_prefix('-') {
+            let back: usize = back.parse().ok()?;
+            let index = self.entries.len().checked_sub(back)?;
+            self.entries.get(index)
+        } else {
+            let number: usize = spec.parse().ok()?;
+            self.entries.get(number.checked_sub(1)?)
+        }
+    }
+
+    /// Replaces the history with the commands in `text`, one per line.
+    pub fn load(&mut self, text: &str) {
+        self.clear();
+        for line in text.lines() {
+            self.push(line);
+        }
+    }
+
+    /// Returns the history as text, one command per line, as understood by [History::load].
+    pub fn serialize(&self) -> String {
+        let mut text = String::new();
+        for entry in &self.entries {
+            text.push_str(entry);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
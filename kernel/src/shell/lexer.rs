@@ -0,0 +1,315 @@
+use alloc::{
+    string::String,
+    vec::Vec
+};
+use core::fmt;
+
+/// A piece of a [Word], as written on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordPart {
+    /// Text that is used as-is: anything unquoted, quoted or escaped.
+    Literal(String),
+    /// A `$NAME` or `${NAME}` reference, replaced by the variable's value. `$?` is the variable
+    /// `?`, the status of the last command.
+    Variable(String),
+    /// A `$(command)` substitution, replaced by the command's output.
+    Command(String),
+}
+
+/// One argument on the command line.
+///
+/// Its parts are expanded and joined back together when the command runs, so `"$USER"s` is a
+/// single argument, and an expansion is never split into several.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Word {
+    pub parts: Vec<WordPart>,
+}
+
+impl Word {
+    /// Appends literal text, merging it with the previous part if that is literal too.
+    fn push_char(&mut self, ch: char) {
+        if let Some(WordPart::Literal(text)) = self.parts.last_mut() {
+            text.push(ch);
+        } else {
+            self.parts.push(WordPart::Literal(String::from(ch)));
+        }
+    }
+}
+
+/// Which way a redirection sends data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectKind {
+    /// `< file`: the command reads the file.
+    Input,
+    /// `> file`: the command's output replaces the file.
+    Output,
+    /// `>> file`: the command's output is added to the end of the file.
+    Append,
+}
+
+/// A token of a command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Word(Word),
+    /// `|`
+    Pipe,
+    /// `<`, `>` or `>>`
+    Redirect(RedirectKind),
+    /// `;`
+    Semicolon,
+    /// `&&`
+    And,
+    /// `||`
+    Or,
+}
+
+/// An error in the syntax of a command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// What went wrong.
+    pub message: &'static str,
+    /// The column (counting from 1) where the problem was found.
+    pub column: usize,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "syntax error at column {}: {}", self.column, self.message)
+    }
+}
+
+/// Splits a command line into tokens, each paired with the index of the character it starts at.
+///
+/// - Words are separated by unquoted whitespace.
+/// - `'single quotes'` keep everything inside them as-is.
+/// - `"double quotes"` keep whitespace, but still expand `$` references. Inside them a backslash
+///   only escapes `"`, `\`, `$` and `` ` ``.
+/// - Outside of quotes, a backslash escapes any character.
+/// - `$NAME`, `${NAME}`, `$?` and `$(command)` are expanded when the command runs.
+/// - Unquoted `|`, `<`, `>`, `>>`, `;`, `&&` and `||` are operators, even without whitespace
+///   around them.
+/// - An unquoted `#` at the start of a word begins a comment, which runs to the end of the line.
+pub fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, SyntaxError> {
+    Lexer::new(input).tokenize()
+}
+
+/// Returns true if `ch` can appear in a variable name.
+fn is_name_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Lexer {
+    fn new(input: &str) -> Self {
+        Self {
+            chars: input.chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.position += 1;
+        Some(ch)
+    }
+
+    /// Builds an error pointing at the character at `position`.
+    fn error(&self, message: &'static str, position: usize) -> SyntaxError {
+        SyntaxError {
+            message,
+            column: position + 1,
+        }
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(usize, Token)>, SyntaxError> {
+        let mut tokens = Vec::new();
+        let mut word = Word::default();
+        // Where the current word started, if one has been; `""` is an empty word, but still a word.
+        let mut word_start = None;
+
+        while let Some(ch) = self.next() {
+            let position = self.position - 1;
+            let in_word = word_start.is_some();
+            if !in_word && !ch.is_whitespace() && !matches!(ch, '|' | '<' | '>' | ';' | '&' | '#') {
+                word_start = Some(position);
+            }
+            match ch {
+                ch if ch.is_whitespace() || matches!(ch, '|' | '<' | '>' | ';' | '&') => {
+                    if let Some(start) = word_start.take() {
+                        tokens.push((start, Token::Word(core::mem::take(&mut word))));
+                    }
+                    let operator = match ch {
+                        '|' if self.peek() == Some('|') => {
+                            self.position += 1;
+                            Token::Or
+                        }
+                        '|' => Token::Pipe,
+                        ';' => Token::Semicolon,
+                        '&' if self.peek() == Some('&') => {
+                            self.position += 1;
+                            Token::And
+                        }
+                        '&' => {
+                            return Err(self.error("background jobs (&) are not supported", position));
+                        }
+                        '<' => Token::Redirect(RedirectKind::Input),
+                        '>' if self.peek() == Some('>') => {
+                            self.position += 1;
+                            Token::Redirect(RedirectKind::Append)
+                        }
+                        '>' => Token::Redirect(RedirectKind::Output),
+                        _ => continue,
+                    };
+                    tokens.push((position, operator));
+                }
+                '#' if !in_word => {
+                    while self.next().is_some_and(|ch| ch != '\n') {}
+                }
+                '\'' => self.single_quoted(&mut word)?,
+                '"' => self.double_quoted(&mut word)?,
+                '\\' => {
+                    let escaped = self
+                        .next()
+                        .ok_or_else(|| self.error("nothing to escape after \\", self.position - 1))?;
+                    word.push_char(escaped);
+                }
+                '$' => self.dollar(&mut word)?,
+                ch => word.push_char(ch),
+            }
+        }
+        if let Some(start) = word_start {
+            tokens.push((start, Token::Word(word)));
+        }
+        Ok(tokens)
+    }
+
+    /// Reads the rest of a `'single quoted'` string, the opening quote having been consumed.
+    fn single_quoted(&mut self, word: &mut Word) -> Result<(), SyntaxError> {
+        let start = self.position - 1;
+        // Make sure `''` still adds an (empty) literal to the word.
+        word.parts.push(WordPart::Literal(String::new()));
+        loop {
+            match self.next() {
+                Some('\'') => return Ok(()),
+                Some(ch) => word.push_char(ch),
+                None => return Err(self.error("unterminated single quote", start)),
+            }
+        }
+    }
+
+    /// Reads the rest of a `"double quoted"` string, the opening quote having been consumed.
+    fn double_quoted(&mut self, word: &mut Word) -> Result<(), SyntaxError> {
+        let start = self.position - 1;
+        word.parts.push(WordPart::Literal(String::new()));
+        loop {
+            match self.next() {
+                Some('"') => return Ok(()),
+                Some('\\') => match self.peek() {
+                    Some(ch @ ('"' | '\\' | '$' | '`')) => {
+                        self.position += 1;
+                        word.push_char(ch);
+                    }
+                    _ => word.push_char('\\'),
+                },
+                Some('$') => self.dollar(word)?,
+                Some(ch) => word.push_char(ch),
+                None => return Err(self.error("unterminated double quote", start)),
+            }
+        }
+    }
+
+    /// Reads what follows a `$`, which has been consumed.
+    fn dollar(&mut self, word: &mut Word) -> Result<(), SyntaxError> {
+        let start = self.position - 1;
+        match self.peek() {
+            Some('{') => {
+                self.position += 1;
+                let name = self.name();
+                match self.next() {
+                    Some('}') if !name.is_empty() => {
+                        word.parts.push(WordPart::Variable(name));
+                        Ok(())
+                    }
+                    Some(_) => Err(self.error("bad substitution", start)),
+                    None => Err(self.error("unterminated ${", start)),
+                }
+            }
+            Some('(') => {
+                self.position += 1;
+                let command = self.substitution(start)?;
+                word.parts.push(WordPart::Command(command));
+                Ok(())
+            }
+            Some('?') => {
+                self.position += 1;
+                word.parts.push(WordPart::Variable(String::from("?")));
+                Ok(())
+            }
+            Some(ch) if ch.is_ascii_alphabetic() || ch == '_' => {
+                let name = self.name();
+                word.parts.push(WordPart::Variable(name));
+                Ok(())
+            }
+            // A `$` that does not start an expansion is just a dollar sign.
+            _ => {
+                word.push_char('$');
+                Ok(())
+            }
+        }
+    }
+
+    /// Reads a variable name.
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(ch) = self.peek().filter(|&ch| is_name_char(ch)) {
+            name.push(ch);
+            self.position += 1;
+        }
+        name
+    }
+
+    /// Reads the command inside a `$(...)`, the `$(` having been consumed, up to the matching
+    /// parenthesis. Quotes inside are skipped over, so `$(echo ")")` works.
+    fn substitution(&mut self, start: usize) -> Result<String, SyntaxError> {
+        let mut command = String::new();
+        let mut depth = 0;
+        let mut quote = None;
+        loop {
+            let Some(ch) = self.next() else {
+                return Err(self.error("unterminated $(", start));
+            };
+            match (quote, ch) {
+                (Some(open), ch) if ch == open => quote = None,
+                (Some('"'), '\\') => {
+                    command.push(ch);
+                    if let Some(escaped) = self.next() {
+                        command.push(escaped);
+                    }
+                    continue;
+                }
+                (Some(_), _) => {}
+                (None, '\'' | '"') => quote = Some(ch),
+                (None, '\\') => {
+                    command.push(ch);
+                    if let Some(escaped) = self.next() {
+                        command.push(escaped);
+                    }
+                    continue;
+                }
+                (None, '(') => depth += 1,
+                (None, ')') if depth == 0 => return Ok(command),
+                (None, ')') => depth -= 1,
+                (None, _) => {}
+            }
+            command.push(ch);
+        }
+    }
+}
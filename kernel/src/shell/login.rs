@@ -0,0 +1,120 @@
+//! The login screen, and reading lines of input such as passwords outside of the shell's own
+//! line editor.
+use crate::{
+    framebuffer::{color::ColoredWriting, ConsoleColor},
+    input::{self, Kinds},
+    print,
+    println,
+    shell::PROMPT_WIDTH,
+    users::{self, User, GUEST, GUEST_ENABLED}
+};
+use alloc::string::String;
+use core::sync::atomic::Ordering;
+use pc_keyboard::DecodedKey;
+
+/// Prints `prompt`, and reads a line typed at it.
+///
+/// ### params:
+/// - `prompt`: what to ask.
+/// - `echo`: whether to show what is typed. Passwords aren't shown at all, not even as `*`s, so
+///   their length isn't given away either.
+pub fn read_line(prompt: &str, echo: bool) -> String {
+    let input = input::focus(Kinds::KEYS);
+    print!("{}", prompt);
+    PROMPT_WIDTH.store((prompt.chars().count() + 1) * 10, Ordering::Relaxed);
+    let mut line = String::new();
+    loop {
+        match input.read_key() {
+            DecodedKey::Unicode('\n') => {
+                println!();
+                return line;
+            }
+            DecodedKey::Unicode('\x08') => {
+                if line.pop().is_some() && echo {
+                    print!("\x08");
+                }
+            }
+            DecodedKey::Unicode(char) if !char.is_control() => {
+                line.push(char);
+                if echo {
+                    print!("{}", char);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reads a password typed at `prompt`, without showing it.
+pub fn read_password(prompt: &str) -> String {
+    read_line(prompt, false)
+}
+
+/// Asks for a new password twice, to catch typos.
+///
+/// ### returns:
+/// - `Ok(String)`: the new password.
+/// - `Err(&str)`: why there isn't one: it was empty, or the two didn't match.
+pub fn read_new_password() -> Result<String, &'static str> {
+    let password = read_password("New password: ");
+    if password.is_empty() {
+        return Err("the password cannot be empty");
+    }
+    if read_password("Retype new password: ") != password {
+        return Err("the passwords do not match");
+    }
+    Ok(password)
+}
+
+/// Makes `user`, who has just logged in, choose a new password if theirs has
+/// [expired](User::password_expired), asking until they do.
+fn renew_expired_password(user: &User) {
+    if !user.password_expired() {
+        return;
+    }
+    println!("{}'s password is the default one, which anyone can know. Choose a new one.", user.name);
+    loop {
+        match read_new_password() {
+            Ok(password) => {
+                _ = users::with_users(|users| users.set_password(&user.name, Some(&password)));
+                return println!("Password updated for {}", user.name);
+            }
+            Err(message) => println!("{}", message.fg(ConsoleColor::Red)),
+        }
+    }
+}
+
+/// Shows the login screen until someone logs in.
+///
+/// ### returns:
+/// - `User`: who logged in.
+pub fn login() -> User {
+    println!();
+    println!("{}", "Welcome to PortfoliOS!".fg(ConsoleColor::BrightCyan));
+    if GUEST_ENABLED {
+        println!("Log in as {} to look around; no password needed.", GUEST.fg(ConsoleColor::BrightGreen));
+    }
+    loop {
+        let name = read_line("login: ", true);
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        let user = users::find(name);
+        if let Some(user) = user.as_ref().filter(|user| !user.has_password()) {
+            return user.clone();
+        }
+        // Ask for a password even if there is no such user, so that isn't given away.
+        let password = read_password("Password: ");
+        match user {
+            Some(user) if user.check_password(&password) => {
+                renew_expired_password(&user);
+                return user;
+            }
+            Some(_) => {}
+            // Take as long as checking a real password, so that isn't given away either.
+            None => users::with_users(|users| users.check_decoy(&password)),
+        }
+        println!("{}", "Login incorrect".fg(ConsoleColor::Red));
+    }
+}
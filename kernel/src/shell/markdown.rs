@@ -0,0 +1,479 @@
+//! Renders Markdown as colored console text, wrapped to a width.
+//!
+//! Supported are ATX headings (`#`), paragraphs, `**bold**`, `*italic*`, `~~struck~~` and
+//! `` `code` ``, links and images, block quotes, nested lists, fenced code blocks, rules, and
+//! tables with alignment. The console has no bold or italic text, so styles are shown as colors.
+use alloc::{
+    format,
+    string::String,
+    vec,
+    vec::Vec
+};
+
+/// ANSI color codes for each style. 0 is the console's normal color.
+const NORMAL: u8 = 0;
+const HEADING: u8 = 96;
+const SUBHEADING: u8 = 95;
+const BOLD: u8 = 93;
+const ITALIC: u8 = 36;
+const CODE: u8 = 92;
+const LINK: u8 = 94;
+/// Rules, table borders, quote bars, URLs and struck out text.
+const FADED: u8 = 90;
+
+/// Narrower than this, and wrapping does more harm than good.
+const MIN_WIDTH: usize = 20;
+
+/// Block quotes nested deeper than this are shown as plain text, `>` and all.
+const MAX_QUOTE_DEPTH: usize = 8;
+
+/// A character, and the color it is shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cell {
+    ch: char,
+    color: u8,
+}
+
+/// Renders `source` as lines of at most `width` characters, not counting color codes. Every line
+/// ends with a newline.
+pub fn render(source: &str, width: usize) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut rendered = Vec::new();
+    render_blocks(&lines, width.max(MIN_WIDTH), 0, &mut rendered);
+    let mut text = String::new();
+    for line in rendered {
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text
+}
+
+/// Renders a run of lines, block by block, separating the blocks with a blank line.
+///
+/// ### params:
+/// - `quotes`: how many block quotes the lines are nested in.
+fn render_blocks(lines: &[&str], width: usize, quotes: usize, out: &mut Vec<String>) {
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        if line.trim().is_empty() {
+            index += 1;
+            continue;
+        }
+        if !out.is_empty() {
+            out.push(String::new());
+        }
+
+        let trimmed = line.trim_start();
+        if let Some(fence) = fence(trimmed) {
+            index += 1;
+            while index < lines.len() && !lines[index].trim_start().starts_with(fence) {
+                out.push(to_ansi(&styled(&format!("  {}", lines[index]), CODE)));
+                index += 1;
+            }
+            // Skip the closing fence, if there is one.
+            index += 1;
+        } else if let Some((level, text)) = heading(trimmed) {
+            render_heading(level, text, width, out);
+            index += 1;
+        } else if is_rule(trimmed) {
+            out.push(to_ansi(&styled(&"-".repeat(width), FADED)));
+            index += 1;
+        } else if trimmed.starts_with('|') && lines.get(index + 1).and_then(|line| alignments(line)).is_some() {
+            let start = index;
+            index += 2;
+            while index < lines.len() && lines[index].trim_start().starts_with('|') {
+                index += 1;
+            }
+            render_table(&lines[start..index], width, out);
+        } else if trimmed.starts_with('>') && quotes < MAX_QUOTE_DEPTH {
+            let mut quoted = Vec::new();
+            while index < lines.len() && lines[index].trim_start().starts_with('>') {
+                let inner = &lines[index].trim_start()[1..];
+                quoted.push(inner.strip_prefix(' ').unwrap_or(inner));
+                index += 1;
+            }
+            let mut inner = Vec::new();
+            render_blocks(&quoted, width.saturating_sub(2).max(1), quotes + 1, &mut inner);
+            let bar = to_ansi(&styled("| ", FADED));
+            out.extend(inner.into_iter().map(|line| format!("{bar}{line}")));
+        } else if list_item(line).is_some() {
+            index = render_list(lines, index, width, out);
+        } else {
+            let start = index;
+            index += 1;
+            while index < lines.len() && !lines[index].trim().is_empty() && !starts_block(lines[index]) {
+                index += 1;
+            }
+            let text = lines[start..index].iter().map(|line| line.trim()).collect::<Vec<_>>().join(" ");
+            out.extend(wrap(&inline(&text, NORMAL), width).iter().map(|line| to_ansi(line)));
+        }
+    }
+}
+
+/// Returns true if `line` starts a block other than a paragraph, which ends the paragraph before
+/// it.
+fn starts_block(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    fence(trimmed).is_some()
+        || heading(trimmed).is_some()
+        || is_rule(trimmed)
+        || trimmed.starts_with('>')
+        || trimmed.starts_with('|')
+        || list_item(line).is_some()
+}
+
+/// Returns the fence a fenced code block starts with, if `line` starts one.
+fn fence(line: &str) -> Option<&'static str> {
+    ["```", "~~~"].into_iter().find(|fence| line.starts_with(fence))
+}
+
+/// Returns the level and text of a heading, if `line` is one.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&ch| ch == '#').count();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim_end()))
+}
+
+/// Returns true if `line` is a rule: three or more `-`, `*` or `_`, and nothing else but spaces.
+fn is_rule(line: &str) -> bool {
+    let marks: Vec<char> = line.chars().filter(|ch| !ch.is_whitespace()).collect();
+    marks.len() >= 3 && ['-', '*', '_'].iter().any(|&mark| marks.iter().all(|&ch| ch == mark))
+}
+
+fn render_heading(level: usize, text: &str, width: usize, out: &mut Vec<String>) {
+    let color = if level <= 2 { HEADING } else { SUBHEADING };
+    let lines = wrap(&inline(text, color), width);
+    let underline_len = lines.iter().map(Vec::len).max().unwrap_or(0);
+    out.extend(lines.iter().map(|line| to_ansi(line)));
+    // Underline the top two levels, like setext headings.
+    match level {
+        1 => out.push(to_ansi(&styled(&"=".repeat(underline_len), color))),
+        2 => out.push(to_ansi(&styled(&"-".repeat(underline_len), color))),
+        _ => {}
+    }
+}
+
+/// A list item: how deep it is nested, its marker, and its text.
+struct ListItem<'a> {
+    depth: usize,
+    marker: Option<&'a str>,
+    text: &'a str,
+}
+
+/// Returns the item `line` starts, if it starts one. Each two spaces of indentation nest it one
+/// level deeper. Unordered items have no marker: one is chosen by their depth.
+fn list_item(line: &str) -> Option<ListItem<'_>> {
+    let indent: usize = line.chars().take_while(|ch| ch.is_whitespace()).map(|ch| if ch == '\t' { 4 } else { 1 }).sum();
+    let trimmed = line.trim_start();
+    let depth = indent / 2;
+    if let Some(text) = ["- ", "* ", "+ "].iter().find_map(|marker| trimmed.strip_prefix(marker)) {
+        // `* * *` is a rule, not an item.
+        if is_rule(trimmed) {
+            return None;
+        }
+        return Some(ListItem { depth, marker: None, text });
+    }
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    let after = &trimmed[digits..];
+    if digits > 0 && (after.starts_with(". ") || after.starts_with(") ")) {
+        return Some(ListItem { depth, marker: Some(&trimmed[..digits + 1]), text: &after[2..] });
+    }
+    None
+}
+
+/// Renders the list starting at `lines[start]`, and returns the index of the line after it.
+///
+/// An item's text goes on until the next item, or a line that isn't indented. A blank line only
+/// ends the list if the line after it is not another item.
+fn render_list(lines: &[&str], start: usize, width: usize, out: &mut Vec<String>) -> usize {
+    let mut index = start;
+    while let Some(item) = lines.get(index).and_then(|line| list_item(line)) {
+        let mut text = String::from(item.text.trim());
+        index += 1;
+        while let Some(line) = lines.get(index) {
+            if line.trim().is_empty() || list_item(line).is_some() || !line.starts_with([' ', '\t']) {
+                break;
+            }
+            text.push(' ');
+            text.push_str(line.trim());
+            index += 1;
+        }
+
+        let indent = item.depth * 2;
+        let marker = match item.marker {
+            Some(marker) => String::from(marker),
+            None => String::from(["*", "-", "+"][item.depth % 3]),
+        };
+        let hanging = indent + marker.chars().count() + 1;
+        let wrapped = wrap(&inline(&text, NORMAL), width.saturating_sub(hanging).max(MIN_WIDTH / 2));
+        for (number, line) in wrapped.iter().enumerate() {
+            let prefix = if number == 0 {
+                format!("{}{} ", " ".repeat(indent), to_ansi(&styled(&marker, BOLD)))
+            } else {
+                " ".repeat(hanging)
+            };
+            out.push(format!("{prefix}{}", to_ansi(line)));
+        }
+
+        // Carry on over a blank line if another item follows it.
+        let next = lines[index..].iter().position(|line| !line.trim().is_empty()).map(|offset| index + offset);
+        match next {
+            Some(next) if next > index && list_item(lines[next]).is_some() => index = next,
+            _ => {}
+        }
+    }
+    index
+}
+
+/// How a table column is aligned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// Splits a table row into its cells.
+fn table_cells(line: &str) -> Vec<&str> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    line.split('|').map(str::trim).collect()
+}
+
+/// Returns the alignment of each column, if `line` is the row under a table's header.
+fn alignments(line: &str) -> Option<Vec<Alignment>> {
+    if !line.trim_start().starts_with('|') {
+        return None;
+    }
+    table_cells(line)
+        .into_iter()
+        .map(|cell| {
+            let dashes = cell.trim_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|ch| ch == '-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => Alignment::Center,
+                (false, true) => Alignment::Right,
+                _ => Alignment::Left,
+            })
+        })
+        .collect()
+}
+
+/// Renders a table, from its header row to its last row, with a border around it. Columns that
+/// would make it wider than `width` are cut short.
+fn render_table(lines: &[&str], width: usize, out: &mut Vec<String>) {
+    let alignments = alignments(lines[1]).unwrap_or_default();
+    let columns = alignments.len();
+    let rows: Vec<Vec<Vec<Cell>>> = lines
+        .iter()
+        .enumerate()
+        .filter(|(number, _)| *number != 1)
+        .map(|(number, line)| {
+            let color = if number == 0 { BOLD } else { NORMAL };
+            let mut cells: Vec<Vec<Cell>> = table_cells(line).iter().map(|cell| inline(cell, color)).collect();
+            cells.resize(columns, Vec::new());
+            cells
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = (0..columns)
+        .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0).max(1))
+        .collect();
+    // Each column takes up 3 more characters for its border and padding, and the table 1 more.
+    while widths.iter().sum::<usize>() + columns * 3 + 1 > width {
+        let Some(widest) = widths.iter_mut().filter(|width| **width > 3).max_by_key(|width| **width) else {
+            break;
+        };
+        *widest -= 1;
+    }
+
+    let border = |fill: &str| {
+        let segments: Vec<String> = widths.iter().map(|width| fill.repeat(width + 2)).collect();
+        to_ansi(&styled(&format!("+{}+", segments.join("+")), FADED))
+    };
+    out.push(border("-"));
+    for (number, row) in rows.iter().enumerate() {
+        let bar = to_ansi(&styled("|", FADED));
+        let mut line = bar.clone();
+        for ((cell, &width), &alignment) in row.iter().zip(&widths).zip(&alignments) {
+            let mut cell = cell.clone();
+            if cell.len() > width {
+                cell.truncate(width - 1);
+                cell.push(Cell { ch: '~', color: FADED });
+            }
+            let space = width - cell.len();
+            let (left, right) = match alignment {
+                Alignment::Left => (0, space),
+                Alignment::Center => (space / 2, space - space / 2),
+                Alignment::Right => (space, 0),
+            };
+            line.push_str(&format!(" {}{}{} {}", " ".repeat(left), to_ansi(&cell), " ".repeat(right), bar));
+        }
+        out.push(line);
+        if number == 0 {
+            out.push(border("="));
+        }
+    }
+    out.push(border("-"));
+}
+
+/// Gives every character of `text` the same color.
+fn styled(text: &str, color: u8) -> Vec<Cell> {
+    text.chars().map(|ch| Cell { ch, color }).collect()
+}
+
+/// Parses the inline markup in `text`: emphasis, code, links and escapes.
+///
+/// ### params:
+/// - `color`: the color of plain text, like a heading's.
+fn inline(text: &str, color: u8) -> Vec<Cell> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut cells = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let ch = chars[index];
+        let rest = &chars[index..];
+        let previous = index.checked_sub(1).map(|previous| chars[previous]);
+
+        if ch == '\\' && rest.get(1).is_some_and(char::is_ascii_punctuation) {
+            cells.push(Cell { ch: rest[1], color });
+            index += 2;
+            continue;
+        }
+        if ch == '`' {
+            let ticks = rest.iter().take_while(|&&ch| ch == '`').count();
+            let delimiter = vec!['`'; ticks];
+            if let Some(end) = find(rest, ticks, &delimiter) {
+                let code: String = rest[ticks..end].iter().collect();
+                cells.extend(styled(code.trim(), CODE));
+                index += end + ticks;
+                continue;
+            }
+        }
+        if ch == '!' && rest.get(1) == Some(&'[') {
+            if let Some((label, _, length)) = link(&rest[1..]) {
+                cells.extend(styled(&format!("[image: {label}]"), FADED));
+                index += 1 + length;
+                continue;
+            }
+        }
+        if ch == '[' {
+            if let Some((label, url, length)) = link(rest) {
+                cells.extend(inline(&label, LINK));
+                if url != label {
+                    cells.extend(styled(&format!(" ({url})"), FADED));
+                }
+                index += length;
+                continue;
+            }
+        }
+        if ch == '<' {
+            if let Some(end) = rest.iter().position(|&ch| ch == '>') {
+                let url: String = rest[1..end].iter().collect();
+                if url.contains("://") || url.starts_with("mailto:") {
+                    cells.extend(styled(&url, LINK));
+                    index += end + 1;
+                    continue;
+                }
+            }
+        }
+        // Emphasis: the longest delimiter that has a closing match wins.
+        let emphasis = [("**", BOLD), ("__", BOLD), ("~~", FADED), ("*", ITALIC), ("_", ITALIC)];
+        let matched = emphasis.iter().find_map(|&(delimiter, style)| {
+            let delimiter: Vec<char> = delimiter.chars().collect();
+            if !rest.starts_with(&delimiter) || rest.get(delimiter.len()).is_none_or(|ch| ch.is_whitespace()) {
+                return None;
+            }
+            // `snake_case` has no emphasis in it.
+            if delimiter[0] == '_' && previous.is_some_and(char::is_alphanumeric) {
+                return None;
+            }
+            let end = find(rest, delimiter.len() + 1, &delimiter)?;
+            Some((delimiter.len(), end, style))
+        });
+        if let Some((length, end, style)) = matched {
+            let inner: String = rest[length..end].iter().collect();
+            cells.extend(inline(&inner, style));
+            index += end + length;
+            continue;
+        }
+
+        cells.push(Cell { ch, color });
+        index += 1;
+    }
+    cells
+}
+
+/// Returns where `delimiter` next appears in `chars`, looking from `from` on.
+fn find(chars: &[char], from: usize, delimiter: &[char]) -> Option<usize> {
+    (from..chars.len()).find(|&start| chars[start..].starts_with(delimiter))
+}
+
+/// Parses a `[label](url)` link at the start of `chars`.
+///
+/// ### returns:
+/// - `Some((label, url, length))`: the link, and how many characters it takes up.
+fn link(chars: &[char]) -> Option<(String, String, usize)> {
+    let close = chars.iter().position(|&ch| ch == ']')?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    let end = close + 2 + chars[close + 2..].iter().position(|&ch| ch == ')')?;
+    let label = chars[1..close].iter().collect();
+    let url = chars[close + 2..end].iter().collect();
+    Some((label, url, end + 1))
+}
+
+/// Wraps cells into lines of at most `width`, breaking between words, or inside a word that is
+/// too long for a line of its own.
+fn wrap(cells: &[Cell], width: usize) -> Vec<Vec<Cell>> {
+    let mut lines = Vec::new();
+    let mut line: Vec<Cell> = Vec::new();
+    for word in cells.split(|cell| cell.ch == ' ').filter(|word| !word.is_empty()) {
+        if !line.is_empty() && line.len() + 1 + word.len() > width {
+            lines.push(core::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            // Keep the space inside a styled run, like a bold phrase, so it doesn't switch colors
+            // back and forth.
+            let before = line[line.len() - 1].color;
+            let color = if before == word[0].color { before } else { NORMAL };
+            line.push(Cell { ch: ' ', color });
+        }
+        for &cell in word {
+            if line.len() == width {
+                lines.push(core::mem::take(&mut line));
+            }
+            line.push(cell);
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Turns cells into text, with ANSI color codes where the color changes, and back to normal at
+/// the end.
+fn to_ansi(cells: &[Cell]) -> String {
+    let mut text = String::new();
+    let mut current = NORMAL;
+    for cell in cells {
+        if cell.color != current {
+            text.push_str(&format!("\x1b[{}m", cell.color));
+            current = cell.color;
+        }
+        text.push(cell.ch);
+    }
+    if current != NORMAL {
+        text.push_str("\x1b[0m");
+    }
+    text
+}
@@ -1,317 +1,307 @@
+pub mod calc;
+pub mod command;
+mod commands;
+pub mod completion;
+pub mod editor;
+pub mod environment;
+pub mod exec;
+pub mod history;
+pub mod lexer;
+pub mod login;
+pub mod markdown;
+pub mod pager;
+pub mod parser;
+pub mod prompt;
+pub mod registry;
+pub mod script;
+pub mod session;
+
 use crate::{
     framebuffer::ConsoleColor,
-    framebuffer::global_writer::{clear_screen, print_fmt},
     framebuffer::color::ColoredWriting,
+    framebuffer::global_writer::clear_screen,
+    input::{self, Focus, Kinds},
+    keyboard::KeyPress,
     print,
-    println
+    println,
+    serial_print
 };
 use alloc::{
     format,
-    string::String,
-    vec::Vec
+    string::{String, ToString}
 };
-use spin::{Lazy, Mutex};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use command::{ExitStatus, Output};
+use completion::Completion;
+use pc_keyboard::{DecodedKey, KeyCode};
+use session::Session;
+
+/// Width of the prompt currently on screen, in pixels.
+///
+/// The framebuffer writer reads this to stop a backspace from erasing into the prompt. It lives
+/// outside of [Shell] so the writer doesn't need a way to reach the shell.
+pub static PROMPT_WIDTH: AtomicUsize = AtomicUsize::new(0);
+
+/// State of an in-progress Ctrl+R reverse incremental search.
+struct ReverseSearch {
+    /// What the user has typed so far.
+    query: String,
+    /// Index of the history entry currently matched.
+    matched: Option<usize>,
+    /// The line being edited when the search started, restored if it is cancelled.
+    original: String,
+    /// Number of characters the search line currently takes up after the prompt.
+    rendered: usize,
+}
 
 pub struct Shell {
     buffer: String,
-    prompt: String,
-    name: String,
-    pub len: usize,
-    err: u8,
-    command: String,
-    args: Vec<String>,
+    session: Session,
+    search: Option<ReverseSearch>,
+    /// The focus the shell keeps underneath everything else, for the keys typed at its prompt.
+    input: Focus,
 }
 
 impl Shell {
     fn new() -> Self {
-        let prompt = String::from("WHAT IS YOUR NAME? ");
         Self {
             buffer: String::new(),
-            prompt: prompt.clone(),
-            name: String::new(),
-            len: (prompt.chars().count() + 1) * 10,
-            err: 0,
-            command: String::new(),
-            args: Vec::new(),
+            session: Session::new(),
+            search: None,
+            input: input::focus(Kinds::KEYS),
         }
     }
 
-    pub fn append(&mut self, input: char) {
-        self.buffer.push(input);
-    }
-
-    pub fn init(&mut self) {
-        match self.err {
-            0 => print!("{}", self.prompt.fg(ConsoleColor::BrightWhite)),
-            1 => print!("{}", self.prompt.fg(ConsoleColor::Red)),
-            2 => print!("{}", self.prompt.fg(ConsoleColor::Yellow)),
-            _ => print!("{}", self.prompt.fg(ConsoleColor::BrightWhite)),
+    /// Handles a key press from the keyboard: editing the line, recalling history, and running
+    /// the command once Enter is pressed.
+    pub fn handle_key(&mut self, press: KeyPress) {
+        let key = press.key;
+        if self.search.is_some() {
+            self.handle_search_key(key);
+            return;
+        }
+        match key {
+            DecodedKey::Unicode('\n') => {
+                println!();
+                self.exec();
+            }
+            // Ctrl+W, or Ctrl+Backspace
+            DecodedKey::Unicode('\x17') => self.delete_word(),
+            DecodedKey::Unicode('\x08') if press.ctrl && press.code == KeyCode::Backspace => self.delete_word(),
+            // Ctrl+U
+            DecodedKey::Unicode('\x15') => self.replace_input(""),
+            // Ctrl+C: give up on the line, and start a new one.
+            DecodedKey::Unicode('\x03') => {
+                println!("^C");
+                self.buffer.clear();
+                self.init();
+            }
+            // Ctrl+L: clear the screen, keeping the line being typed.
+            DecodedKey::Unicode('\x0c') => {
+                clear_screen();
+                self.init();
+                print!("{}", self.buffer);
+            }
+            DecodedKey::Unicode('\x08') => {
+                if self.buffer.pop().is_some() {
+                    print!("\x08");
+                }
+            }
+            DecodedKey::Unicode('\t') => self.complete(),
+            // Ctrl+R
+            DecodedKey::Unicode('\x12') => self.start_search(),
+            DecodedKey::Unicode(char) if !char.is_control() => {
+                self.buffer.push(char);
+                print!("{}", char);
+            }
+            DecodedKey::RawKey(KeyCode::ArrowUp) => {
+                if let Some(line) = self.session.history.older(&self.buffer).map(String::from) {
+                    self.replace_input(&line);
+                }
+            }
+            DecodedKey::RawKey(KeyCode::ArrowDown) => {
+                if let Some(line) = self.session.history.newer().map(String::from) {
+                    self.replace_input(&line);
+                }
+            }
+            DecodedKey::RawKey(key) => {
+                serial_print!("{:#?}  ", key);
+            }
+            _ => {}
         }
     }
 
-    fn parse_command(&mut self) {
-        let parts: Vec<String> = self.buffer.trim().split_whitespace().map(String::from).collect();
-        if !parts.is_empty() {
-            self.command = parts[0].clone();
-            self.args = parts[1..].to_vec();
-        } else {
-            self.command.clear();
-            self.args.clear();
+    /// Completes the word being typed, or lists the candidates if there is more than one.
+    fn complete(&mut self) {
+        match completion::complete(&self.buffer, &registry::all()) {
+            Completion::None => {}
+            Completion::Insert(text) => {
+                self.buffer.push_str(&text);
+                print!("{}", text);
+            }
+            Completion::Ambiguous(candidates) => {
+                println!();
+                println!("{}", candidates.join("  "));
+                self.init();
+                print!("{}", self.buffer);
+            }
         }
     }
 
-    pub fn exec(&mut self) {
-        self.parse_command();
-
-        if self.prompt == "WHAT IS YOUR NAME? " {
-            println!("Hello, {}!", self.buffer);
-            self.prompt = format!("{}@PortfoliOS -> # ", self.buffer);
-            self.name = self.buffer.clone();
-            self.buffer.clear();
-            self.len = (self.prompt.chars().count() + 1) * 10;
-        } else {
-            self.err = 0;
-
-            match self.command.as_str() {
-                "whoami" => {
-                    println!("\
-                    Hello there {}! I am Agamjot Singh Bindra, a student of 11th grade, at Bal Bharati Public School, and the creator of \
-                PortfoliOS, and it's shell, AgamShell(shortened to ASH). ", self.name);
-                },
-                "projects" => {let print = "
-I have worked on the following projects:
-1. PortfoliOS - A simple OS, with a shell, and a framebuffer.
-2. PortfoliOS-CLI - A simple CLI, for my portfolio (indev)
-3. CareerCompass - A Career Guidance website, made using react, nodejs, and firebase.
-4. AI-Snake-Game - A simple snake game, with AI, made using python.(I am not good at naming things...)
-";
-                    println!("{}" ,print);},
-                "clear" => {
-                    clear_screen();
-                },
-                "whatilike" => {
-                    let print = "
-I like the following things:
-1. Coding - I love coding, and I am learning new things every day.
-2. Gaming - I like to play games, such as Kerbal Space Program, even though with school, and all, I don't really have the time to do so.
-3. 8TXt745lcHnuFVncMB3em5enK0ex63Sa \x1b[31m(ERROR: MEMORY_OVERFLOW... TERMINATING USER)\x1b[0m
-";
-                    println!("{}" ,print);
-                    for _ in 0..5e6 as u128 {
-                        core::hint::spin_loop();
-                    }
-                    println!("That was a joke... I am not a hacker... Promise...");
-                    println!("3. was supposed to be kernel debugging...");
-                    for _ in 0..5e5 as u128 {
-                        core::hint::spin_loop();
-                    }
-                    let print = "                 ___-----------___
-           __--~~                 ~~--__
-       _-~~                             ~~-_
-    _-~                                     ~-_
-   /                                           \\
-  |                                             |
- |                                               |
- |                                               |
-|                                                 |
-|                                                 |
-|                                                 |
- |                                               |
- |  |    _-------_               _-------_    |  |
- |  |  /~         ~\\           /~         ~\\  |  |
-  ||  |             |         |             |  ||
-  || |               |       |               | ||
-  || |              |         |              | ||
-  |   \\_           /           \\           _/   |
- |      ~~--_____-~    /~V~\\    ~-_____--~~      |
- |                    |     |                    |
-|                    |       |                    |
-|                    |  /^\\  |                    |
- |                    ~~   ~~                    |
-  \\_         _                       _         _/
-    ~--____-~ ~\\                   /~ ~-____--~
-         \\     /\\                 /\\     /
-          \\    | ( ,           , ) |    /
-           |   | (~(__(  |  )__)~) |   |
-            |   \\/ (  (~~|~~)  ) \\/   |
-             |   |  [ [  |  ] ]  /   |
-              |                     |
-               \\                   /
-                ~-_             _-~
-                   ~--___-___--~".fg(ConsoleColor::Red);
-                    println!("{}" ,print);
-                },
-                "help" => {
-                    println!("Available commands: whoami, projects, whatilike, clear, help, echo, shutdown, exit, portfoliofetch");
-                    println!("Try running ls...")
-                },
-                "echo" => self.err = self.handle_echo(),
-                "portfoliofetch" => {
-                    println!("{}" ,"  _____           _    __      _ _  ____   _____ 
- |  __ \\         | |  / _|    | (_)/ __ \\ / ____|
- | |__) |__  _ __| |_| |_ ___ | |_| |  | | (___  
- |  ___/ _ \\| '__| __|  _/ _ \\| | | |  | |\\___ \\ 
- | |  | (_) | |  | |_| || (_) | | | |__| |____) |
- |_|   \\___/|_|   \\__|_| \\___/|_|_|\\____/|_____/".fg(ConsoleColor::BrightRed));
-                        println!("Uptime: idk... don't you have a clock?");
-                        println!("Kernel Version: 0.0.1");
-                        println!("PortfoliOS Version: 0.0.1");
-                        println!("Developer: Agamjot Singh Bindra");
-                        println!("Website: ummm... good question...");
-                }
-
+    /// Deletes the word before the end of the line, and any spaces after it, as Ctrl+W does in
+    /// other shells.
+    fn delete_word(&mut self) {
+        let kept = self.buffer.trim_end().trim_end_matches(|ch: char| !ch.is_whitespace()).len();
+        let removed = self.buffer[kept..].chars().count();
+        self.buffer.truncate(kept);
+        print!("{}", "\x08".repeat(removed));
+    }
 
-                "exit" | "execute66" => {
-                    let format = "War! The Republic is crumbling under attacks by the ruthless Sith Lord, Count Dooku. There are heroes on both sides. Evil is everywhere.
-In a stunning move, the fiendish droid leader, General Grievous, has swept into the Republic capital and kidnapped Chancellor Palpatine, leader of the Galactic Senate.
-As the Separatist Droid Army attempts to flee the besieged capital with their valuable hostage, two Jedi Knights lead a desperate mission to rescue the captive Chancellor...\n";
+    /// Replaces the line being edited, both in the buffer and on screen.
+    fn replace_input(&mut self, text: &str) {
+        print!("{}", "\x08".repeat(self.buffer.chars().count()));
+        self.buffer = text.to_string();
+        print!("{}", self.buffer);
+    }
 
+    fn start_search(&mut self) {
+        let rendered = self.buffer.chars().count();
+        self.search = Some(ReverseSearch {
+            query: String::new(),
+            matched: None,
+            original: self.buffer.clone(),
+            rendered,
+        });
+        self.render_search();
+    }
 
-                for char in format.chars(){
-                    print_fmt(format_args!("{}", char), ConsoleColor::BrightYellow, ConsoleColor::Black);
-                    for _ in 0..100_000 {
-                        core::hint::spin_loop();
-                    }
-                }
-                println!("(c) Whoever made star wars episode 3...");
-                }
-                "rename" => self.err = self.handle_rename(),
-                "bye" => {
-                    println!("Bye {}! See you later!", self.name);
-                    println!("Exiting...");
-                    for _ in 0..5e6 as u128 {
-                        core::hint::spin_loop();
-                    }
-                    println!("Exiting... (c) Agamjot Singh Bindra");
-                    println!("Bye!");
-                }
-                "calc" => self.err = self.handle_calc(),
-                _ => {
-                    if ["ls", "touch", "cd", "mkdir", "cat"].contains(&self.command.as_str()) {
-                        println!("Bro... there is no filesystem... <add skull emoji here when emojis are supported... //todo>");
-                        self.err = 2;
-                    } else {
-                        self.err = 1;
-                        println!("{} is not a valid command", self.buffer);
-                    }
+    fn handle_search_key(&mut self, key: DecodedKey) {
+        let Some(search) = self.search.as_mut() else { return };
+        match key {
+            // Ctrl+R again: look for an older match.
+            DecodedKey::Unicode('\x12') => {
+                let before = search.matched.unwrap_or(self.session.history.len());
+                if let Some(index) = self.session.history.search(&search.query, before) {
+                    search.matched = Some(index);
                 }
             }
+            DecodedKey::Unicode('\x08') => {
+                search.query.pop();
+                search.matched = self.session.history.search(&search.query, self.session.history.len());
+            }
+            DecodedKey::Unicode('\n') => {
+                self.finish_search(true);
+                println!();
+                self.exec();
+                return;
+            }
+            // Escape or Ctrl+G: give up, and put back what was being typed.
+            DecodedKey::Unicode('\x1b') | DecodedKey::Unicode('\x07') => {
+                self.finish_search(false);
+                return;
+            }
+            DecodedKey::Unicode(char) if !char.is_control() => {
+                search.query.push(char);
+                // Keep the current match if it still matches, like bash does.
+                let before = search.matched.map_or(self.session.history.len(), |index| index + 1);
+                search.matched = self.session.history.search(&search.query, before);
+            }
+            // Any other key accepts the match for editing.
+            DecodedKey::Unicode(_) | DecodedKey::RawKey(_) => {
+                self.finish_search(true);
+                return;
+            }
         }
-        self.buffer.clear();
-        self.init();
-    }
-
-    fn handle_rename(&mut self) -> u8 {
-        if self.args.is_empty() {
-            println!("Usage: rename <old_name> <new_name>");
-            return 1;
-        }
-        if self.args.len() != 2 {
-            println!("Usage: rename <old_name> <new_name>");
-            return 1;
-        }
-        let old_name = &self.args[0];
-        let new_name = &self.args[1];
-        println!("Renaming {} to {}", old_name, new_name);
-        if self.name == *old_name {
-            self.name = new_name.clone();
-            println!("Renamed {} to {}", old_name, new_name);
-        } else {
-            println!("{} is not your name", old_name);
-        }
-        0
+        self.render_search();
     }
 
-    fn handle_calc(&self) -> u8{
-        if self.args.is_empty(){
-            println!("Usage: calc <num1> <operator> <num2>");
-            println!("Operators: +, -, *, /");
-            return 1;
-        }
-        if self.args.len() != 3 {
-            println!("Usage: calc <num1> <operator> <num2>");
-            return 1;
-        }
-        let num1: f64 = self.args[0].parse().unwrap_or_else(
-            |_| {
-                println!("Invalid number: {}", self.args[0]);
-                0.0
-            }
-        );
-        let num2: f64 = self.args[2].parse().unwrap_or_else(
-            |_| {
-                println!("Invalid number: {}", self.args[2]);
-                0.0
-            }
+    /// Redraws the reverse search line in place of the input.
+    fn render_search(&mut self) {
+        let Some(search) = self.search.as_mut() else { return };
+        let found = search.matched.and_then(|index| self.session.history.get(index));
+        let line = format!(
+            "({}reverse-i-search)`{}': {}",
+            if found.is_none() && !search.query.is_empty() { "failed " } else { "" },
+            search.query,
+            found.unwrap_or("")
         );
+        print!("{}{}", "\x08".repeat(search.rendered), line);
+        search.rendered = line.chars().count();
+    }
 
-        match self.args[1].as_str() {
-            "+" => println!("{} + {} = {}", num1, num2, num1 + num2),
-            "-" => println!("{} - {} = {}", num1, num2, num1 - num2),
-            "*" => println!("{} * {} = {}", num1, num2, num1 * num2),
-            "/" => {
-                if num2 == 0.0 {
-                    println!("Division by zero is not allowed");
-                } else {
-                    println!("{} / {} = {}", num1, num2, num1 / num2);
-                }
-            },
-            _ => {
-                println!("Invalid operator: {}", self.args[1]);
-            }
-
+    /// Leaves reverse search mode.
+    ///
+    /// ### params:
+    /// - `accept`: if true, the matched command becomes the line being edited; otherwise the line
+    ///   from before the search is restored.
+    fn finish_search(&mut self, accept: bool) {
+        let Some(search) = self.search.take() else { return };
+        let line = match search.matched.and_then(|index| self.session.history.get(index)) {
+            Some(matched) if accept => matched.to_string(),
+            _ => search.original,
         };
-        0
+        print!("{}", "\x08".repeat(search.rendered));
+        self.buffer = line;
+        print!("{}", self.buffer);
+    }
+
+    /// Returns the prompt to show: the user's `PS1`, or the [default](prompt::DEFAULT_PS1).
+    fn prompt(&self) -> String {
+        let ps1 = self.session.environment.get("PS1").unwrap_or(prompt::DEFAULT_PS1);
+        prompt::render(ps1, &self.session, self.session.status)
     }
 
-    fn handle_echo(&self) -> u8 {
-        if self.args.is_empty() {
-            println!("Usage: echo [-c <color>] <message>");
-            return 1;
+    pub fn init(&mut self) {
+        let prompt = self.prompt();
+        PROMPT_WIDTH.store((prompt::visible_len(&prompt) + 1) * 10, Ordering::Relaxed);
+        match self.session.status {
+            ExitStatus::SUCCESS => print!("{}", prompt.fg(ConsoleColor::BrightWhite)),
+            ExitStatus::WARNING => print!("{}", prompt.fg(ConsoleColor::Yellow)),
+            _ => print!("{}", prompt.fg(ConsoleColor::Red)),
         }
+    }
 
-        // Check for the color switch: echo -c <color> <message>
-        if self.args[0] == "-c" && self.args.len() > 2 {
-            let color_input = self.args[1].to_lowercase();
-            let color = match color_input.as_str() {
-                "black" => ConsoleColor::Black,
-                "red" => ConsoleColor::Red,
-                "green" => ConsoleColor::Green,
-                "yellow" => ConsoleColor::Yellow,
-                "blue" => ConsoleColor::Blue,
-                "magenta" => ConsoleColor::Magenta,
-                "cyan" => ConsoleColor::Cyan,
-                "white" => ConsoleColor::White,
-                "brightblack" => ConsoleColor::BrightBlack,
-                "brightred" => ConsoleColor::BrightRed,
-                "brightgreen" => ConsoleColor::BrightGreen,
-                "brightyellow" => ConsoleColor::BrightYellow,
-                "brightblue" => ConsoleColor::BrightBlue,
-                "brightmagenta" => ConsoleColor::BrightMagenta,
-                "brightcyan" => ConsoleColor::BrightCyan,
-                "brightwhite" => ConsoleColor::BrightWhite,
-                _ => {
-                    println!("Invalid color. Supported: black, red, green, yellow, blue, magenta, cyan, white, brightblack, brightred, brightgreen, brightyellow, brightblue, brightmagenta, brightcyan, brightwhite.");
-                    return 1;
+    pub fn exec(&mut self) {
+        match self.session.history.expand(&self.buffer) {
+            Ok(expanded) => {
+                if let Some(expanded) = expanded {
+                    println!("{}", expanded);
+                    self.buffer = expanded;
                 }
-            };
-            let message = self.args[2..].join(" ");
-            println!("{}", message.fg(color));
-        } else {
-            let message = self.args.join(" ");
-            println!("{}", message);
+                self.session.history.push(&self.buffer);
+                self.session.save_history();
+                let line = self.buffer.clone();
+                exec::run_line(&mut self.session, &line, &mut Output::Screen);
+            }
+            Err(message) => {
+                println!("{}", message);
+                self.session.status = ExitStatus::FAILURE;
+            }
+        }
+        self.buffer.clear();
+        // `logout` may have ended the session, in which case the login screen comes next.
+        if self.session.is_active() {
+            self.init();
         }
-        0
     }
 
-    pub fn pop(&mut self) {
-        if !self.buffer.is_empty() {
-            self.buffer.pop();
+}
+
+/// Runs the shell: shows the login screen, then handles keys as they are pressed until the user
+/// logs out, forever.
+///
+/// This is the kernel's main task. The keyboard interrupt handler only pushes keys, and they are
+/// read from the shell's [input] queue here, so commands run with interrupts enabled, and can take
+/// as long as they like without holding up the rest of the kernel.
+pub fn run() -> ! {
+    let mut shell = Shell::new();
+    loop {
+        let user = login::login();
+        shell.session.start(&user);
+        shell.init();
+        while shell.session.is_active() {
+            let press = shell.input.read_key_press();
+            shell.handle_key(press);
         }
     }
 }
-
-
-pub static GLOBAL_SHELL: Lazy<Mutex<Shell>> = Lazy::new(|| {
-    Mutex::new(Shell::new())
-});
@@ -0,0 +1,337 @@
+//! A full-screen pager, like `less`, for text that doesn't fit on the screen.
+//!
+//! The text is split into lines as wide as the screen up front, carrying its colors over from one
+//! line to the next, so any part of it can be drawn on its own.
+//!
+//! Commands can show text in it with [show], and ones that [page their
+//! output](crate::shell::command::Command::pages_output) end up in it through an [AutoPager] if
+//! they print more than fits on the screen.
+use crate::{
+    framebuffer::{self, global_writer::with_writer},
+    input::{self, Focus, Kinds},
+    print,
+    shell::{command::Output, login}
+};
+use alloc::{
+    format,
+    string::String,
+    vec::Vec
+};
+use core::fmt::Write;
+use pc_keyboard::{DecodedKey, KeyCode};
+
+/// The color of the status line at the bottom of the screen.
+const STATUS_COLOR: &str = "\x1b[93m";
+
+/// Shows `text` in the pager if `out` is the screen. Otherwise, like when it is piped into
+/// another command, it is written to `out` as it is.
+///
+/// ### params:
+/// - `title`: what the text is, like a file name, for the status line.
+pub fn show(text: &str, title: &str, out: &mut Output) {
+    match out {
+        Output::Screen => Pager::new(text, title).run(),
+        Output::Buffer(_) | Output::Paged(_) => out.write_str(text),
+    }
+}
+
+/// Output that goes straight to the screen until it would scroll the first of it off, and is
+/// kept back from then on, to be shown in the pager once the command is done.
+///
+/// This way output that fits is printed as usual, and output that comes slowly, like a section
+/// with pauses in it, is still seen as it comes, up to a screenful.
+pub struct AutoPager {
+    /// Everything written so far, including what has been printed.
+    text: String,
+    /// Set once there is more than fits on the screen, when output stops being printed.
+    overflowed: bool,
+    /// How many lines the output has taken up on the screen so far.
+    lines: usize,
+    /// How many characters are on the last of them.
+    column: usize,
+    height: usize,
+    columns: usize,
+}
+
+impl AutoPager {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            overflowed: false,
+            lines: 0,
+            column: 0,
+            // Leave a line for the prompt that comes after the output.
+            height: framebuffer::rows().saturating_sub(1).max(1),
+            columns: framebuffer::columns().max(1),
+        }
+    }
+
+    /// Prints `text`, or holds it back if there is too much to fit on the screen.
+    pub fn write(&mut self, text: &str) {
+        self.text.push_str(text);
+        if self.overflowed {
+            return;
+        }
+        let mut chars = text.chars();
+        while let Some(ch) = chars.next() {
+            match ch {
+                // Color codes take up no room.
+                '\x1b' => _ = chars.by_ref().find(|&ch| ch == 'm'),
+                '\n' => {
+                    self.lines += 1;
+                    self.column = 0;
+                }
+                _ => {
+                    if self.column == self.columns {
+                        self.lines += 1;
+                        self.column = 0;
+                    }
+                    self.column += 1;
+                }
+            }
+        }
+        if self.lines >= self.height {
+            self.overflowed = true;
+        } else {
+            print!("{text}");
+        }
+    }
+
+    /// Shows everything that was written in the pager, if not all of it was printed.
+    ///
+    /// ### params:
+    /// - `title`: what the output is, like the name of the command it came from.
+    pub fn finish(self, title: &str) {
+        if self.overflowed {
+            Pager::new(&self.text, title).run();
+        }
+    }
+}
+
+impl Default for AutoPager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Pager<'a> {
+    title: &'a str,
+    /// The lines to show, each one no wider than the screen, with color codes.
+    lines: Vec<String>,
+    /// The same lines without their color codes, and in lowercase, to search.
+    plain: Vec<String>,
+    /// The index of the line at the top of the screen.
+    top: usize,
+    /// How many lines of text fit on the screen, above the status line.
+    height: usize,
+    columns: usize,
+    /// What was last searched for, so `n` and `N` can look for it again.
+    pattern: Option<String>,
+    /// Shown in the status line until the next key is pressed, like "Pattern not found".
+    message: Option<String>,
+    input: Focus,
+}
+
+impl<'a> Pager<'a> {
+    fn new(text: &str, title: &'a str) -> Self {
+        let columns = framebuffer::columns();
+        let lines = split_lines(text, columns);
+        let plain = lines.iter().map(|line| strip_colors(line).to_lowercase()).collect();
+        Self {
+            title,
+            lines,
+            plain,
+            top: 0,
+            height: framebuffer::rows().saturating_sub(1).max(1),
+            columns,
+            pattern: None,
+            message: None,
+            input: input::focus(Kinds::KEYS),
+        }
+    }
+
+    /// The furthest down the top line can go: where the last line is at the bottom of the screen.
+    fn last_top(&self) -> usize {
+        self.lines.len().saturating_sub(self.height)
+    }
+
+    fn scroll_to(&mut self, top: usize) {
+        self.top = top.min(self.last_top());
+    }
+
+    /// Handles keys until `q` is pressed, then clears the screen for the shell.
+    fn run(&mut self) {
+        loop {
+            self.draw();
+            let key = self.input.read_key();
+            self.message = None;
+            let page = self.height;
+            match key {
+                // q, Escape or Ctrl+C
+                DecodedKey::Unicode('q' | 'Q' | '\x1b' | '\x03') => break,
+                DecodedKey::Unicode('j' | 'e' | '\n') | DecodedKey::RawKey(KeyCode::ArrowDown) => {
+                    self.scroll_to(self.top + 1);
+                }
+                DecodedKey::Unicode('k' | 'y') | DecodedKey::RawKey(KeyCode::ArrowUp) => {
+                    self.scroll_to(self.top.saturating_sub(1));
+                }
+                DecodedKey::Unicode(' ' | 'f') | DecodedKey::RawKey(KeyCode::PageDown) => {
+                    self.scroll_to(self.top + page);
+                }
+                DecodedKey::Unicode('b') | DecodedKey::RawKey(KeyCode::PageUp) => {
+                    self.scroll_to(self.top.saturating_sub(page));
+                }
+                DecodedKey::Unicode('d') => self.scroll_to(self.top + page / 2),
+                DecodedKey::Unicode('u') => self.scroll_to(self.top.saturating_sub(page / 2)),
+                DecodedKey::Unicode('g' | '<') | DecodedKey::RawKey(KeyCode::Home) => self.scroll_to(0),
+                DecodedKey::Unicode('G' | '>') | DecodedKey::RawKey(KeyCode::End) => self.scroll_to(usize::MAX),
+                DecodedKey::Unicode('/') => self.search(true),
+                DecodedKey::Unicode('?') => self.search(false),
+                DecodedKey::Unicode('n') => self.find_next(true),
+                DecodedKey::Unicode('N') => self.find_next(false),
+                DecodedKey::Unicode('h') => {
+                    let help = "q quit, j/k line, space/b page, d/u half page, g/G top/bottom, / ? search, n/N next";
+                    self.message = Some(String::from(help));
+                }
+                _ => {}
+            }
+        }
+        framebuffer::global_writer::clear_screen();
+    }
+
+    /// Reads a pattern on the status line, and goes to the first line after (or before) the top one
+    /// that has it. An empty pattern searches for the last one again.
+    fn search(&mut self, forward: bool) {
+        self.draw_lines();
+        with_writer(|writer| writer.set_cursor_position(0, self.height));
+        let pattern = login::read_line(if forward { "/" } else { "?" }, true);
+        if !pattern.is_empty() {
+            self.pattern = Some(pattern.to_lowercase());
+        }
+        self.find_next(forward);
+    }
+
+    /// Goes to the next line with the last pattern in it, searching down the text, or up it if
+    /// `forward` is false. The line found goes at the top of the screen.
+    fn find_next(&mut self, forward: bool) {
+        let Some(pattern) = self.pattern.as_deref() else {
+            self.message = Some(String::from("No previous pattern"));
+            return;
+        };
+        let found = if forward {
+            (self.top + 1..self.plain.len()).find(|&index| self.plain[index].contains(pattern))
+        } else {
+            (0..self.top).rev().find(|&index| self.plain[index].contains(pattern))
+        };
+        match found {
+            // Searching down can find a line the top can't scroll as far as, so say where it is.
+            Some(index) => {
+                self.scroll_to(index);
+                if index != self.top {
+                    self.message = Some(format!("Found on line {}", index + 1));
+                }
+            }
+            None => self.message = Some(String::from("Pattern not found")),
+        }
+    }
+
+    /// Redraws the lines on screen, and the status line under them.
+    fn draw(&self) {
+        self.draw_lines();
+        let status = match &self.message {
+            Some(message) => message.clone(),
+            None => {
+                let last = (self.top + self.height).min(self.lines.len());
+                let end = if last == self.lines.len() { " (END)" } else { "" };
+                format!("{} lines {}-{}/{}{}  h for help, q to quit", self.title, self.top + 1, last, self.lines.len(), end)
+            }
+        };
+        let status: String = status.chars().take(self.columns).collect();
+        with_writer(|writer| {
+            writer.set_cursor_position(0, self.height);
+            _ = write!(writer, "{STATUS_COLOR}{status}\x1b[0m");
+        });
+    }
+
+    /// Clears the screen, and draws the lines that are on it.
+    fn draw_lines(&self) {
+        with_writer(|writer| {
+            _ = writer.clear_screen();
+            for (row, line) in self.lines.iter().skip(self.top).take(self.height).enumerate() {
+                writer.set_cursor_position(0, row);
+                _ = writer.write_str(line);
+            }
+        });
+    }
+}
+
+/// Splits `text` into lines of at most `columns` characters, not counting color codes.
+///
+/// A line that is split keeps its colors: the codes in effect where it was split start the next
+/// part, and every line that changes colors ends by going back to normal. Tabs become spaces, and
+/// characters the console's font doesn't have become `?`.
+fn split_lines(text: &str, columns: usize) -> Vec<String> {
+    let columns = columns.max(1);
+    let mut lines = Vec::new();
+    // The color codes in effect, since the last reset.
+    let mut active = String::new();
+    for source in text.lines() {
+        let mut line = active.clone();
+        let mut width = 0;
+        let mut chars = source.chars().peekable();
+        while let Some(ch) = chars.next() {
+            if ch == '\x1b' && chars.peek() == Some(&'[') {
+                let mut code = String::from("\x1b");
+                for ch in chars.by_ref() {
+                    code.push(ch);
+                    if ch == 'm' {
+                        break;
+                    }
+                }
+                if code == "\x1b[m" || code == "\x1b[0m" {
+                    active.clear();
+                } else {
+                    active.push_str(&code);
+                }
+                line.push_str(&code);
+                continue;
+            }
+            let (ch, count) = match ch {
+                '\t' => (' ', 4 - width % 4),
+                ch if ch.is_ascii() && !ch.is_ascii_control() => (ch, 1),
+                ch if ch.is_control() => continue,
+                _ => ('?', 1),
+            };
+            for _ in 0..count {
+                if width == columns {
+                    if !active.is_empty() {
+                        line.push_str("\x1b[0m");
+                    }
+                    lines.push(core::mem::replace(&mut line, active.clone()));
+                    width = 0;
+                }
+                line.push(ch);
+                width += 1;
+            }
+        }
+        if !active.is_empty() {
+            line.push_str("\x1b[0m");
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Returns `line` without its color codes.
+fn strip_colors(line: &str) -> String {
+    let mut plain = String::new();
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            chars.by_ref().find(|&ch| ch == 'm');
+        } else {
+            plain.push(ch);
+        }
+    }
+    plain
+}
@@ -0,0 +1,113 @@
+use crate::shell::lexer::{self, RedirectKind, SyntaxError, Token, Word};
+use alloc::vec::Vec;
+
+/// A redirection of a command's input or output to a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    pub kind: RedirectKind,
+    /// The file, still to be expanded.
+    pub target: Word,
+}
+
+/// One command in a pipeline: its words, and where its input and output are redirected.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stage {
+    pub words: Vec<Word>,
+    pub redirects: Vec<Redirect>,
+}
+
+impl Stage {
+    fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.redirects.is_empty()
+    }
+}
+
+/// When a pipeline in a list runs, depending on the status of what ran before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    /// The first pipeline, or one after a `;`: it always runs.
+    Always,
+    /// After `&&`: it only runs if the last status was a success.
+    OnSuccess,
+    /// After `||`: it only runs if the last status was a failure.
+    OnFailure,
+}
+
+/// A list of stages, each of which feeds its output to the next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    pub condition: Condition,
+    pub stages: Vec<Stage>,
+}
+
+/// Parses a command line into a list of pipelines, separated by `;`, `&&` or `||`. An empty line
+/// gives an empty list.
+pub fn parse(line: &str) -> Result<Vec<Pipeline>, SyntaxError> {
+    let mut pipelines = Vec::new();
+    let mut stages = Vec::new();
+    let mut stage = Stage::default();
+    let mut condition = Condition::Always;
+    let mut tokens = lexer::tokenize(line)?.into_iter();
+    // The last operator, so one with nothing after it can be reported.
+    let mut last_operator = None;
+
+    while let Some((position, token)) = tokens.next() {
+        match token {
+            Token::Word(word) => stage.words.push(word),
+            Token::Redirect(kind) => match tokens.next() {
+                Some((_, Token::Word(target))) => stage.redirects.push(Redirect { kind, target }),
+                _ => return Err(error("expected a file name after the redirection", position)),
+            },
+            Token::Pipe => {
+                if stage.is_empty() {
+                    return Err(error("expected a command before |", position));
+                }
+                stages.push(core::mem::take(&mut stage));
+                last_operator = Some((position, Token::Pipe));
+            }
+            Token::Semicolon | Token::And | Token::Or => {
+                if stage.is_empty() {
+                    let message = match (&token, stages.is_empty()) {
+                        (_, false) => "expected a command after |",
+                        (Token::Semicolon, true) => "expected a command before ;",
+                        (Token::And, true) => "expected a command before &&",
+                        _ => "expected a command before ||",
+                    };
+                    return Err(error(message, position));
+                }
+                stages.push(core::mem::take(&mut stage));
+                pipelines.push(Pipeline {
+                    condition,
+                    stages: core::mem::take(&mut stages),
+                });
+                condition = match token {
+                    Token::And => Condition::OnSuccess,
+                    Token::Or => Condition::OnFailure,
+                    _ => Condition::Always,
+                };
+                last_operator = Some((position, token));
+            }
+        }
+    }
+
+    if !stage.is_empty() {
+        stages.push(stage);
+        pipelines.push(Pipeline { condition, stages });
+    } else {
+        // A trailing `;` is fine, but the other operators need something after them.
+        match last_operator {
+            Some((position, Token::Pipe)) => return Err(error("expected a command after |", position)),
+            Some((position, Token::And)) => return Err(error("expected a command after &&", position)),
+            Some((position, Token::Or)) => return Err(error("expected a command after ||", position)),
+            _ => {}
+        }
+    }
+    Ok(pipelines)
+}
+
+fn error(message: &'static str, position: usize) -> SyntaxError {
+    SyntaxError {
+        message,
+        column: position + 1,
+    }
+}
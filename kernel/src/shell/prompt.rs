@@ -0,0 +1,68 @@
+use crate::{
+    shell::{command::ExitStatus, session::Session},
+    time
+};
+use alloc::{format, string::String};
+
+/// The prompt used when `PS1` is not set, which is the one the shell has always had.
+pub const DEFAULT_PS1: &str = "\\u@PortfoliOS -> # ";
+
+/// Expands the escapes in a `PS1` prompt string.
+///
+/// - `\u`: the user's name.
+/// - `\h`: the host name.
+/// - `\t`: the time, as `HH:MM:SS`.
+/// - `\?`: the status of the last command.
+/// - `\$`: `#` for root, and `$` for everyone else.
+/// - `\e`: an escape character, to start an ANSI color sequence like `\e[32m`.
+/// - `\\`: a backslash.
+///
+/// Any other escape is left as it is.
+pub fn render(ps1: &str, session: &Session, status: ExitStatus) -> String {
+    let mut prompt = String::new();
+    let mut chars = ps1.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            prompt.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('u') => prompt.push_str(&session.name),
+            Some('h') => prompt.push_str("PortfoliOS"),
+            Some('t') => {
+                let now = time::now();
+                prompt.push_str(&format!("{:02}:{:02}:{:02}", now.hour, now.minute, now.second));
+            }
+            Some('?') => prompt.push_str(&format!("{}", status.code())),
+            Some('$') => prompt.push(if session.is_root() { '#' } else { '$' }),
+            Some('e') => prompt.push('\x1b'),
+            Some('\\') => prompt.push('\\'),
+            Some(other) => {
+                prompt.push('\\');
+                prompt.push(other);
+            }
+            None => prompt.push('\\'),
+        }
+    }
+    prompt
+}
+
+/// Returns how many characters of `text` actually show up on screen, leaving out ANSI escape
+/// sequences like the ones [ColoredWriting](crate::framebuffer::color::ColoredWriting) adds.
+pub fn visible_len(text: &str) -> usize {
+    let mut len = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' && chars.peek() == Some(&'[') {
+            // Skip up to and including the terminating 'm', like the writer does.
+            for ch in chars.by_ref() {
+                if ch == 'm' {
+                    break;
+                }
+            }
+        } else {
+            len += 1;
+        }
+    }
+    len
+}
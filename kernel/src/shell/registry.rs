@@ -0,0 +1,31 @@
+use crate::shell::{command::Command, commands};
+use alloc::{sync::Arc, vec::Vec};
+use spin::{Lazy, Mutex};
+
+/// Every command the shell can run, starting with the builtins.
+static REGISTRY: Lazy<Mutex<Vec<Arc<dyn Command>>>> = Lazy::new(|| Mutex::new(commands::builtins()));
+
+/// Adds a command to the shell. A command with the same name is replaced.
+///
+/// This is how kernel modules outside of the shell provide commands of their own. They have to
+/// be listed with the builtins, in [names](commands::names), too.
+pub fn register(command: impl Command + 'static) {
+    commands::check_listed(&command);
+    let mut registry = REGISTRY.lock();
+    registry.retain(|existing| existing.name() != command.name());
+    registry.push(Arc::new(command));
+}
+
+/// Finds the command called `name`, by its name or one of its aliases.
+pub fn find(name: &str) -> Option<Arc<dyn Command>> {
+    REGISTRY.lock().iter().find(|command| command.is_called(name)).cloned()
+}
+
+/// Returns every registered command, sorted by name.
+///
+/// The registry is not locked once this returns, so the commands are free to use it themselves.
+pub fn all() -> Vec<Arc<dyn Command>> {
+    let mut commands = REGISTRY.lock().clone();
+    commands.sort_by_key(|command| command.name());
+    commands
+}
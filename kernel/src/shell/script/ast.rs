@@ -0,0 +1,84 @@
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Negate,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Int(i64),
+    Str(String),
+    Bool(bool),
+    Variable(String),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    /// A call to a function, either one the script defines or a builtin.
+    Call(String, Vec<Expr>),
+}
+
+/// What a `for` loop goes over.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Iterable {
+    /// `start..end`, not including `end`.
+    Range(Expr, Expr),
+    /// The whitespace separated words of a string.
+    Words(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatementKind {
+    /// `let name = value`
+    Let(String, Expr),
+    /// `name = value`
+    Assign(String, Expr),
+    /// `if`, then any `else if`s, and maybe an `else`.
+    If(Vec<(Expr, Vec<Statement>)>, Option<Vec<Statement>>),
+    While(Expr, Vec<Statement>),
+    For(String, Iterable, Vec<Statement>),
+    Return(Option<Expr>),
+    Break,
+    Continue,
+    /// A function call on its own, for its side effects.
+    Expr(Expr),
+    /// Any other line, which is run as a shell command.
+    Command(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    /// The line the statement starts on, counting from 1.
+    pub line: usize,
+    pub kind: StatementKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub params: Vec<String>,
+    pub body: Vec<Statement>,
+}
+
+/// A parsed script.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Program {
+    pub body: Vec<Statement>,
+    pub functions: BTreeMap<String, Function>,
+}
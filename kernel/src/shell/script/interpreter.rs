@@ -0,0 +1,414 @@
+use crate::shell::{
+    command::{ExitStatus, Output},
+    exec,
+    script::{
+        ast::{BinaryOp, Expr, Function, Iterable, Program, Statement, StatementKind, UnaryOp},
+        value::Value,
+        ScriptError
+    },
+    session::Session
+};
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec
+};
+
+/// How deep function calls can nest, counting those in every script running, before the script
+/// is stopped. Each call takes up to about 14 KiB of the kernel stack in a debug build.
+const MAX_DEPTH: usize = 32;
+
+/// What running a statement did to the flow of control.
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+    Return(Option<Value>),
+}
+
+/// Runs a parsed script.
+pub struct Interpreter<'a> {
+    session: &'a mut Session,
+    out: &'a mut Output,
+    functions: &'a BTreeMap<String, Function>,
+    /// The script's path followed by its arguments, for `arg()` and `argc()`.
+    args: &'a [String],
+    /// Variables, one frame per function call. The first frame holds the globals.
+    frames: Vec<BTreeMap<String, Value>>,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(
+        session: &'a mut Session,
+        out: &'a mut Output,
+        program: &'a Program,
+        args: &'a [String],
+    ) -> Self {
+        Self {
+            session,
+            out,
+            functions: &program.functions,
+            args,
+            frames: vec![BTreeMap::new()],
+        }
+    }
+
+    /// Runs the script's top level.
+    ///
+    /// ### returns:
+    /// - `Ok(ExitStatus)`: the status given to a top-level `return`, or else the status of the
+    ///   last command run.
+    pub fn run(&mut self, body: &[Statement]) -> Result<ExitStatus, ScriptError> {
+        match self.block(body)? {
+            Flow::Return(Some(value)) => match value {
+                Value::Int(code @ 0..=255) => Ok(ExitStatus(code as u8)),
+                Value::Bool(success) => Ok(ExitStatus::from(success)),
+                other => Err(ScriptError {
+                    line: body.last().map_or(1, |statement| statement.line),
+                    message: format!("a script can only return a status from 0 to 255, not {other}"),
+                }),
+            },
+            _ => Ok(self.session.status),
+        }
+    }
+
+    fn block(&mut self, body: &[Statement]) -> Result<Flow, ScriptError> {
+        for statement in body {
+            match self.statement(statement)? {
+                Flow::Normal => {}
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<Flow, ScriptError> {
+        let line = statement.line;
+        match &statement.kind {
+            StatementKind::Let(name, value) => {
+                let value = self.eval(value, line)?;
+                self.frame().insert(name.clone(), value);
+            }
+            StatementKind::Assign(name, value) => {
+                let value = self.eval(value, line)?;
+                let frame = if self.frame().contains_key(name) {
+                    self.frame()
+                } else if self.frames[0].contains_key(name) {
+                    &mut self.frames[0]
+                } else {
+                    return Err(error(line, format!("`{name}` is not defined; use `let` to create it")));
+                };
+                frame.insert(name.clone(), value);
+            }
+            StatementKind::If(branches, otherwise) => {
+                for (condition, body) in branches {
+                    if self.eval(condition, line)?.is_truthy() {
+                        return self.block(body);
+                    }
+                }
+                if let Some(body) = otherwise {
+                    return self.block(body);
+                }
+            }
+            StatementKind::While(condition, body) => {
+                while self.eval(condition, line)?.is_truthy() {
+                    match self.block(body)? {
+                        Flow::Break => break,
+                        Flow::Normal | Flow::Continue => {}
+                        flow @ Flow::Return(_) => return Ok(flow),
+                    }
+                }
+            }
+            StatementKind::For(name, iterable, body) => {
+                let items: Vec<Value> = match iterable {
+                    Iterable::Range(start, end) => {
+                        let start = self.int(start, line)?;
+                        let end = self.int(end, line)?;
+                        (start..end).map(Value::Int).collect()
+                    }
+                    Iterable::Words(text) => match self.eval(text, line)? {
+                        Value::Str(text) => text.split_whitespace().map(|word| Value::Str(word.to_string())).collect(),
+                        other => {
+                            return Err(error(line, format!("can only loop over a range or a string, not {}", other.type_name())));
+                        }
+                    },
+                };
+                for item in items {
+                    self.frame().insert(name.clone(), item);
+                    match self.block(body)? {
+                        Flow::Break => break,
+                        Flow::Normal | Flow::Continue => {}
+                        flow @ Flow::Return(_) => return Ok(flow),
+                    }
+                }
+            }
+            StatementKind::Return(value) => {
+                let value = value.as_ref().map(|value| self.eval(value, line)).transpose()?;
+                return Ok(Flow::Return(value));
+            }
+            StatementKind::Break => return Ok(Flow::Break),
+            StatementKind::Continue => return Ok(Flow::Continue),
+            StatementKind::Expr(expr) => {
+                self.eval(expr, line)?;
+            }
+            StatementKind::Command(command) => {
+                self.run_command(command, None);
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    /// The variables of the function being run.
+    fn frame(&mut self) -> &mut BTreeMap<String, Value> {
+        self.frames.last_mut().expect("there is always a global frame")
+    }
+
+    /// Runs a shell command, with the script's variables visible to it as `$name`.
+    ///
+    /// ### params:
+    /// - `capture`: where to put the command's output instead of the script's output, if anywhere.
+    fn run_command(&mut self, command: &str, capture: Option<&mut Output>) -> ExitStatus {
+        let mut scope: BTreeMap<String, String> = BTreeMap::new();
+        for frame in [&self.frames[0], self.frames.last().expect("there is always a global frame")] {
+            for (name, value) in frame {
+                scope.insert(name.clone(), value.to_string());
+            }
+        }
+        self.session.script_scopes.push(scope);
+        let status = exec::run_line(self.session, command, capture.unwrap_or(self.out));
+        self.session.script_scopes.pop();
+        status
+    }
+
+    fn int(&mut self, expr: &Expr, line: usize) -> Result<i64, ScriptError> {
+        match self.eval(expr, line)? {
+            Value::Int(value) => Ok(value),
+            other => Err(error(line, format!("expected an integer, but got {}", other.type_name()))),
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr, line: usize) -> Result<Value, ScriptError> {
+        match expr {
+            Expr::Int(value) => Ok(Value::Int(*value)),
+            Expr::Str(text) => Ok(Value::Str(text.clone())),
+            Expr::Bool(value) => Ok(Value::Bool(*value)),
+            Expr::Variable(name) => self
+                .frames
+                .last()
+                .and_then(|frame| frame.get(name))
+                .or_else(|| self.frames[0].get(name))
+                .cloned()
+                .ok_or_else(|| error(line, format!("`{name}` is not defined"))),
+            Expr::Unary(op, operand) => {
+                let operand = self.eval(operand, line)?;
+                match (op, operand) {
+                    (UnaryOp::Not, value) => Ok(Value::Bool(!value.is_truthy())),
+                    (UnaryOp::Negate, Value::Int(value)) => value
+                        .checked_neg()
+                        .map(Value::Int)
+                        .ok_or_else(|| error(line, String::from("integer overflow"))),
+                    (UnaryOp::Negate, other) => Err(error(line, format!("cannot negate {}", other.type_name()))),
+                }
+            }
+            // `&&` and `||` only evaluate their right side if they need to.
+            Expr::Binary(BinaryOp::And, left, right) => {
+                let result = self.eval(left, line)?.is_truthy() && self.eval(right, line)?.is_truthy();
+                Ok(Value::Bool(result))
+            }
+            Expr::Binary(BinaryOp::Or, left, right) => {
+                let result = self.eval(left, line)?.is_truthy() || self.eval(right, line)?.is_truthy();
+                Ok(Value::Bool(result))
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left, line)?;
+                let right = self.eval(right, line)?;
+                binary(*op, left, right).map_err(|message| error(line, message))
+            }
+            Expr::Call(name, args) => {
+                let mut values = Vec::new();
+                for arg in args {
+                    values.push(self.eval(arg, line)?);
+                }
+                self.call(name, values, line)
+            }
+        }
+    }
+
+    /// Calls a function the script defines, or else a builtin one.
+    fn call(&mut self, name: &str, args: Vec<Value>, line: usize) -> Result<Value, ScriptError> {
+        if let Some(function) = self.functions.get(name) {
+            if args.len() != function.params.len() {
+                return Err(error(
+                    line,
+                    format!("`{name}` takes {} arguments, but was given {}", function.params.len(), args.len()),
+                ));
+            }
+            if self.session.call_depth >= MAX_DEPTH {
+                return Err(error(line, format!("too much recursion in `{name}`")));
+            }
+            self.frames.push(function.params.iter().cloned().zip(args).collect());
+            self.session.call_depth += 1;
+            let flow = self.block(&function.body);
+            self.session.call_depth -= 1;
+            self.frames.pop();
+            return match flow? {
+                Flow::Return(Some(value)) => Ok(value),
+                // A function that returns nothing gives the status of its last command.
+                _ => Ok(Value::Int(self.session.status.code() as i64)),
+            };
+        }
+        self.builtin(name, args, line)
+    }
+
+    /// Calls a builtin function.
+    fn builtin(&mut self, name: &str, args: Vec<Value>, line: usize) -> Result<Value, ScriptError> {
+        let arity = |count: usize| {
+            if args.len() == count {
+                Ok(())
+            } else {
+                Err(error(line, format!("`{name}` takes {count} arguments, but was given {}", args.len())))
+            }
+        };
+        let text = |index: usize| match &args[index] {
+            Value::Str(text) => Ok(text.clone()),
+            other => Err(error(line, format!("`{name}` expects a string, but got {}", other.type_name()))),
+        };
+
+        match name {
+            "print" => {
+                let words: Vec<String> = args.iter().map(Value::to_string).collect();
+                writeln!(self.out, "{}", words.join(" "));
+                Ok(Value::Bool(true))
+            }
+            "len" => {
+                arity(1)?;
+                Ok(Value::Int(text(0)?.chars().count() as i64))
+            }
+            "str" => {
+                arity(1)?;
+                Ok(Value::Str(args[0].to_string()))
+            }
+            "int" => {
+                arity(1)?;
+                match &args[0] {
+                    Value::Int(value) => Ok(Value::Int(*value)),
+                    Value::Bool(value) => Ok(Value::Int(*value as i64)),
+                    Value::Str(text) => text
+                        .trim()
+                        .parse()
+                        .map(Value::Int)
+                        .map_err(|_| error(line, format!("`{text}` is not an integer"))),
+                }
+            }
+            "upper" => {
+                arity(1)?;
+                Ok(Value::Str(text(0)?.to_uppercase()))
+            }
+            "lower" => {
+                arity(1)?;
+                Ok(Value::Str(text(0)?.to_lowercase()))
+            }
+            "trim" => {
+                arity(1)?;
+                Ok(Value::Str(text(0)?.trim().to_string()))
+            }
+            "contains" => {
+                arity(2)?;
+                Ok(Value::Bool(text(0)?.contains(text(1)?.as_str())))
+            }
+            "replace" => {
+                arity(3)?;
+                Ok(Value::Str(text(0)?.replace(text(1)?.as_str(), &text(2)?)))
+            }
+            "run" => {
+                arity(1)?;
+                let mut capture = Output::Buffer(String::new());
+                self.run_command(&text(0)?, Some(&mut capture));
+                Ok(Value::Str(capture.into_string().trim_end_matches('\n').to_string()))
+            }
+            "status" => {
+                arity(0)?;
+                Ok(Value::Int(self.session.status.code() as i64))
+            }
+            "arg" => {
+                arity(1)?;
+                let Value::Int(index) = args[0] else {
+                    return Err(error(line, String::from("`arg` expects an integer")));
+                };
+                let arg = usize::try_from(index).ok().and_then(|index| self.args.get(index));
+                Ok(Value::Str(arg.cloned().unwrap_or_default()))
+            }
+            "argc" => {
+                arity(0)?;
+                Ok(Value::Int(self.args.len() as i64 - 1))
+            }
+            _ => Err(error(line, format!("there is no function called `{name}`"))),
+        }
+    }
+}
+
+fn error(line: usize, message: String) -> ScriptError {
+    ScriptError { line, message }
+}
+
+/// Applies an arithmetic or comparison operator.
+fn binary(op: BinaryOp, left: Value, right: Value) -> Result<Value, String> {
+    let overflow = || String::from("integer overflow");
+    match (op, left, right) {
+        (BinaryOp::Equal, left, right) => Ok(Value::Bool(left == right)),
+        (BinaryOp::NotEqual, left, right) => Ok(Value::Bool(left != right)),
+        (BinaryOp::Add, Value::Int(left), Value::Int(right)) => left.checked_add(right).map(Value::Int).ok_or_else(overflow),
+        // Adding anything to a string joins them together.
+        (BinaryOp::Add, left @ Value::Str(_), right) | (BinaryOp::Add, left, right @ Value::Str(_)) => {
+            Ok(Value::Str(format!("{left}{right}")))
+        }
+        (BinaryOp::Subtract, Value::Int(left), Value::Int(right)) => left.checked_sub(right).map(Value::Int).ok_or_else(overflow),
+        (BinaryOp::Multiply, Value::Int(left), Value::Int(right)) => left.checked_mul(right).map(Value::Int).ok_or_else(overflow),
+        (BinaryOp::Multiply, Value::Str(text), Value::Int(count)) => match usize::try_from(count) {
+            Ok(count) => Ok(Value::Str(text.repeat(count))),
+            Err(_) => Err(String::from("cannot repeat a string a negative number of times")),
+        },
+        (BinaryOp::Divide | BinaryOp::Remainder, Value::Int(_), Value::Int(0)) => Err(String::from("division by zero")),
+        (BinaryOp::Divide, Value::Int(left), Value::Int(right)) => left.checked_div(right).map(Value::Int).ok_or_else(overflow),
+        (BinaryOp::Remainder, Value::Int(left), Value::Int(right)) => left.checked_rem(right).map(Value::Int).ok_or_else(overflow),
+        (BinaryOp::Less | BinaryOp::LessEqual | BinaryOp::Greater | BinaryOp::GreaterEqual, left, right) => {
+            let ordering = match (&left, &right) {
+                (Value::Int(left), Value::Int(right)) => left.cmp(right),
+                (Value::Str(left), Value::Str(right)) => left.cmp(right),
+                _ => return Err(format!("cannot compare {} with {}", left.type_name(), right.type_name())),
+            };
+            Ok(Value::Bool(match op {
+                BinaryOp::Less => ordering.is_lt(),
+                BinaryOp::LessEqual => ordering.is_le(),
+                BinaryOp::Greater => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            }))
+        }
+        (op, left, right) => Err(format!(
+            "cannot use `{}` on {} and {}",
+            symbol(op),
+            left.type_name(),
+            right.type_name()
+        )),
+    }
+}
+
+fn symbol(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Subtract => "-",
+        BinaryOp::Multiply => "*",
+        BinaryOp::Divide => "/",
+        BinaryOp::Remainder => "%",
+        BinaryOp::Equal => "==",
+        BinaryOp::NotEqual => "!=",
+        BinaryOp::Less => "<",
+        BinaryOp::LessEqual => "<=",
+        BinaryOp::Greater => ">",
+        BinaryOp::GreaterEqual => ">=",
+        BinaryOp::And => "&&",
+        BinaryOp::Or => "||",
+    }
+}
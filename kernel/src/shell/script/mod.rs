@@ -0,0 +1,97 @@
+//! The ash scripting language.
+//!
+//! A script is a file of lines, each of which is a statement or a shell command:
+//!
+//! ```text
+//! #!/bin/ash
+//! fn greet(who) {
+//!     echo "Hello, $who!"
+//! }
+//!
+//! let count = int(arg(1))
+//! for i in 0..count {
+//!     if i % 2 == 0 {
+//!         greet("even " + str(i))
+//!     } else {
+//!         print("odd", i)
+//!     }
+//! }
+//! ```
+//!
+//! - `let name = value` creates a variable, and `name = value` changes one.
+//! - `if`/`else if`/`else`, `while` and `for name in start..end` (or `in "some words"`) take a
+//!   block, opened with `{` at the end of the line and closed with `}` on a line of its own.
+//! - `fn name(params) {` defines a function, and `return value` leaves it.
+//! - Values are integers, strings and booleans, with the usual arithmetic and comparison
+//!   operators, `&&`, `||` and `!`. `+` joins strings.
+//! - Builtin functions: `print`, `len`, `str`, `int`, `upper`, `lower`, `trim`, `contains`,
+//!   `replace`, `run` (a command's output), `status` (the last command's status), `arg` and
+//!   `argc`.
+//! - Any other line is run as a shell command, which sees the script's variables as `$name`.
+mod ast;
+mod interpreter;
+mod parser;
+mod token;
+mod value;
+
+use crate::{
+    println,
+    shell::{
+        command::{ExitStatus, Output},
+        session::Session
+    }
+};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+use interpreter::Interpreter;
+
+/// How many scripts can run inside each other before the next is refused. Each one takes
+/// about 12 KiB of the kernel stack.
+const MAX_NESTING: usize = 16;
+
+/// An error in a script, parsing or running it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    /// The line it happened on, counting from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Runs a script.
+///
+/// ### params:
+/// - `session`: the session the script's commands run in.
+/// - `source`: the script itself.
+/// - `args`: the script's path, followed by its arguments.
+/// - `out`: where the script's output goes.
+pub fn run(session: &mut Session, source: &str, args: &[String], out: &mut Output) -> Result<ExitStatus, ScriptError> {
+    let program = parser::parse(source)?;
+    Interpreter::new(session, out, &program, args).run(&program.body)
+}
+
+/// Runs the script at `path`, reporting any error with the path and line it happened on.
+pub fn run_file(session: &mut Session, path: &str, source: &str, args: &[String], out: &mut Output) -> ExitStatus {
+    let mut all_args = Vec::with_capacity(args.len() + 1);
+    all_args.push(String::from(path));
+    all_args.extend_from_slice(args);
+    if session.script_depth >= MAX_NESTING {
+        println!("{}: scripts nested too deeply, more than {}", path, MAX_NESTING);
+        return ExitStatus::FAILURE;
+    }
+    session.script_depth += 1;
+    let result = run(session, source, &all_args, out);
+    session.script_depth -= 1;
+    match result {
+        Ok(status) => status,
+        Err(error) => {
+            println!("{}: {}", path, error);
+            ExitStatus::FAILURE
+        }
+    }
+}
@@ -0,0 +1,449 @@
+use crate::shell::script::{
+    ast::{BinaryOp, Expr, Function, Iterable, Program, Statement, StatementKind, UnaryOp},
+    token::{self, Token},
+    ScriptError
+};
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec
+};
+
+/// Parses the source of a script.
+///
+/// Scripts are made of lines. A line is either a statement of the language, a `}` closing a
+/// block (which may carry on with `else`), or a shell command. Blank lines and lines starting
+/// with `#`, like the `#!` line, are skipped.
+pub fn parse(source: &str) -> Result<Program, ScriptError> {
+    let mut parser = Parser {
+        lines: source
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .collect(),
+        index: 0,
+        functions: BTreeMap::new(),
+    };
+    let (body, _) = parser.block(None, false)?;
+    Ok(Program {
+        body,
+        functions: parser.functions,
+    })
+}
+
+struct Parser<'a> {
+    /// The lines worth parsing, with their line numbers.
+    lines: Vec<(usize, &'a str)>,
+    index: usize,
+    functions: BTreeMap<String, Function>,
+}
+
+impl<'a> Parser<'a> {
+    /// Parses statements up to the `}` closing a block, or the end of the script at the top
+    /// level.
+    ///
+    /// ### params:
+    /// - `opened`: the line the block was opened on, or `None` at the top level.
+    /// - `in_loop`: whether `break` and `continue` are allowed.
+    ///
+    /// ### returns:
+    /// - `Ok((body, rest))`: the statements, and whatever followed the `}` on its line.
+    fn block(&mut self, opened: Option<usize>, in_loop: bool) -> Result<(Vec<Statement>, &'a str), ScriptError> {
+        let mut body = Vec::new();
+        while let Some(&(line, text)) = self.lines.get(self.index) {
+            self.index += 1;
+            if let Some(rest) = text.strip_prefix('}') {
+                return match opened {
+                    Some(_) => Ok((body, rest.trim())),
+                    None => Err(error(line, "unexpected `}` with no block to close")),
+                };
+            }
+            if let Some(statement) = self.statement(line, text, opened.is_none(), in_loop)? {
+                body.push(statement);
+            }
+        }
+        match opened {
+            Some(opened) => Err(ScriptError {
+                line: opened,
+                message: String::from("this block is never closed with a `}`"),
+            }),
+            None => Ok((body, "")),
+        }
+    }
+
+    /// Parses a nested block, which must not have anything after its closing `}`.
+    fn body(&mut self, opened: usize, in_loop: bool) -> Result<Vec<Statement>, ScriptError> {
+        let (body, rest) = self.block(Some(opened), in_loop)?;
+        if !rest.is_empty() {
+            return Err(error(self.lines[self.index - 1].0, "unexpected text after `}`"));
+        }
+        Ok(body)
+    }
+
+    /// Parses the statement on a line, along with any block it opens. Function definitions are
+    /// stored away rather than returned, since they do nothing where they are.
+    fn statement(
+        &mut self,
+        line: usize,
+        text: &'a str,
+        top_level: bool,
+        in_loop: bool,
+    ) -> Result<Option<Statement>, ScriptError> {
+        let word_end = text
+            .find(|ch: char| !ch.is_ascii_alphanumeric() && ch != '_')
+            .unwrap_or(text.len());
+        let (word, after) = text.split_at(word_end);
+        let tokens = || token::tokenize(text).map_err(at(line));
+
+        let kind = match word {
+            "let" => match tokens()?.as_slice() {
+                [_, Token::Ident(name), Token::Assign, value @ ..] => {
+                    StatementKind::Let(name.clone(), expression(value).map_err(at(line))?)
+                }
+                _ => return Err(error(line, "expected `let <name> = <value>`")),
+            },
+            "if" => return self.if_statement(line, text, in_loop).map(Some),
+            "while" => {
+                let tokens = tokens()?;
+                let condition = header(&tokens[1..]).and_then(expression).map_err(at(line))?;
+                StatementKind::While(condition, self.body(line, true)?)
+            }
+            "for" => {
+                let tokens = tokens()?;
+                let [_, Token::Ident(name), Token::Ident(in_keyword), rest @ ..] = tokens.as_slice() else {
+                    return Err(error(line, "expected `for <name> in <start>..<end> {`"));
+                };
+                if in_keyword != "in" {
+                    return Err(error(line, "expected `in` after the loop variable"));
+                }
+                let iterable = header(rest).and_then(iterable).map_err(at(line))?;
+                StatementKind::For(name.clone(), iterable, self.body(line, true)?)
+            }
+            "fn" => {
+                if !top_level {
+                    return Err(error(line, "functions can only be defined at the top level"));
+                }
+                let tokens = tokens()?;
+                let (name, params) = signature(&tokens).map_err(at(line))?;
+                let body = self.body(line, false)?;
+                if self.functions.insert(name.clone(), Function { params, body }).is_some() {
+                    return Err(ScriptError {
+                        line,
+                        message: format!("function `{name}` is defined twice"),
+                    });
+                }
+                return Ok(None);
+            }
+            "return" => {
+                let tokens = tokens()?;
+                match &tokens[1..] {
+                    [] => StatementKind::Return(None),
+                    value => StatementKind::Return(Some(expression(value).map_err(at(line))?)),
+                }
+            }
+            "break" | "continue" if after.trim().is_empty() => {
+                if !in_loop {
+                    return Err(ScriptError {
+                        line,
+                        message: format!("`{word}` outside of a loop"),
+                    });
+                }
+                if word == "break" { StatementKind::Break } else { StatementKind::Continue }
+            }
+            "else" => return Err(error(line, "`else` must follow the `}` of an `if` on the same line")),
+            _ if is_name(word) && after.trim_start().starts_with('=') && !after.trim_start().starts_with("==") => {
+                match tokens()?.as_slice() {
+                    [Token::Ident(name), Token::Assign, value @ ..] => {
+                        StatementKind::Assign(name.clone(), expression(value).map_err(at(line))?)
+                    }
+                    _ => return Err(error(line, "expected `<name> = <value>`")),
+                }
+            }
+            _ if is_name(word) && after.starts_with('(') => {
+                StatementKind::Expr(expression(&tokens()?).map_err(at(line))?)
+            }
+            _ => StatementKind::Command(text.to_string()),
+        };
+        Ok(Some(Statement { line, kind }))
+    }
+
+    /// Parses an `if`, and the `else if`s and `else` that follow it.
+    fn if_statement(&mut self, line: usize, text: &str, in_loop: bool) -> Result<Statement, ScriptError> {
+        let mut branches = Vec::new();
+        let mut otherwise = None;
+        let mut header_line = line;
+        let mut header_text = text.to_string();
+        loop {
+            // `header_text` starts with `if`.
+            let tokens = token::tokenize(&header_text).map_err(at(header_line))?;
+            let condition = header(&tokens[1..])
+                .and_then(expression)
+                .map_err(at(header_line))?;
+            let (body, rest) = self.block(Some(header_line), in_loop)?;
+            branches.push((condition, body));
+
+            let closing_line = self.lines[self.index - 1].0;
+            let Some(rest) = rest.strip_prefix("else") else {
+                if rest.is_empty() {
+                    break;
+                }
+                return Err(error(closing_line, "expected `else` or nothing after `}`"));
+            };
+            let rest = rest.trim_start();
+            if rest.starts_with("if") && rest[2..].starts_with(|ch: char| ch.is_whitespace()) {
+                header_line = closing_line;
+                header_text = rest.to_string();
+                continue;
+            }
+            if rest != "{" {
+                return Err(error(closing_line, "expected `{` or `if` after `else`"));
+            }
+            otherwise = Some(self.body(closing_line, in_loop)?);
+            break;
+        }
+        Ok(Statement {
+            line,
+            kind: StatementKind::If(branches, otherwise),
+        })
+    }
+}
+
+/// Turns an error message about `line` into a [ScriptError].
+fn at(line: usize) -> impl Fn(String) -> ScriptError {
+    move |message| ScriptError { line, message }
+}
+
+fn error(line: usize, message: &str) -> ScriptError {
+    ScriptError {
+        line,
+        message: String::from(message),
+    }
+}
+
+fn is_name(word: &str) -> bool {
+    word.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_')
+}
+
+/// Checks that a block header ends with `{`, and returns what comes before it.
+fn header(tokens: &[Token]) -> Result<&[Token], String> {
+    match tokens {
+        [rest @ .., Token::LBrace] => Ok(rest),
+        _ => Err(String::from("expected `{` at the end of the line")),
+    }
+}
+
+/// Parses what a `for` loop goes over.
+fn iterable(tokens: &[Token]) -> Result<Iterable, String> {
+    let mut parser = ExprParser { tokens, position: 0 };
+    let first = parser.or()?;
+    let iterable = if parser.eat(&Token::DotDot) {
+        Iterable::Range(first, parser.or()?)
+    } else {
+        Iterable::Words(first)
+    };
+    parser.finish()?;
+    Ok(iterable)
+}
+
+/// Parses `fn name(a, b) {`.
+fn signature(tokens: &[Token]) -> Result<(String, Vec<String>), String> {
+    let [_, Token::Ident(name), Token::LParen, rest @ ..] = tokens else {
+        return Err(String::from("expected `fn <name>(<parameters>) {`"));
+    };
+    let rest = header(rest)?;
+    let [params @ .., Token::RParen] = rest else {
+        return Err(String::from("expected `)` after the parameters"));
+    };
+    let mut names = Vec::new();
+    for (index, param) in params.iter().enumerate() {
+        match (index % 2, param) {
+            (0, Token::Ident(param)) => {
+                if names.contains(param) {
+                    return Err(format!("parameter `{param}` is listed twice"));
+                }
+                names.push(param.clone());
+            }
+            (1, Token::Comma) => {}
+            _ => return Err(String::from("parameters must be names separated by commas")),
+        }
+    }
+    if params.last() == Some(&Token::Comma) {
+        return Err(String::from("parameters must be names separated by commas"));
+    }
+    Ok((name.clone(), names))
+}
+
+/// Parses a whole expression.
+fn expression(tokens: &[Token]) -> Result<Expr, String> {
+    let mut parser = ExprParser { tokens, position: 0 };
+    let expr = parser.or()?;
+    parser.finish()?;
+    Ok(expr)
+}
+
+/// A recursive descent parser for expressions, one function per precedence level.
+struct ExprParser<'t> {
+    tokens: &'t [Token],
+    position: usize,
+}
+
+impl ExprParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn finish(&self) -> Result<(), String> {
+        match self.peek() {
+            None => Ok(()),
+            Some(token) => Err(format!("unexpected {} in expression", describe(token))),
+        }
+    }
+
+    /// Parses a left-associative chain of binary operators.
+    fn binary(
+        &mut self,
+        operators: &[(Token, BinaryOp)],
+        operand: fn(&mut Self) -> Result<Expr, String>,
+    ) -> Result<Expr, String> {
+        let mut left = operand(self)?;
+        'chain: loop {
+            for (token, op) in operators {
+                if self.eat(token) {
+                    let right = operand(self)?;
+                    left = Expr::Binary(*op, Box::new(left), Box::new(right));
+                    continue 'chain;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        self.binary(&[(Token::OrOr, BinaryOp::Or)], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        self.binary(&[(Token::AndAnd, BinaryOp::And)], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        self.binary(
+            &[
+                (Token::EqEq, BinaryOp::Equal),
+                (Token::NotEq, BinaryOp::NotEqual),
+                (Token::Less, BinaryOp::Less),
+                (Token::LessEq, BinaryOp::LessEqual),
+                (Token::Greater, BinaryOp::Greater),
+                (Token::GreaterEq, BinaryOp::GreaterEqual),
+            ],
+            Self::additive,
+        )
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        self.binary(
+            &[(Token::Plus, BinaryOp::Add), (Token::Minus, BinaryOp::Subtract)],
+            Self::multiplicative,
+        )
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        self.binary(
+            &[
+                (Token::Star, BinaryOp::Multiply),
+                (Token::Slash, BinaryOp::Divide),
+                (Token::Percent, BinaryOp::Remainder),
+            ],
+            Self::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat(&Token::Minus) {
+            return Ok(Expr::Unary(UnaryOp::Negate, Box::new(self.unary()?)));
+        }
+        if self.eat(&Token::Bang) {
+            return Ok(Expr::Unary(UnaryOp::Not, Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(String::from("expected a value, but the line ended"));
+        };
+        self.position += 1;
+        match token {
+            Token::Int(value) => Ok(Expr::Int(value)),
+            Token::Str(text) => Ok(Expr::Str(text)),
+            Token::Ident(name) if name == "true" => Ok(Expr::Bool(true)),
+            Token::Ident(name) if name == "false" => Ok(Expr::Bool(false)),
+            Token::Ident(name) if self.eat(&Token::LParen) => {
+                let mut args = Vec::new();
+                if !self.eat(&Token::RParen) {
+                    loop {
+                        args.push(self.or()?);
+                        if self.eat(&Token::RParen) {
+                            break;
+                        }
+                        if !self.eat(&Token::Comma) {
+                            return Err(format!("expected `,` or `)` in the call to `{name}`"));
+                        }
+                    }
+                }
+                Ok(Expr::Call(name, args))
+            }
+            Token::Ident(name) => Ok(Expr::Variable(name)),
+            Token::LParen => {
+                let expr = self.or()?;
+                if !self.eat(&Token::RParen) {
+                    return Err(String::from("expected `)`"));
+                }
+                Ok(expr)
+            }
+            token => Err(format!("expected a value, but found {}", describe(&token))),
+        }
+    }
+}
+
+/// Describes a token for an error message.
+fn describe(token: &Token) -> String {
+    let text = match token {
+        Token::Int(value) => return format!("`{value}`"),
+        Token::Str(_) => return String::from("a string"),
+        Token::Ident(name) => return format!("`{name}`"),
+        Token::Plus => "+",
+        Token::Minus => "-",
+        Token::Star => "*",
+        Token::Slash => "/",
+        Token::Percent => "%",
+        Token::EqEq => "==",
+        Token::NotEq => "!=",
+        Token::Less => "<",
+        Token::LessEq => "<=",
+        Token::Greater => ">",
+        Token::GreaterEq => ">=",
+        Token::AndAnd => "&&",
+        Token::OrOr => "||",
+        Token::Bang => "!",
+        Token::LParen => "(",
+        Token::RParen => ")",
+        Token::Comma => ",",
+        Token::DotDot => "..",
+        Token::Assign => "=",
+        Token::LBrace => "{",
+    };
+    format!("`{text}`")
+}
@@ -0,0 +1,102 @@
+use alloc::{format, string::String, vec::Vec};
+
+/// A token of an ash script expression or statement header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Int(i64),
+    Str(String),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    EqEq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    AndAnd,
+    OrOr,
+    Bang,
+    LParen,
+    RParen,
+    Comma,
+    DotDot,
+    Assign,
+    LBrace,
+}
+
+/// Splits one line of a script into tokens. A `#` outside of a string starts a comment.
+pub fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        let token = match ch {
+            ch if ch.is_whitespace() => continue,
+            '#' => break,
+            '0'..='9' => {
+                let mut digits = String::from(ch);
+                while let Some(&digit) = chars.peek().filter(|ch| ch.is_ascii_digit()) {
+                    digits.push(digit);
+                    chars.next();
+                }
+                let value = digits
+                    .parse()
+                    .map_err(|_| format!("{digits} is too big for an integer"))?;
+                Token::Int(value)
+            }
+            'a'..='z' | 'A'..='Z' | '_' => {
+                let mut name = String::from(ch);
+                while let Some(&next) = chars.peek().filter(|ch| ch.is_ascii_alphanumeric() || **ch == '_') {
+                    name.push(next);
+                    chars.next();
+                }
+                Token::Ident(name)
+            }
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => text.push('\n'),
+                            Some('t') => text.push('\t'),
+                            Some('e') => text.push('\x1b'),
+                            Some(escaped @ ('"' | '\\')) => text.push(escaped),
+                            Some(other) => return Err(format!("unknown escape \\{other} in string")),
+                            None => return Err(String::from("unterminated string")),
+                        },
+                        Some(ch) => text.push(ch),
+                        None => return Err(String::from("unterminated string")),
+                    }
+                }
+                Token::Str(text)
+            }
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '*' => Token::Star,
+            '/' => Token::Slash,
+            '%' => Token::Percent,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ',' => Token::Comma,
+            '{' => Token::LBrace,
+            '=' if chars.next_if_eq(&'=').is_some() => Token::EqEq,
+            '=' => Token::Assign,
+            '!' if chars.next_if_eq(&'=').is_some() => Token::NotEq,
+            '!' => Token::Bang,
+            '<' if chars.next_if_eq(&'=').is_some() => Token::LessEq,
+            '<' => Token::Less,
+            '>' if chars.next_if_eq(&'=').is_some() => Token::GreaterEq,
+            '>' => Token::Greater,
+            '&' if chars.next_if_eq(&'&').is_some() => Token::AndAnd,
+            '|' if chars.next_if_eq(&'|').is_some() => Token::OrOr,
+            '.' if chars.next_if_eq(&'.').is_some() => Token::DotDot,
+            other => return Err(format!("unexpected character `{other}`")),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
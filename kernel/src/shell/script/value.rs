@@ -0,0 +1,41 @@
+use alloc::string::String;
+use core::fmt;
+
+/// A value in an ash script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Int(i64),
+    Str(String),
+    Bool(bool),
+}
+
+impl Value {
+    /// Whether the value counts as true in a condition: `true`, a non-zero integer, or a
+    /// non-empty string.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Int(value) => *value != 0,
+            Value::Str(text) => !text.is_empty(),
+            Value::Bool(value) => *value,
+        }
+    }
+
+    /// The name of the value's type with an article, like "an integer", for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "an integer",
+            Value::Str(_) => "a string",
+            Value::Bool(_) => "a boolean",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{value}"),
+            Value::Str(text) => write!(f, "{text}"),
+            Value::Bool(value) => write!(f, "{value}"),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{
+    fs,
+    keyboard,
+    println,
+    shell::{
+        calc::Number,
+        command::{ExitStatus, Output},
+        exec,
+        environment::Environment,
+        history::{History, HISTORY_FILE}
+    },
+    users::{self, User}
+};
+use alloc::{collections::BTreeMap, format, string::String, vec::Vec};
+
+/// The file in the user's home run at the start of every session, to set up aliases and
+/// variables.
+pub const RC_FILE: &str = ".ashrc";
+
+/// The variable holding the keyboard layout to use, by its name in [LAYOUTS](keyboard::LAYOUTS).
+/// It is switched to once the [RC_FILE] has run, and `loadkeys` keeps it up to date.
+pub const KEYMAP: &str = "KEYMAP";
+
+/// What the [RC_FILE] holds until it is changed.
+const DEFAULT_RC: &str = "\
+# Run by ash, one line at a time, at the start of every session.
+alias ll='ls -a'
+alias h=history
+alias cls=clear
+alias ?=help
+# The keyboard layout, switched to once this file has run. Run loadkeys to see the others.
+set KEYMAP=us
+echo \"Type help to see what you can do, or cat $HOME/.ashrc to see how this shell was set up.\"
+";
+
+/// Everything the shell knows about the person using it.
+pub struct Session {
+    /// The name of the user logged in, or empty before anyone is.
+    pub name: String,
+    /// The logged in user's uid.
+    pub uid: u32,
+    /// The logged in user's home directory.
+    pub home: String,
+    /// The users `su` switched away from, most recent last, to go back to on `logout`.
+    pub previous_users: Vec<String>,
+    /// The commands they have run.
+    pub history: History,
+    /// Their shell variables.
+    pub environment: Environment,
+    /// The status of the last command they ran.
+    pub status: ExitStatus,
+    /// The variables of the scripts being run, innermost last, as the commands in them see
+    /// them. They shadow the environment.
+    pub script_scopes: Vec<BTreeMap<String, String>>,
+    /// How many scripts are running inside each other, through `run` or a `#!` path.
+    pub script_depth: usize,
+    /// How many script function calls are in progress, across all the scripts running.
+    pub call_depth: usize,
+    /// How many `$(command)` substitutions are running inside each other.
+    pub substitution_depth: usize,
+    /// The last answer `calc` gave, which it calls `ans`.
+    pub calc_ans: Option<Number>,
+    /// Their aliases, from the name to the command line it stands for.
+    pub aliases: BTreeMap<String, String>,
+    /// The best score in each game they have played, by the game's name.
+    pub high_scores: BTreeMap<&'static str, u32>,
+}
+
+impl Session {
+    pub const fn new() -> Self {
+        Self {
+            name: String::new(),
+            uid: 0,
+            home: String::new(),
+            previous_users: Vec::new(),
+            history: History::new(),
+            environment: Environment::new(),
+            status: ExitStatus::SUCCESS,
+            script_scopes: Vec::new(),
+            script_depth: 0,
+            call_depth: 0,
+            substitution_depth: 0,
+            calc_ans: None,
+            aliases: BTreeMap::new(),
+            high_scores: BTreeMap::new(),
+        }
+    }
+
+    /// Returns true once someone has logged in, until they log out.
+    pub fn is_active(&self) -> bool {
+        !self.name.is_empty()
+    }
+
+    /// Returns true if the logged in user is `root`.
+    pub fn is_root(&self) -> bool {
+        self.is_active() && self.uid == 0
+    }
+
+    /// Starts the session of `user`, who has just logged in: sets up their environment, loads
+    /// their history, and runs their [RC_FILE].
+    pub fn start(&mut self, user: &User) {
+        *self = Self::new();
+        self.switch_to(user);
+        self.environment.export("USER");
+        self.environment.export("HOME");
+        self.environment.set("SHELL", "ash");
+        self.environment.export("SHELL");
+        self.run_rc_file();
+        self.apply_keymap();
+    }
+
+    /// Becomes `user` for `su`, keeping the environment but swapping the history. [logout]
+    /// comes back to the current user.
+    ///
+    /// [logout]: Session::logout
+    pub fn su(&mut self, user: &User) {
+        self.save_history();
+        self.previous_users.push(self.name.clone());
+        self.switch_to(user);
+    }
+
+    /// Logs the current user out, going back to whoever they `su`'d from, if anyone.
+    ///
+    /// ### returns:
+    /// - `true`: if the session is still active, as the previous user.
+    /// - `false`: if the session is over.
+    pub fn logout(&mut self) -> bool {
+        self.save_history();
+        while let Some(name) = self.previous_users.pop() {
+            // The user may have gone in the meantime; skip them if so.
+            if let Some(user) = users::find(&name) {
+                self.switch_to(&user);
+                return true;
+            }
+        }
+        *self = Self::new();
+        false
+    }
+
+    /// Takes on `user`'s identity, keeping `$USER` and `$HOME` in step, and loads their history.
+    fn switch_to(&mut self, user: &User) {
+        self.name = user.name.clone();
+        self.uid = user.uid;
+        self.home = user.home.clone();
+        self.environment.set("USER", &user.name);
+        self.environment.set("HOME", &user.home);
+        self.load_history();
+    }
+
+    /// Returns true if the logged in user may change the file at `path`: anywhere but in another
+    /// user's home directory, or anywhere at all for `root`.
+    pub fn can_write(&self, path: &str) -> bool {
+        self.is_root() || users::home_owner(path).is_none_or(|uid| uid == self.uid)
+    }
+
+    /// Returns the path of `file` in the user's home directory.
+    pub fn home_file(&self, file: &str) -> String {
+        fs::normalize(&format!("{}/{}", self.home, file))
+    }
+
+    /// Runs each line of the user's [RC_FILE] as a command, writing the [DEFAULT_RC] there first
+    /// if there isn't one, so it can be looked at and changed.
+    pub fn run_rc_file(&mut self) {
+        let path = self.home_file(RC_FILE);
+        let source = fs::with_store(|store| {
+            if !store.exists(&path) {
+                store.write(&path, DEFAULT_RC.as_bytes());
+            }
+            store.read_to_string(&path)
+        });
+        let Some(Some(source)) = source else { return };
+        for line in source.lines() {
+            exec::run_line(self, line, &mut Output::Screen);
+        }
+    }
+
+    /// Switches to the keyboard layout in [KEYMAP], if it is set.
+    fn apply_keymap(&self) {
+        let Some(layout) = self.variable(KEYMAP) else { return };
+        if !keyboard::set_layout(layout) {
+            println!("ash: {}: there is no layout called {}; run loadkeys to list them", KEYMAP, layout);
+        }
+    }
+
+    /// Returns the value of a variable, looking in the innermost script being run first.
+    pub fn variable(&self, name: &str) -> Option<&str> {
+        self.script_scopes
+            .last()
+            .and_then(|scope| scope.get(name))
+            .map(String::as_str)
+            .or_else(|| self.environment.get(name))
+    }
+
+    /// Loads the history the user saved in a previous session, if there is a file store to load
+    /// it from.
+    pub fn load_history(&mut self) {
+        let path = self.home_file(HISTORY_FILE);
+        match fs::with_store(|store| store.read_to_string(&path)) {
+            Some(Some(text)) => self.history.load(&text),
+            _ => self.history.clear(),
+        }
+    }
+
+    /// Saves the history to the user's home in the file store, if there is one.
+    pub fn save_history(&self) {
+        if !self.is_active() {
+            return;
+        }
+        let path = self.home_file(HISTORY_FILE);
+        if !self.can_write(&path) {
+            return;
+        }
+        let text = self.history.serialize();
+        fs::with_store(|store| store.write(&path, text.as_bytes()));
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,391 @@
+//! Information about the machine the kernel is running on: its CPU, memory, display, interrupt
+//! controllers and firmware, for `portfoliofetch` and the like.
+//!
+//! What the bootloader and ACPI tell us is recorded once, at boot, by [init]. The CPU is asked
+//! with `cpuid` when needed, and the rest is read live.
+use crate::{allocator, framebuffer::global_writer::with_writer, memory, time};
+use acpi::{platform::PlatformInfo, sdt::SdtHeader, AcpiHandler, AcpiTables, InterruptModel};
+use alloc::{
+    alloc::Global,
+    format,
+    string::String,
+    vec::Vec
+};
+use bootloader_api::info::{MemoryRegion, MemoryRegionKind, MemoryRegions, PixelFormat};
+use core::{arch::x86_64::__cpuid, fmt, mem::size_of, ptr, time::Duration};
+use spin::Once;
+
+/// The firmware that booted the machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootMode {
+    Bios,
+    Uefi,
+    /// The memory map had nothing in it only one of them reports.
+    Unknown,
+}
+
+impl fmt::Display for BootMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BootMode::Bios => write!(f, "BIOS"),
+            BootMode::Uefi => write!(f, "UEFI"),
+            BootMode::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// What was found out about the machine at boot.
+#[derive(Debug, Clone)]
+pub struct Machine {
+    /// Bytes of RAM, including what the firmware and bootloader use, but not reserved ranges.
+    pub total_memory: u64,
+    /// Bytes of RAM the kernel is free to use.
+    pub usable_memory: u64,
+    pub boot_mode: BootMode,
+    /// How interrupts are delivered, as the ACPI MADT describes it, like `APIC, 1 I/O APIC`.
+    pub interrupt_model: String,
+    /// How many processors ACPI lists, counting the one we are running on.
+    pub processors: usize,
+    /// The local APIC IDs of the processors ACPI lists, the one we are running on first.
+    pub apic_ids: Vec<u32>,
+    /// The bootloader's memory map.
+    pub memory_regions: Vec<MemoryRegion>,
+    /// The ACPI tables the firmware has, the DSDT included.
+    pub acpi_tables: Vec<AcpiTableInfo>,
+}
+
+/// What the header of an ACPI table says about it.
+#[derive(Debug, Clone)]
+pub struct AcpiTableInfo {
+    /// Like `APIC`, for the MADT, or `FACP`, for the FADT.
+    pub signature: String,
+    /// Who made the table, like `BOCHS`.
+    pub oem_id: String,
+    pub oem_table_id: String,
+    pub oem_revision: u32,
+    pub revision: u8,
+    /// The length of the whole table, header included, in bytes.
+    pub length: u32,
+}
+
+impl From<SdtHeader> for AcpiTableInfo {
+    fn from(header: SdtHeader) -> Self {
+        // The header is packed, so its fields are copied out before being used.
+        let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).trim_end().into();
+        let (oem_revision, revision, length) = (header.oem_revision, header.revision, header.length);
+        Self {
+            signature: header.signature.as_str().into(),
+            oem_id: text(&header.oem_id),
+            oem_table_id: text(&header.oem_table_id),
+            oem_revision,
+            revision,
+            length,
+        }
+    }
+}
+
+static MACHINE: Once<Machine> = Once::new();
+
+/// Records what the bootloader's memory map and the ACPI tables say about the machine. Must be
+/// called after [memory::init], to read the DSDT's header.
+pub fn init<H: AcpiHandler>(
+    memory_regions: &MemoryRegions,
+    acpi_tables: &AcpiTables<H>,
+    platform_info: &PlatformInfo<Global>,
+) {
+    let mut total_memory = 0;
+    let mut usable_memory = 0;
+    let mut boot_mode = BootMode::Unknown;
+    for region in memory_regions.iter() {
+        let len = region.end - region.start;
+        let is_ram = match region.kind {
+            MemoryRegionKind::Usable => {
+                usable_memory += len;
+                true
+            }
+            MemoryRegionKind::Bootloader => true,
+            // The UEFI memory types for loader, boot services, runtime services and ACPI memory.
+            MemoryRegionKind::UnknownUefi(kind) => {
+                boot_mode = BootMode::Uefi;
+                matches!(kind, 1..=6 | 9 | 10)
+            }
+            // The E820 types for ACPI memory.
+            MemoryRegionKind::UnknownBios(kind) => {
+                boot_mode = BootMode::Bios;
+                matches!(kind, 3 | 4)
+            }
+            _ => false,
+        };
+        if is_ram {
+            total_memory += len;
+        }
+    }
+
+    let interrupt_model = match &platform_info.interrupt_model {
+        InterruptModel::Apic(apic) => {
+            let io_apics = apic.io_apics.len();
+            let plural = if io_apics == 1 { "" } else { "s" };
+            let pics = if apic.also_has_legacy_pics { ", 8259 PICs masked" } else { "" };
+            format!("APIC, {io_apics} I/O APIC{plural}{pics}")
+        }
+        _ => String::from("unknown"),
+    };
+    let processors = platform_info
+        .processor_info
+        .as_ref()
+        .map_or(1, |info| 1 + info.application_processors.len());
+    let apic_ids = platform_info.processor_info.as_ref().map_or_else(Vec::new, |info| {
+        let processors = core::iter::once(&info.boot_processor).chain(info.application_processors.iter());
+        processors.map(|processor| processor.local_apic_id).collect()
+    });
+
+    let mut tables: Vec<AcpiTableInfo> = acpi_tables.headers().map(AcpiTableInfo::from).collect();
+    // The DSDT isn't listed with the rest; the FADT points to it instead.
+    let dsdt = acpi_tables.dsdt().ok().zip(memory::physical_memory_offset()).map(|(dsdt, offset)| {
+        let header = offset + (dsdt.address - size_of::<SdtHeader>()) as u64;
+        // The acpi crate found a valid header here to find the DSDT at all.
+        unsafe { ptr::read_unaligned(header.as_ptr::<SdtHeader>()) }
+    });
+    tables.extend(dsdt.map(AcpiTableInfo::from));
+
+    MACHINE.call_once(|| Machine {
+        total_memory,
+        usable_memory,
+        boot_mode,
+        interrupt_model,
+        processors,
+        apic_ids,
+        memory_regions: memory_regions.to_vec(),
+        acpi_tables: tables,
+    });
+}
+
+/// Returns what was found out about the machine at boot, or `None` if [init] hasn't run.
+pub fn machine() -> Option<&'static Machine> {
+    MACHINE.get()
+}
+
+/// What the CPU says about itself.
+#[derive(Debug, Clone)]
+pub struct Cpu {
+    /// The manufacturer's ID, like `GenuineIntel` or `AuthenticAMD`.
+    pub vendor: String,
+    /// The model, like `QEMU Virtual CPU version 2.5+`, if the CPU has a brand string.
+    pub brand: Option<String>,
+    /// The family, model and stepping, with the extended family and model added in.
+    pub family: u32,
+    pub model: u32,
+    pub stepping: u32,
+    /// The ID of the local APIC of the processor we are running on, as it was at reset.
+    pub apic_id: u32,
+    /// How many bits physical and virtual addresses have, if the CPU says.
+    pub address_bits: Option<(u32, u32)>,
+    /// The ID of the hypervisor we are running under, like `TCGTCGTCGTCG` for QEMU, if there is one.
+    pub hypervisor: Option<String>,
+    /// The notable instruction set extensions it has, by their usual names.
+    pub features: Vec<&'static str>,
+}
+
+/// Features to look for: the `cpuid` leaf, the register (0 to 3 for eax, ebx, ecx and edx), the
+/// bit, and the name.
+const FEATURES: [(u32, usize, u32, &str); 27] = [
+    (0x1, 3, 0, "fpu"),
+    (0x1, 3, 4, "tsc"),
+    (0x1, 3, 5, "msr"),
+    (0x1, 3, 6, "pae"),
+    (0x1, 3, 9, "apic"),
+    (0x1, 3, 25, "sse"),
+    (0x1, 3, 26, "sse2"),
+    (0x1, 3, 28, "htt"),
+    (0x1, 2, 0, "sse3"),
+    (0x1, 2, 1, "pclmulqdq"),
+    (0x1, 2, 9, "ssse3"),
+    (0x1, 2, 12, "fma"),
+    (0x1, 2, 19, "sse4.1"),
+    (0x1, 2, 20, "sse4.2"),
+    (0x1, 2, 21, "x2apic"),
+    (0x1, 2, 23, "popcnt"),
+    (0x1, 2, 25, "aes"),
+    (0x1, 2, 28, "avx"),
+    (0x1, 2, 30, "rdrand"),
+    (0x1, 2, 31, "hypervisor"),
+    (0x7, 1, 3, "bmi1"),
+    (0x7, 1, 5, "avx2"),
+    (0x7, 1, 8, "bmi2"),
+    (0x7, 1, 16, "avx512f"),
+    (0x7, 1, 18, "rdseed"),
+    (0x8000_0001, 3, 20, "nx"),
+    (0x8000_0001, 3, 29, "lm"),
+];
+
+/// Runs `cpuid` for `leaf`, and returns eax, ebx, ecx and edx, or all zeroes if the CPU doesn't
+/// have that leaf.
+fn cpuid(leaf: u32) -> [u32; 4] {
+    let extended = leaf & 0x8000_0000;
+    // Every x86_64 CPU has `cpuid`, and the highest leaf of each range is always there.
+    let max = __cpuid(extended).eax;
+    if leaf > max {
+        return [0; 4];
+    }
+    let result = __cpuid(leaf);
+    [result.eax, result.ebx, result.ecx, result.edx]
+}
+
+/// Turns the 12 bytes of ID `cpuid` gives in three registers, like the vendor, into text.
+fn id_string(registers: [u32; 3]) -> String {
+    registers.iter().flat_map(|register| register.to_le_bytes()).map(char::from).collect()
+}
+
+/// Asks the CPU what it is, with `cpuid`.
+pub fn cpu() -> Cpu {
+    let [_, ebx, ecx, edx] = cpuid(0);
+    let vendor = id_string([ebx, edx, ecx]);
+
+    let [signature, ebx, _, _] = cpuid(1);
+    let base_family = (signature >> 8) & 0xf;
+    let mut family = base_family;
+    let mut model = (signature >> 4) & 0xf;
+    if base_family == 0xf {
+        family += (signature >> 20) & 0xff;
+    }
+    if base_family == 0x6 || base_family == 0xf {
+        model += ((signature >> 16) & 0xf) << 4;
+    }
+    let stepping = signature & 0xf;
+    let apic_id = ebx >> 24;
+
+    let address_bits = (cpuid(0x8000_0000)[0] >= 0x8000_0008).then(|| {
+        let sizes = cpuid(0x8000_0008)[0];
+        (sizes & 0xff, (sizes >> 8) & 0xff)
+    });
+
+    // Hypervisors put their ID at leaf 0x4000_0000, which is only there if the hypervisor bit is
+    // set, so it can't be checked for like the other ranges.
+    let hypervisor = (cpuid(1)[2] & (1 << 31) != 0).then(|| {
+        let result = __cpuid(0x4000_0000);
+        id_string([result.ebx, result.ecx, result.edx])
+    });
+
+    let brand = (cpuid(0x8000_0000)[0] >= 0x8000_0004).then(|| {
+        let bytes: Vec<u8> = (0x8000_0002..=0x8000_0004)
+            .flat_map(cpuid)
+            .flat_map(u32::to_le_bytes)
+            .take_while(|&byte| byte != 0)
+            .collect();
+        String::from_utf8_lossy(&bytes).trim().into()
+    });
+
+    let features = FEATURES
+        .iter()
+        .filter(|(leaf, register, bit, _)| cpuid(*leaf)[*register] & (1 << bit) != 0)
+        .map(|(_, _, _, name)| *name)
+        .collect();
+
+    Cpu { vendor, brand, family, model, stepping, apic_id, address_bits, hypervisor, features }
+}
+
+/// Returns the value of a section variable about the system, like `uptime`, or `None` if there is
+/// no such variable. [VARIABLES](crate::content::section::VARIABLES) lists them all.
+pub fn variable(name: &str) -> Option<String> {
+    let unknown = || String::from("unknown");
+    let value = match name {
+        "kernel" => format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        "uptime" => format_duration(time::uptime()),
+        "cpu" => {
+            let cpu = cpu();
+            cpu.brand.unwrap_or(cpu.vendor)
+        }
+        "cpu_features" => cpu().features.join(" "),
+        "cpus" => machine().map_or_else(unknown, |machine| format!("{}", machine.processors)),
+        "memory" => machine().map_or_else(unknown, |machine| {
+            format!("{} usable of {}", format_bytes(machine.usable_memory), format_bytes(machine.total_memory))
+        }),
+        "heap" => {
+            let used = allocator::heap_used();
+            let size = allocator::HEAP_SIZE;
+            format!("{} / {} ({}%)", format_bytes(used as u64), format_bytes(size as u64), used * 100 / size)
+        }
+        "display" => {
+            let info = with_writer(|writer| writer.info);
+            let format = match info.pixel_format {
+                PixelFormat::Rgb => "RGB",
+                PixelFormat::Bgr => "BGR",
+                PixelFormat::U8 => "grayscale",
+                _ => "unknown format",
+            };
+            format!("{}x{}, {format}, {} bits per pixel", info.width, info.height, info.bytes_per_pixel * 8)
+        }
+        "interrupts" => machine().map_or_else(unknown, |machine| machine.interrupt_model.clone()),
+        "boot" => machine().map_or_else(unknown, |machine| format!("{}", machine.boot_mode)),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Names the kind of a memory region in the bootloader's memory map, with the name the firmware
+/// gives it if the bootloader doesn't know it.
+pub fn region_kind_name(kind: MemoryRegionKind) -> String {
+    const UEFI: [&str; 15] = [
+        "reserved",
+        "UEFI loader code",
+        "UEFI loader data",
+        "UEFI boot services code",
+        "UEFI boot services data",
+        "UEFI runtime code",
+        "UEFI runtime data",
+        "conventional",
+        "unusable",
+        "ACPI reclaimable",
+        "ACPI NVS",
+        "memory-mapped I/O",
+        "memory-mapped port space",
+        "PAL code",
+        "persistent",
+    ];
+    const BIOS: [&str; 6] = ["", "usable", "reserved", "ACPI reclaimable", "ACPI NVS", "bad"];
+    match kind {
+        MemoryRegionKind::Usable => String::from("usable"),
+        MemoryRegionKind::Bootloader => String::from("bootloader"),
+        MemoryRegionKind::UnknownUefi(kind) => match UEFI.get(kind as usize) {
+            Some(name) => String::from(*name),
+            None => format!("UEFI type {kind}"),
+        },
+        MemoryRegionKind::UnknownBios(kind) => match BIOS.get(kind as usize) {
+            Some(name) if !name.is_empty() => String::from(*name),
+            _ => format!("E820 type {kind}"),
+        },
+        _ => String::from("unknown"),
+    }
+}
+
+/// Formats a number of bytes in the largest unit it has at least one of, like `1.5 GiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["bytes", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 || value >= 100.0 {
+        format!("{} {}", value as u64, UNITS[unit])
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Formats a duration like neofetch does, to the minute: `2 hours, 5 mins`. Under a minute, it
+/// is in seconds.
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds < 60 {
+        return format!("{seconds} secs");
+    }
+    let parts = [(seconds / 86_400, "day"), (seconds / 3600 % 24, "hour"), (seconds / 60 % 60, "min")];
+    let parts: Vec<String> = parts
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, unit)| format!("{count} {unit}{}", if *count == 1 { "" } else { "s" }))
+        .collect();
+    parts.join(", ")
+}
@@ -0,0 +1,105 @@
+use crate::interrupts::{timer_ticks, TIMER_INTERVAL_MS};
+use core::{fmt, time::Duration};
+use x86_64::instructions::{interrupts::without_interrupts, port::Port};
+
+/// CMOS register select port. Bit 7 also controls NMIs, which we leave enabled.
+const CMOS_ADDRESS: u16 = 0x70;
+/// CMOS data port.
+const CMOS_DATA: u16 = 0x71;
+
+/// A date and time, as kept by the real time clock. The RTC has no notion of time zones, so this
+/// is whatever the firmware was set to, which is usually UTC under an emulator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// Reads a CMOS register.
+fn read_cmos(register: u8) -> u8 {
+    let mut address = Port::<u8>::new(CMOS_ADDRESS);
+    let mut data = Port::<u8>::new(CMOS_DATA);
+    unsafe {
+        address.write(register);
+        data.read()
+    }
+}
+
+/// Returns true while the RTC is updating its registers, when they must not be read.
+fn update_in_progress() -> bool {
+    read_cmos(0x0A) & 0x80 != 0
+}
+
+/// Reads the raw time registers, in whatever format the RTC keeps them.
+fn read_raw() -> [u8; 6] {
+    while update_in_progress() {}
+    [
+        read_cmos(0x00),
+        read_cmos(0x02),
+        read_cmos(0x04),
+        read_cmos(0x07),
+        read_cmos(0x08),
+        read_cmos(0x09),
+    ]
+}
+
+/// Reads the current date and time from the CMOS real time clock.
+///
+/// The registers are read until two reads in a row agree, so an update that happens halfway
+/// through cannot give a torn result.
+pub fn now() -> DateTime {
+    let [second, minute, hour, day, month, year] = without_interrupts(|| {
+        let mut previous = read_raw();
+        loop {
+            let current = read_raw();
+            if current == previous {
+                return current;
+            }
+            previous = current;
+        }
+    });
+
+    let status_b = without_interrupts(|| read_cmos(0x0B));
+    let binary = status_b & 0x04 != 0;
+    let twenty_four_hour = status_b & 0x02 != 0;
+    let decode = |value: u8| if binary { value } else { (value & 0x0F) + (value >> 4) * 10 };
+
+    // In 12 hour mode, bit 7 of the hour is set for PM.
+    let pm = hour & 0x80 != 0;
+    let mut hour = decode(hour & 0x7F);
+    if !twenty_four_hour {
+        hour %= 12;
+        if pm {
+            hour += 12;
+        }
+    }
+
+    DateTime {
+        // The century register is not always there, so assume this one.
+        year: 2000 + decode(year) as u16,
+        month: decode(month),
+        day: decode(day),
+        hour,
+        minute: decode(minute),
+        second: decode(second),
+    }
+}
+
+/// Returns how long it has been since the timer was started, near the end of booting.
+pub fn uptime() -> Duration {
+    Duration::from_millis(timer_ticks() * TIMER_INTERVAL_MS)
+}
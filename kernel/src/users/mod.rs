@@ -0,0 +1,190 @@
+//! User accounts.
+//!
+//! The database lives in memory, like everything else, and starts out with `root` and, if
+//! [GUEST_ENABLED] is set, a `guest` account for visitors that needs no password.
+//!
+//! Users have no file permissions beyond their home directories: every file in the store can be
+//! read by anyone logged in, but only a home's owner and `root` can write to it, so no one can
+//! change what another user's `.ashrc` runs as them.
+mod password;
+
+use alloc::{
+    format,
+    string::String,
+    vec::Vec
+};
+use core::fmt;
+use password::PasswordHash;
+use spin::{Lazy, Mutex};
+
+/// Whether there is a `guest` account, so visitors can look around without a password.
+pub const GUEST_ENABLED: bool = true;
+
+/// The name of the guest account.
+pub const GUEST: &str = "guest";
+
+/// Root's password, unless another one is given in `PORTFOLIOS_ROOT_PASSWORD` at build time.
+/// Everyone can know it, so it has to be changed the first time root logs in.
+const DEFAULT_ROOT_PASSWORD: &str = "portfolios";
+
+/// The uid the first account after `root` gets.
+const FIRST_UID: u32 = 1000;
+
+/// A user account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    pub name: String,
+    pub uid: u32,
+    /// The user's home directory, where their shell keeps its files.
+    pub home: String,
+    /// `None` if logging in as the user needs no password.
+    password: Option<PasswordHash>,
+    /// Whether the password is one everyone can know, which must be changed before the user can
+    /// log in.
+    password_expired: bool,
+}
+
+impl User {
+    /// Returns true for `root`, who can do anything.
+    pub fn is_root(&self) -> bool {
+        self.uid == 0
+    }
+
+    /// Returns true if logging in as the user needs a password.
+    pub fn has_password(&self) -> bool {
+        self.password.is_some()
+    }
+
+    /// Returns true if `password` lets someone in as the user. Anything does, if the user has no
+    /// password.
+    pub fn check_password(&self, password: &str) -> bool {
+        self.password.as_ref().is_none_or(|hash| hash.verify(password))
+    }
+
+    /// Returns true if the user's password must be changed before they can log in.
+    pub fn password_expired(&self) -> bool {
+        self.password_expired
+    }
+}
+
+/// Why a change to the user database was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserError {
+    /// There is already a user by that name.
+    Exists,
+    /// There is no user by that name.
+    NotFound,
+    /// The name is not allowed: see [is_valid_name].
+    InvalidName,
+}
+
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserError::Exists => write!(f, "user already exists"),
+            UserError::NotFound => write!(f, "no such user"),
+            UserError::InvalidName => write!(f, "names are up to 32 lowercase letters, digits, - or _, starting with a letter"),
+        }
+    }
+}
+
+/// Returns true if `name` can be a user's name.
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    name.len() <= 32
+        && chars.next().is_some_and(|ch| ch.is_ascii_lowercase())
+        && chars.all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-' || ch == '_')
+}
+
+/// Every user account.
+pub struct UserDatabase {
+    users: Vec<User>,
+    /// What passwords given for names that aren't users are checked against, so a login takes
+    /// as long whether or not the name is a user's.
+    decoy: PasswordHash,
+}
+
+impl UserDatabase {
+    /// Creates the database with the accounts every boot starts with.
+    fn new() -> Self {
+        let mut database = Self { users: Vec::new(), decoy: PasswordHash::new(DEFAULT_ROOT_PASSWORD) };
+        let root_password = option_env!("PORTFOLIOS_ROOT_PASSWORD");
+        database.users.push(User {
+            name: String::from("root"),
+            uid: 0,
+            home: String::from("/root"),
+            password: Some(PasswordHash::new(root_password.unwrap_or(DEFAULT_ROOT_PASSWORD))),
+            password_expired: root_password.is_none(),
+        });
+        if GUEST_ENABLED {
+            let _ = database.add(GUEST, None);
+        }
+        database
+    }
+
+    /// Returns the user called `name`, if there is one.
+    pub fn find(&self, name: &str) -> Option<&User> {
+        self.users.iter().find(|user| user.name == name)
+    }
+
+    /// Adds a user, giving them the next free uid and a home under `/home`.
+    ///
+    /// ### params:
+    /// - `password`: their password, or `None` to let them log in without one.
+    pub fn add(&mut self, name: &str, password: Option<&str>) -> Result<&User, UserError> {
+        if !is_valid_name(name) {
+            return Err(UserError::InvalidName);
+        }
+        if self.find(name).is_some() {
+            return Err(UserError::Exists);
+        }
+        let uid = self.users.iter().map(|user| user.uid + 1).max().unwrap_or(0).max(FIRST_UID);
+        self.users.push(User {
+            name: String::from(name),
+            uid,
+            home: format!("/home/{name}"),
+            password: password.map(PasswordHash::new),
+            password_expired: false,
+        });
+        Ok(&self.users[self.users.len() - 1])
+    }
+
+    /// Hashes `password` as checking a user's password does, and throws the result away. For
+    /// logins as names that aren't users, so they take as long as those that are.
+    pub fn check_decoy(&self, password: &str) {
+        _ = self.decoy.verify(password);
+    }
+
+    /// Changes a user's password, or removes it if `password` is `None`.
+    pub fn set_password(&mut self, name: &str, password: Option<&str>) -> Result<(), UserError> {
+        let user = self.users.iter_mut().find(|user| user.name == name).ok_or(UserError::NotFound)?;
+        user.password = password.map(PasswordHash::new);
+        user.password_expired = false;
+        Ok(())
+    }
+
+    /// Returns an iterator over every user, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &User> {
+        self.users.iter()
+    }
+}
+
+/// The global user database, created the first time it is used, which must be after the heap
+/// has been initialised.
+static USERS: Lazy<Mutex<UserDatabase>> = Lazy::new(|| Mutex::new(UserDatabase::new()));
+
+/// Runs `f` with the global user database.
+pub fn with_users<R>(f: impl FnOnce(&mut UserDatabase) -> R) -> R {
+    f(&mut USERS.lock())
+}
+
+/// Returns the uid of the user whose home directory `path` is in, if it is in one.
+pub fn home_owner(path: &str) -> Option<u32> {
+    let is_in = |home: &str| path.strip_prefix(home).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+    with_users(|users| users.iter().find(|user| is_in(&user.home)).map(|user| user.uid))
+}
+
+/// Returns a copy of the user called `name`, if there is one.
+pub fn find(name: &str) -> Option<User> {
+    with_users(|users| users.find(name).cloned())
+}
@@ -0,0 +1,54 @@
+use core::arch::x86_64::_rdtsc;
+use sha2::Sha256;
+use x86_64::instructions::random::RdRand;
+
+/// PBKDF2 rounds for new passwords. Kept low enough that logging in is not a chore in an
+/// unoptimised build.
+const ITERATIONS: u32 = 10_000;
+
+/// A password, hashed with PBKDF2-HMAC-SHA256 and a random salt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordHash {
+    salt: [u8; 16],
+    /// Kept with the hash, so raising [ITERATIONS] does not break existing passwords.
+    iterations: u32,
+    hash: [u8; 32],
+}
+
+impl PasswordHash {
+    /// Hashes `password` with a fresh salt.
+    pub fn new(password: &str) -> Self {
+        let salt = random_salt();
+        Self {
+            salt,
+            iterations: ITERATIONS,
+            hash: derive(password, &salt, ITERATIONS),
+        }
+    }
+
+    /// Returns true if `password` is the one this is a hash of.
+    pub fn verify(&self, password: &str) -> bool {
+        let hash = derive(password, &self.salt, self.iterations);
+        // Compare every byte, so the time taken says nothing about how much of it matched.
+        hash.iter().zip(self.hash.iter()).fold(0, |difference, (a, b)| difference | (a ^ b)) == 0
+    }
+}
+
+fn derive(password: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut hash = [0; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut hash);
+    hash
+}
+
+/// Returns 16 random bytes, from `RDRAND` if the CPU has it, or the time stamp counter if not.
+fn random_salt() -> [u8; 16] {
+    let rdrand = RdRand::new();
+    let mut salt = [0; 16];
+    for chunk in salt.chunks_mut(8) {
+        let value = rdrand
+            .and_then(|rdrand| rdrand.get_u64())
+            .unwrap_or_else(|| unsafe { _rdtsc() }.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        chunk.copy_from_slice(&value.to_le_bytes());
+    }
+    salt
+}